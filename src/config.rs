@@ -2,12 +2,13 @@ use crate::configs::StarshipRootConfig;
 use crate::utils;
 use ansi_term::{Color, Style};
 use indexmap::IndexMap;
+use serde::Serialize;
 
 use std::clone::Clone;
 use std::collections::HashMap;
 use std::marker::Sized;
 
-use std::env;
+use std::ffi::OsStr;
 use toml::Value;
 
 /// Root config of a module.
@@ -174,7 +175,7 @@ where
 
 /// A wrapper around `Vec<T>` that implements `ModuleConfig`, and either
 /// accepts a value of type `T` or a list of values of type `T`.
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct VecOr<T>(pub Vec<T>);
 
 impl<'a, T> ModuleConfig<'a> for VecOr<T>
@@ -202,9 +203,10 @@ pub struct StarshipConfig {
 }
 
 impl StarshipConfig {
-    /// Initialize the Config struct
-    pub fn initialize() -> Self {
-        if let Some(file_data) = Self::config_from_file() {
+    /// Initialize the Config struct from the file at `config_file_path`, or from
+    /// `~/.config/starship.toml` if no path is given
+    pub fn initialize(config_file_path: Option<&OsStr>) -> Self {
+        if let Some(file_data) = Self::config_from_file(config_file_path) {
             StarshipConfig {
                 config: Some(file_data),
             }
@@ -216,30 +218,34 @@ impl StarshipConfig {
     }
 
     /// Create a config from a starship configuration file
-    fn config_from_file() -> Option<Value> {
-        let file_path = if let Ok(path) = env::var("STARSHIP_CONFIG") {
-            // Use $STARSHIP_CONFIG as the config path if available
-            log::debug!("STARSHIP_CONFIG is set: {}", &path);
-            path
+    fn config_from_file(config_file_path: Option<&OsStr>) -> Option<Value> {
+        let toml_content = if let Some(file_path) = config_file_path {
+            // A config file that was asked for explicitly should exist
+            log::debug!("Using config path: {:?}", file_path);
+            match utils::read_file(file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::warn!(
+                        "Unable to read config file {:?}, using the default config: {}",
+                        file_path,
+                        &e
+                    );
+                    return None;
+                }
+            }
         } else {
             // Default to using ~/.config/starship.toml
-            log::debug!("STARSHIP_CONFIG is not set");
-            let config_path = dirs_next::home_dir()?.join(".config/starship.toml");
-            let config_path_str = config_path.to_str()?.to_owned();
-            log::debug!("Using default config path: {}", config_path_str);
-            config_path_str
-        };
-
-        let toml_content = match utils::read_file(&file_path) {
-            Ok(content) => {
-                log::trace!("Config file content: \"\n{}\"", &content);
-                Some(content)
-            }
-            Err(e) => {
-                log::debug!("Unable to read config file content: {}", &e);
-                None
+            let file_path = dirs_next::home_dir()?.join(".config/starship.toml");
+            log::debug!("Using default config path: {:?}", file_path);
+            match utils::read_file(&file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::debug!("Unable to read config file content: {}", &e);
+                    return None;
+                }
             }
-        }?;
+        };
+        log::trace!("Config file content: \"\n{}\"", &toml_content);
 
         match toml::from_str(&toml_content) {
             Ok(parsed) => {
@@ -330,6 +336,24 @@ impl StarshipConfig {
         module_config
     }
 
+    /// Get the subset of the table for an `env_var` module by its name
+    pub fn get_env_var_module_config(&self, module_name: &str) -> Option<&Value> {
+        self.get_config(&["env_var", module_name])
+            .filter(|config| config.is_table())
+    }
+
+    /// Get the names of the `env_var` modules configured as sub-tables of `[env_var]`
+    pub fn get_env_var_modules(&self) -> Vec<&str> {
+        match self.get_config(&["env_var"]).and_then(Value::as_table) {
+            Some(table) => table
+                .iter()
+                .filter(|(_, config)| config.is_table())
+                .map(|(name, _)| name.as_str())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
     /// Get the table of all the registered custom modules, if any
     pub fn get_custom_modules(&self) -> Option<&toml::value::Table> {
         self.get_config(&["custom"])?.as_table()
@@ -351,57 +375,76 @@ impl StarshipConfig {
  - 'underline'
  - 'bold'
  - 'italic'
+ - 'dimmed'
+ - 'inverted'
+ - 'strikethrough'
+ - 'blink'
+ - 'hidden'
+ - 'none'          (yields no style at all, unless prefixed with 'bg:')
  - '<color>'        (see the parse_color_string doc for valid color strings)
 */
 pub fn parse_style_string(style_string: &str) -> Option<ansi_term::Style> {
+    parse_style_string_with_base(style_string, ansi_term::Style::new())
+}
+
+/** Parse a style string on top of an existing style. Attributes and colors
+ that the style string doesn't mention are kept from `base_style`, while a
+ `none` token still yields no style at all.
+*/
+pub fn parse_style_string_with_base(
+    style_string: &str,
+    base_style: ansi_term::Style,
+) -> Option<ansi_term::Style> {
     style_string
         .split_whitespace()
-        .fold(Some(ansi_term::Style::new()), |maybe_style, token| {
-            maybe_style.and_then(|style| {
-                let token = token.to_lowercase();
-
-                // Check for FG/BG identifiers and strip them off if appropriate
-                // If col_fg is true, color the foreground. If it's false, color the background.
-                let (token, col_fg) = if token.as_str().starts_with("fg:") {
-                    (token.trim_start_matches("fg:").to_owned(), true)
-                } else if token.as_str().starts_with("bg:") {
-                    (token.trim_start_matches("bg:").to_owned(), false)
-                } else {
-                    (token, true) // Bare colors are assumed to color the foreground
-                };
-
-                match token.as_str() {
-                    "underline" => Some(style.underline()),
-                    "bold" => Some(style.bold()),
-                    "italic" => Some(style.italic()),
-                    "dimmed" => Some(style.dimmed()),
-                    // When the string is supposed to be a color:
-                    // Decide if we yield none, reset background or set color.
-                    color_string => {
-                        if color_string == "none" && col_fg {
-                            None // fg:none yields no style.
+        .try_fold(base_style, |style, token| {
+            let token = token.to_lowercase();
+
+            // Check for FG/BG identifiers and strip them off if appropriate
+            // If col_fg is true, color the foreground. If it's false, color the background.
+            let (token, col_fg) = if token.as_str().starts_with("fg:") {
+                (token.trim_start_matches("fg:").to_owned(), true)
+            } else if token.as_str().starts_with("bg:") {
+                (token.trim_start_matches("bg:").to_owned(), false)
+            } else {
+                (token, true) // Bare colors are assumed to color the foreground
+            };
+
+            match token.as_str() {
+                "underline" => Some(style.underline()),
+                "bold" => Some(style.bold()),
+                "italic" => Some(style.italic()),
+                "dimmed" => Some(style.dimmed()),
+                "inverted" => Some(style.reverse()),
+                "strikethrough" => Some(style.strikethrough()),
+                "blink" => Some(style.blink()),
+                "hidden" => Some(style.hidden()),
+                // When the string is supposed to be a color:
+                // Decide if we yield none, reset background or set color.
+                color_string => {
+                    if color_string == "none" && col_fg {
+                        None // fg:none yields no style.
+                    } else {
+                        // Either bg or valid color or both.
+                        let parsed = parse_color_string(color_string);
+                        // bg + invalid color = reset the background to default.
+                        if !col_fg && parsed.is_none() {
+                            let mut new_style = style;
+                            new_style.background = Option::None;
+                            Some(new_style)
                         } else {
-                            // Either bg or valid color or both.
-                            let parsed = parse_color_string(color_string);
-                            // bg + invalid color = reset the background to default.
-                            if !col_fg && parsed.is_none() {
-                                let mut new_style = style;
-                                new_style.background = Option::None;
-                                Some(new_style)
-                            } else {
-                                // Valid color, apply color to either bg or fg
-                                parsed.map(|ansi_color| {
-                                    if col_fg {
-                                        style.fg(ansi_color)
-                                    } else {
-                                        style.on(ansi_color)
-                                    }
-                                })
-                            }
+                            // Valid color, apply color to either bg or fg
+                            parsed.map(|ansi_color| {
+                                if col_fg {
+                                    style.fg(ansi_color)
+                                } else {
+                                    style.on(ansi_color)
+                                }
+                            })
                         }
                     }
                 }
-            })
+            }
         })
 }
 
@@ -414,20 +457,17 @@ pub fn parse_style_string(style_string: &str) -> Option<ansi_term::Style> {
 fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
     // Parse RGB hex values
     log::trace!("Parsing color_string: {}", color_string);
-    if color_string.starts_with('#') {
+    if let Some(hex) = color_string.strip_prefix('#') {
         log::trace!(
             "Attempting to read hexadecimal color string: {}",
             color_string
         );
-        if color_string.len() != 7 {
-            log::debug!("Could not parse hexadecimal string: {}", color_string);
-            return None;
+        let rgb = parse_hex_color(hex);
+        match rgb {
+            Some(Color::RGB(r, g, b)) => log::trace!("Read RGB color string: {},{},{}", r, g, b),
+            _ => log::warn!("Could not parse hexadecimal color: {}", color_string),
         }
-        let r: u8 = u8::from_str_radix(&color_string[1..3], 16).ok()?;
-        let g: u8 = u8::from_str_radix(&color_string[3..5], 16).ok()?;
-        let b: u8 = u8::from_str_radix(&color_string[5..7], 16).ok()?;
-        log::trace!("Read RGB color string: {},{},{}", r, g, b);
-        return Some(Color::RGB(r, g, b));
+        return rgb;
     }
 
     // Parse a u8 (ansi color)
@@ -466,6 +506,17 @@ fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
     predefined_color
 }
 
+/// Parse the `RRGGBB` part of a `#RRGGBB` color string
+fn parse_hex_color(hex: &str) -> Option<ansi_term::Color> {
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let r: u8 = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g: u8 = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b: u8 = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some(Color::RGB(r, g, b))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -648,6 +699,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_fixed_color_style() {
+        let config = Value::from("0");
+        assert_eq!(
+            <Style>::from_config(&config).unwrap(),
+            Color::Fixed(0).into()
+        );
+
+        let config = Value::from("255");
+        assert_eq!(
+            <Style>::from_config(&config).unwrap(),
+            Color::Fixed(255).into()
+        );
+
+        let config = Value::from("256");
+        assert_eq!(<Style>::from_config(&config), None);
+
+        let config = Value::from("-1");
+        assert_eq!(<Style>::from_config(&config), None);
+    }
+
+    #[test]
+    fn test_from_bg_color_style() {
+        let config = Value::from("bg:#FF8800");
+        assert_eq!(
+            <Style>::from_config(&config).unwrap(),
+            Style::new().on(Color::RGB(0xFF, 0x88, 0x00))
+        );
+
+        let config = Value::from("bg:17");
+        assert_eq!(
+            <Style>::from_config(&config).unwrap(),
+            Style::new().on(Color::Fixed(17))
+        );
+
+        let config = Value::from("bold fg:#ff8800 bg:17");
+        assert_eq!(
+            <Style>::from_config(&config).unwrap(),
+            Color::RGB(0xFF, 0x88, 0x00).bold().on(Color::Fixed(17))
+        );
+    }
+
+    #[test]
+    fn test_from_invalid_hex_color_style_falls_back() {
+        // A multi-byte string of the right length must not panic when sliced
+        let config = Value::from("#fé888");
+        assert_eq!(<Style>::from_config(&config), None);
+
+        let config = Value::from("bold #ff88zz bg:17");
+        assert_eq!(<Style>::from_config(&config), None);
+    }
+
     #[test]
     fn test_from_vec() {
         let config: Value = Value::Array(vec![Value::from("S")]);
@@ -679,6 +782,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn table_get_styles_inverted_strikethrough_blink_hidden() {
+        let config = Value::from("inverted strikethrough blink hidden");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert!(mystyle.is_reverse);
+        assert!(mystyle.is_strikethrough);
+        assert!(mystyle.is_blink);
+        assert!(mystyle.is_hidden);
+        assert_eq!(
+            mystyle,
+            Style::new().reverse().strikethrough().blink().hidden()
+        );
+    }
+
+    #[test]
+    fn table_get_styles_ansi_sequences() {
+        let config = Value::from("italic dimmed yellow");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert_eq!(mystyle.paint("x").to_string(), "\u{1b}[2;3;33mx\u{1b}[0m");
+
+        let config = Value::from("underline inverted bg:blue");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert_eq!(mystyle.paint("x").to_string(), "\u{1b}[4;7;44mx\u{1b}[0m");
+
+        let config = Value::from("strikethrough blink 120");
+        let mystyle = <Style>::from_config(&config).unwrap();
+        assert_eq!(
+            mystyle.paint("x").to_string(),
+            "\u{1b}[5;9;38;5;120mx\u{1b}[0m"
+        );
+    }
+
     #[test]
     fn table_get_styles_plain_and_broken_styles() {
         // Test a "plain" style with no formatting
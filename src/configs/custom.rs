@@ -1,17 +1,18 @@
 use crate::config::{ModuleConfig, RootModuleConfig, VecOr};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Serialize)]
 pub struct Files<'a>(pub Vec<&'a str>);
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Serialize)]
 pub struct Extensions<'a>(pub Vec<&'a str>);
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Serialize)]
 pub struct Directories<'a>(pub Vec<&'a str>);
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct CustomConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
@@ -21,9 +22,10 @@ pub struct CustomConfig<'a> {
     pub description: &'a str,
     pub style: &'a str,
     pub disabled: bool,
-    pub files: Files<'a>,
-    pub extensions: Extensions<'a>,
-    pub directories: Directories<'a>,
+    pub line_separator: Option<&'a str>,
+    pub detect_files: Files<'a>,
+    pub detect_extensions: Extensions<'a>,
+    pub detect_folders: Directories<'a>,
 }
 
 impl<'a> RootModuleConfig<'a> for CustomConfig<'a> {
@@ -37,9 +39,10 @@ impl<'a> RootModuleConfig<'a> for CustomConfig<'a> {
             description: "<custom config>",
             style: "green bold",
             disabled: false,
-            files: Files::default(),
-            extensions: Extensions::default(),
-            directories: Directories::default(),
+            line_separator: None,
+            detect_files: Files::default(),
+            detect_extensions: Extensions::default(),
+            detect_folders: Directories::default(),
         }
     }
 }
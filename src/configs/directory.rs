@@ -1,9 +1,10 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 use indexmap::IndexMap;
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct DirectoryConfig<'a> {
     pub truncation_length: i64,
     pub truncate_to_repo: bool,
@@ -16,6 +17,9 @@ pub struct DirectoryConfig<'a> {
     pub read_only: &'a str,
     pub read_only_style: &'a str,
     pub truncation_symbol: &'a str,
+    pub repo_root_style: Option<&'a str>,
+    pub home_symbol: &'a str,
+    pub contract_other_users_home: bool,
 }
 
 impl<'a> RootModuleConfig<'a> for DirectoryConfig<'a> {
@@ -32,6 +36,9 @@ impl<'a> RootModuleConfig<'a> for DirectoryConfig<'a> {
             read_only: "🔒",
             read_only_style: "red",
             truncation_symbol: "",
+            repo_root_style: None,
+            home_symbol: "~",
+            contract_other_users_home: false,
         }
     }
 }
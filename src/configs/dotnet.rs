@@ -1,10 +1,12 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct DotnetConfig<'a> {
     pub format: &'a str,
+    pub version_format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
     pub heuristic: bool,
@@ -15,6 +17,7 @@ impl<'a> RootModuleConfig<'a> for DotnetConfig<'a> {
     fn new() -> Self {
         DotnetConfig {
             format: "[$symbol$version( 🎯 $tfm)]($style) ",
+            version_format: "v${raw}",
             symbol: "•NET ",
             style: "blue bold",
             heuristic: true,
@@ -0,0 +1,21 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct FillConfig<'a> {
+    pub style: &'a str,
+    pub symbol: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for FillConfig<'a> {
+    fn new() -> Self {
+        FillConfig {
+            style: "bold black",
+            symbol: " ",
+            disabled: false,
+        }
+    }
+}
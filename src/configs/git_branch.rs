@@ -1,12 +1,16 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use indexmap::IndexMap;
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitBranchConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub branch_styles: IndexMap<String, &'a str>,
+    pub remote_symbols: IndexMap<String, &'a str>,
     pub truncation_length: i64,
     pub truncation_symbol: &'a str,
     pub only_attached: bool,
@@ -20,6 +24,8 @@ impl<'a> RootModuleConfig<'a> for GitBranchConfig<'a> {
             format: "on [$symbol$branch]($style)(:[$remote]($style)) ",
             symbol: " ",
             style: "bold purple",
+            branch_styles: IndexMap::new(),
+            remote_symbols: IndexMap::new(),
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
             only_attached: false,
@@ -1,8 +1,9 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitCommitConfig<'a> {
     pub commit_hash_length: usize,
     pub format: &'a str,
@@ -11,6 +12,13 @@ pub struct GitCommitConfig<'a> {
     pub disabled: bool,
     pub tag_symbol: &'a str,
     pub tag_disabled: bool,
+    pub describe_disabled: bool,
+    pub describe_tags: bool,
+    pub describe_match: &'a str,
+    pub describe_dirty_suffix: &'a str,
+    pub show_signature: bool,
+    pub signed_symbol: &'a str,
+    pub unsigned_symbol: &'a str,
 }
 
 impl<'a> RootModuleConfig<'a> for GitCommitConfig<'a> {
@@ -18,12 +26,19 @@ impl<'a> RootModuleConfig<'a> for GitCommitConfig<'a> {
         GitCommitConfig {
             // be consistent with git by default, which has DEFAULT_ABBREV set to 7
             commit_hash_length: 7,
-            format: "[\\($hash$tag\\)]($style) ",
+            format: "[\\($hash$tag$signature\\)]($style) ",
             style: "green bold",
             only_detached: true,
             disabled: false,
             tag_symbol: "🏷  ",
             tag_disabled: true,
+            describe_disabled: true,
+            describe_tags: true,
+            describe_match: "",
+            describe_dirty_suffix: "",
+            show_signature: false,
+            signed_symbol: "✓",
+            unsigned_symbol: "✗",
         }
     }
 }
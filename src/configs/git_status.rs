@@ -1,39 +1,76 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitStatusConfig<'a> {
     pub format: &'a str,
     pub style: &'a str,
+    pub separator: &'a str,
     pub stashed: &'a str,
     pub ahead: &'a str,
     pub behind: &'a str,
     pub diverged: &'a str,
+    pub up_to_date: &'a str,
+    pub upstream_gone: &'a str,
+    pub shallow_symbol: &'a str,
+    pub show_ahead_behind_when_shallow: bool,
     pub conflicted: &'a str,
     pub deleted: &'a str,
+    pub worktree_deleted: Option<&'a str>,
     pub renamed: &'a str,
     pub modified: &'a str,
+    pub typechanged: &'a str,
     pub staged: &'a str,
     pub untracked: &'a str,
+    pub ignored: &'a str,
+    pub submodule: &'a str,
+    pub untracked_files: Option<&'a str>,
+    pub count_mode: &'a str,
+    pub update_index: bool,
+    pub ignore_submodules: &'a str,
+    pub max_index_size: Option<u64>,
+    pub large_repo_symbol: &'a str,
+    pub use_cache: bool,
+    pub async_timeout_ms: Option<u64>,
+    pub loading_symbol: &'a str,
     pub disabled: bool,
 }
 
 impl<'a> RootModuleConfig<'a> for GitStatusConfig<'a> {
     fn new() -> Self {
         GitStatusConfig {
-            format: "([\\[$all_status$ahead_behind\\]]($style) )",
+            format: "([\\[$all_status$ahead_behind$upstream_gone$is_shallow\\]]($style) )",
             style: "red bold",
+            separator: "",
             stashed: "\\$",
             ahead: "⇡",
             behind: "⇣",
             diverged: "⇕",
+            up_to_date: "",
+            upstream_gone: "✗",
+            shallow_symbol: "",
+            show_ahead_behind_when_shallow: false,
             conflicted: "=",
             deleted: "✘",
+            worktree_deleted: None,
             renamed: "»",
             modified: "!",
+            typechanged: "⇢",
             staged: "+",
             untracked: "?",
+            ignored: "",
+            submodule: "",
+            untracked_files: None,
+            count_mode: "changes",
+            update_index: false,
+            ignore_submodules: "none",
+            max_index_size: None,
+            large_repo_symbol: "",
+            use_cache: false,
+            async_timeout_ms: None,
+            loading_symbol: "[\\[…\\]]($style) ",
             disabled: false,
         }
     }
@@ -1,22 +1,41 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct GoConfig<'a> {
     pub format: &'a str,
+    pub version_format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
     pub disabled: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    pub not_capable_style: &'a str,
 }
 
 impl<'a> RootModuleConfig<'a> for GoConfig<'a> {
     fn new() -> Self {
         GoConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: "v${raw}",
             symbol: "🐹 ",
             style: "bold cyan",
             disabled: false,
+            detect_extensions: vec!["go"],
+            detect_files: vec![
+                "go.mod",
+                "go.sum",
+                "go.work",
+                "glide.yaml",
+                "Gopkg.yml",
+                "Gopkg.lock",
+                ".go-version",
+            ],
+            detect_folders: vec!["Godeps"],
+            not_capable_style: "bold red",
         }
     }
 }
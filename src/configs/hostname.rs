@@ -1,11 +1,13 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct HostnameConfig<'a> {
     pub ssh_only: bool,
     pub trim_at: &'a str,
+    pub ssh_symbol: &'a str,
     pub format: &'a str,
     pub style: &'a str,
     pub disabled: bool,
@@ -16,7 +18,8 @@ impl<'a> RootModuleConfig<'a> for HostnameConfig<'a> {
         HostnameConfig {
             ssh_only: true,
             trim_at: ".",
-            format: "[$hostname]($style) in ",
+            ssh_symbol: "🌐 ",
+            format: "[$ssh_symbol$hostname]($style) in ",
             style: "green dimmed bold",
             disabled: false,
         }
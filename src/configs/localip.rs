@@ -0,0 +1,25 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct LocalipConfig<'a> {
+    pub ssh_only: bool,
+    pub probe_address: &'a str,
+    pub format: &'a str,
+    pub style: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for LocalipConfig<'a> {
+    fn new() -> Self {
+        LocalipConfig {
+            ssh_only: true,
+            probe_address: "8.8.8.8:53",
+            format: "[$localipv4]($style)",
+            style: "yellow bold",
+            disabled: true,
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use crate::config::RootModuleConfig;
+use indexmap::IndexMap;
+use serde::Serialize;
+use toml::Value;
+
 pub mod aws;
 pub mod battery;
 pub mod character;
@@ -14,9 +19,11 @@ pub mod elixir;
 pub mod elm;
 pub mod env_var;
 pub mod erlang;
+pub mod fill;
 pub mod gcloud;
 pub mod git_branch;
 pub mod git_commit;
+pub mod git_metrics;
 pub mod git_state;
 pub mod git_status;
 pub mod go;
@@ -27,6 +34,7 @@ pub mod java;
 pub mod jobs;
 pub mod julia;
 pub mod kubernetes;
+pub mod localip;
 pub mod lua;
 pub mod memory_usage;
 pub mod nim;
@@ -34,6 +42,7 @@ pub mod nix_shell;
 pub mod nodejs;
 pub mod ocaml;
 pub mod openstack;
+pub mod os;
 pub mod package;
 pub mod perl;
 pub mod php;
@@ -41,10 +50,12 @@ pub mod purescript;
 pub mod python;
 pub mod ruby;
 pub mod rust;
+pub mod shell;
 pub mod shlvl;
 pub mod singularity;
 mod starship_root;
 pub mod status;
+pub mod sudo;
 pub mod swift;
 pub mod terraform;
 pub mod time;
@@ -52,3 +63,202 @@ pub mod username;
 pub mod zig;
 
 pub use starship_root::*;
+
+/// The whole configuration: the root config and the config of every module, each with the
+/// user's values layered over its defaults
+#[derive(Clone, Serialize)]
+pub struct FullConfig<'a> {
+    #[serde(flatten)]
+    pub root: StarshipRootConfig<'a>,
+    pub aws: aws::AwsConfig<'a>,
+    pub battery: battery::BatteryConfig<'a>,
+    pub character: character::CharacterConfig<'a>,
+    pub cmake: cmake::CMakeConfig<'a>,
+    pub cmd_duration: cmd_duration::CmdDurationConfig<'a>,
+    pub conda: conda::CondaConfig<'a>,
+    pub crystal: crystal::CrystalConfig<'a>,
+    pub dart: dart::DartConfig<'a>,
+    pub directory: directory::DirectoryConfig<'a>,
+    pub docker_context: docker_context::DockerContextConfig<'a>,
+    pub dotnet: dotnet::DotnetConfig<'a>,
+    pub elixir: elixir::ElixirConfig<'a>,
+    pub elm: elm::ElmConfig<'a>,
+    pub env_var: EnvVarModulesConfig<'a>,
+    pub erlang: erlang::ErlangConfig<'a>,
+    pub fill: fill::FillConfig<'a>,
+    pub gcloud: gcloud::GcloudConfig<'a>,
+    pub git_branch: git_branch::GitBranchConfig<'a>,
+    pub git_commit: git_commit::GitCommitConfig<'a>,
+    pub git_metrics: git_metrics::GitMetricsConfig<'a>,
+    pub git_state: git_state::GitStateConfig<'a>,
+    pub git_status: git_status::GitStatusConfig<'a>,
+    pub golang: go::GoConfig<'a>,
+    pub helm: helm::HelmConfig<'a>,
+    pub hg_branch: hg_branch::HgBranchConfig<'a>,
+    pub hostname: hostname::HostnameConfig<'a>,
+    pub java: java::JavaConfig<'a>,
+    pub jobs: jobs::JobsConfig<'a>,
+    pub julia: julia::JuliaConfig<'a>,
+    pub kubernetes: kubernetes::KubernetesConfig<'a>,
+    pub localip: localip::LocalipConfig<'a>,
+    pub lua: lua::LuaConfig<'a>,
+    pub memory_usage: memory_usage::MemoryConfig<'a>,
+    pub nim: nim::NimConfig<'a>,
+    pub nix_shell: nix_shell::NixShellConfig<'a>,
+    pub nodejs: nodejs::NodejsConfig<'a>,
+    pub ocaml: ocaml::OCamlConfig<'a>,
+    pub openstack: openstack::OspConfig<'a>,
+    pub os: os::OsConfig<'a>,
+    pub package: package::PackageConfig<'a>,
+    pub perl: perl::PerlConfig<'a>,
+    pub php: php::PhpConfig<'a>,
+    pub purescript: purescript::PureScriptConfig<'a>,
+    pub python: python::PythonConfig<'a>,
+    pub ruby: ruby::RubyConfig<'a>,
+    pub rust: rust::RustConfig<'a>,
+    pub shell: shell::ShellConfig<'a>,
+    pub shlvl: shlvl::ShLvlConfig<'a>,
+    pub singularity: singularity::SingularityConfig<'a>,
+    pub status: status::StatusConfig<'a>,
+    pub sudo: sudo::SudoConfig<'a>,
+    pub swift: swift::SwiftConfig<'a>,
+    pub terraform: terraform::TerraformConfig<'a>,
+    pub time: time::TimeConfig<'a>,
+    pub username: username::UsernameConfig<'a>,
+    pub zig: zig::ZigConfig<'a>,
+    pub custom: IndexMap<String, custom::CustomConfig<'a>>,
+}
+
+impl<'a> FullConfig<'a> {
+    /// Loads the full config from the user's config, or the defaults if `config` is `None`
+    pub fn load(config: Option<&'a Value>) -> Self {
+        let module = |name: &str| config.and_then(|config| config.get(name));
+
+        FullConfig {
+            root: StarshipRootConfig::try_load(config),
+            aws: aws::AwsConfig::try_load(module("aws")),
+            battery: battery::BatteryConfig::try_load(module("battery")),
+            character: character::CharacterConfig::try_load(module("character")),
+            cmake: cmake::CMakeConfig::try_load(module("cmake")),
+            cmd_duration: cmd_duration::CmdDurationConfig::try_load(module("cmd_duration")),
+            conda: conda::CondaConfig::try_load(module("conda")),
+            crystal: crystal::CrystalConfig::try_load(module("crystal")),
+            dart: dart::DartConfig::try_load(module("dart")),
+            directory: directory::DirectoryConfig::try_load(module("directory")),
+            docker_context: docker_context::DockerContextConfig::try_load(module("docker_context")),
+            dotnet: dotnet::DotnetConfig::try_load(module("dotnet")),
+            elixir: elixir::ElixirConfig::try_load(module("elixir")),
+            elm: elm::ElmConfig::try_load(module("elm")),
+            env_var: EnvVarModulesConfig::load(module("env_var")),
+            erlang: erlang::ErlangConfig::try_load(module("erlang")),
+            fill: fill::FillConfig::try_load(module("fill")),
+            gcloud: gcloud::GcloudConfig::try_load(module("gcloud")),
+            git_branch: git_branch::GitBranchConfig::try_load(module("git_branch")),
+            git_commit: git_commit::GitCommitConfig::try_load(module("git_commit")),
+            git_metrics: git_metrics::GitMetricsConfig::try_load(module("git_metrics")),
+            git_state: git_state::GitStateConfig::try_load(module("git_state")),
+            git_status: git_status::GitStatusConfig::try_load(module("git_status")),
+            golang: go::GoConfig::try_load(module("golang")),
+            helm: helm::HelmConfig::try_load(module("helm")),
+            hg_branch: hg_branch::HgBranchConfig::try_load(module("hg_branch")),
+            hostname: hostname::HostnameConfig::try_load(module("hostname")),
+            java: java::JavaConfig::try_load(module("java")),
+            jobs: jobs::JobsConfig::try_load(module("jobs")),
+            julia: julia::JuliaConfig::try_load(module("julia")),
+            kubernetes: kubernetes::KubernetesConfig::try_load(module("kubernetes")),
+            localip: localip::LocalipConfig::try_load(module("localip")),
+            lua: lua::LuaConfig::try_load(module("lua")),
+            memory_usage: memory_usage::MemoryConfig::try_load(module("memory_usage")),
+            nim: nim::NimConfig::try_load(module("nim")),
+            nix_shell: nix_shell::NixShellConfig::try_load(module("nix_shell")),
+            nodejs: nodejs::NodejsConfig::try_load(module("nodejs")),
+            ocaml: ocaml::OCamlConfig::try_load(module("ocaml")),
+            openstack: openstack::OspConfig::try_load(module("openstack")),
+            os: os::OsConfig::try_load(module("os")),
+            package: package::PackageConfig::try_load(module("package")),
+            perl: perl::PerlConfig::try_load(module("perl")),
+            php: php::PhpConfig::try_load(module("php")),
+            purescript: purescript::PureScriptConfig::try_load(module("purescript")),
+            python: python::PythonConfig::try_load(module("python")),
+            ruby: ruby::RubyConfig::try_load(module("ruby")),
+            rust: rust::RustConfig::try_load(module("rust")),
+            shell: shell::ShellConfig::try_load(module("shell")),
+            shlvl: shlvl::ShLvlConfig::try_load(module("shlvl")),
+            singularity: singularity::SingularityConfig::try_load(module("singularity")),
+            status: status::StatusConfig::try_load(module("status")),
+            sudo: sudo::SudoConfig::try_load(module("sudo")),
+            swift: swift::SwiftConfig::try_load(module("swift")),
+            terraform: terraform::TerraformConfig::try_load(module("terraform")),
+            time: time::TimeConfig::try_load(module("time")),
+            username: username::UsernameConfig::try_load(module("username")),
+            zig: zig::ZigConfig::try_load(module("zig")),
+            custom: module("custom")
+                .and_then(Value::as_table)
+                .map(|modules| {
+                    modules
+                        .iter()
+                        .map(|(name, config)| (name.clone(), custom::CustomConfig::load(config)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The options set directly under `[env_var]`, along with the `[env_var.<name>]` modules
+#[derive(Clone, Serialize)]
+pub struct EnvVarModulesConfig<'a> {
+    #[serde(flatten)]
+    pub config: env_var::EnvVarConfig<'a>,
+    #[serde(flatten)]
+    pub modules: IndexMap<String, env_var::EnvVarConfig<'a>>,
+}
+
+impl<'a> EnvVarModulesConfig<'a> {
+    fn load(config: Option<&'a Value>) -> Self {
+        EnvVarModulesConfig {
+            config: env_var::EnvVarConfig::try_load(config),
+            modules: config
+                .and_then(Value::as_table)
+                .map(|modules| {
+                    modules
+                        .iter()
+                        .filter(|(_, config)| config.is_table())
+                        .map(|(name, config)| {
+                            (name.clone(), env_var::EnvVarConfig::try_load(Some(config)))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::ALL_MODULES;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn full_config_has_a_table_per_module() {
+        let config = Value::try_from(FullConfig::load(None)).unwrap();
+        let tables = config
+            .as_table()
+            .unwrap()
+            .iter()
+            .filter(|(_, value)| value.is_table())
+            .map(|(name, _)| name.as_str())
+            .collect::<BTreeSet<_>>();
+
+        // `line_break` has no options, and `custom` holds the custom modules
+        let expected = ALL_MODULES
+            .iter()
+            .copied()
+            .filter(|module| *module != "line_break")
+            .chain(Some("custom"))
+            .chain(Some("battery").filter(|_| !cfg!(feature = "battery")))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(expected, tables);
+    }
+}
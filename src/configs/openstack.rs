@@ -1,7 +1,8 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct OspConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
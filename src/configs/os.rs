@@ -0,0 +1,48 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use indexmap::{indexmap, IndexMap};
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct OsConfig<'a> {
+    pub format: &'a str,
+    pub style: &'a str,
+    pub symbols: IndexMap<String, &'a str>,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for OsConfig<'a> {
+    fn new() -> Self {
+        OsConfig {
+            format: "[$symbol]($style)",
+            style: "bold white",
+            symbols: indexmap! {
+                String::from("alpine") => "🏔️ ",
+                String::from("amzn") => "🙂 ",
+                String::from("android") => "🤖 ",
+                String::from("arch") => "🎗️ ",
+                String::from("centos") => "💠 ",
+                String::from("debian") => "🌀 ",
+                String::from("dragonfly") => "🐉 ",
+                String::from("fedora") => "🎩 ",
+                String::from("freebsd") => "😈 ",
+                String::from("gentoo") => "🗜️ ",
+                String::from("linux") => "🐧 ",
+                String::from("linuxmint") => "🌿 ",
+                String::from("macos") => "🍎 ",
+                String::from("manjaro") => "🥭 ",
+                String::from("netbsd") => "🚩 ",
+                String::from("nixos") => "❄️ ",
+                String::from("openbsd") => "🐡 ",
+                String::from("opensuse-leap") => "🦎 ",
+                String::from("opensuse-tumbleweed") => "🦎 ",
+                String::from("raspbian") => "🍓 ",
+                String::from("rhel") => "🎩 ",
+                String::from("ubuntu") => "🎯 ",
+                String::from("windows") => "🪟 ",
+            },
+            disabled: true,
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct PhpConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub format: &'a str,
+    pub version_format: &'a str,
     pub disabled: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
 }
 
 impl<'a> RootModuleConfig<'a> for PhpConfig<'a> {
@@ -16,7 +21,11 @@ impl<'a> RootModuleConfig<'a> for PhpConfig<'a> {
             symbol: "🐘 ",
             style: "147 bold",
             format: "via [$symbol$version]($style) ",
+            version_format: "v${raw}",
             disabled: false,
+            detect_extensions: vec!["php"],
+            detect_files: vec!["composer.json", ".php-version"],
+            detect_folders: vec![],
         }
     }
 }
@@ -0,0 +1,37 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct ShellConfig<'a> {
+    pub format: &'a str,
+    pub bash_indicator: &'a str,
+    pub fish_indicator: &'a str,
+    pub ion_indicator: &'a str,
+    pub nu_indicator: &'a str,
+    pub powershell_indicator: &'a str,
+    pub tcsh_indicator: &'a str,
+    pub zsh_indicator: &'a str,
+    pub unknown_indicator: &'a str,
+    pub style: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for ShellConfig<'a> {
+    fn new() -> Self {
+        ShellConfig {
+            format: "[$indicator]($style) ",
+            bash_indicator: "bsh",
+            fish_indicator: "fsh",
+            ion_indicator: "ion",
+            nu_indicator: "nu",
+            powershell_indicator: "psh",
+            tcsh_indicator: "tsh",
+            zsh_indicator: "zsh",
+            unknown_indicator: "",
+            style: "white bold",
+            disabled: true,
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct StarshipRootConfig<'a> {
     pub format: &'a str,
+    pub right_format: &'a str,
     pub scan_timeout: u64,
+    pub command_timeout: u64,
+    pub repo_discovery_stop_at_home: bool,
+    pub follow_repo_config: bool,
     pub add_newline: bool,
 }
 
@@ -15,6 +20,7 @@ pub struct StarshipRootConfig<'a> {
 pub const PROMPT_ORDER: &[&str] = &[
     "username",
     "hostname",
+    "localip",
     "shlvl",
     "singularity",
     "kubernetes",
@@ -22,6 +28,7 @@ pub const PROMPT_ORDER: &[&str] = &[
     "git_branch",
     "git_commit",
     "git_state",
+    "git_metrics",
     "git_status",
     "hg_branch",
     "docker_context",
@@ -61,6 +68,7 @@ pub const PROMPT_ORDER: &[&str] = &[
     "env_var",
     "crystal",
     "custom",
+    "sudo",
     "cmd_duration",
     "line_break",
     "jobs",
@@ -68,6 +76,8 @@ pub const PROMPT_ORDER: &[&str] = &[
     "battery",
     "time",
     "status",
+    "os",
+    "shell",
     "character",
 ];
 
@@ -75,7 +85,11 @@ impl<'a> RootModuleConfig<'a> for StarshipRootConfig<'a> {
     fn new() -> Self {
         StarshipRootConfig {
             format: "$all",
+            right_format: "",
             scan_timeout: 30,
+            command_timeout: 500,
+            repo_discovery_stop_at_home: false,
+            follow_repo_config: false,
             add_newline: true,
         }
     }
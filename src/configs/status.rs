@@ -1,21 +1,30 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct StatusConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
+    pub success_symbol: &'a str,
     pub style: &'a str,
+    pub pipestatus: bool,
+    pub pipestatus_separator: &'a str,
+    pub pipestatus_format: &'a str,
     pub disabled: bool,
 }
 
 impl<'a> RootModuleConfig<'a> for StatusConfig<'a> {
     fn new() -> Self {
         StatusConfig {
-            format: "[$symbol$status]($style) ",
+            format: "[$symbol $status]($style) ",
             symbol: "✖",
+            success_symbol: "",
             style: "bold red",
+            pipestatus: false,
+            pipestatus_separator: "|",
+            pipestatus_format: "[$symbol \\[$pipestatus\\]]($style) ",
             disabled: true,
         }
     }
@@ -5,6 +5,7 @@ use std::process;
 use std::process::Command;
 
 use crate::config::StarshipConfig;
+use crate::configs::FullConfig;
 use std::fs::File;
 use std::io::Write;
 use toml::map::Map;
@@ -24,7 +25,7 @@ pub fn update_configuration(name: &str, value: &str) {
         process::exit(1);
     }
 
-    let starship_config = StarshipConfig::initialize();
+    let starship_config = StarshipConfig::initialize(env::var_os("STARSHIP_CONFIG").as_deref());
     let mut config = starship_config
         .config
         .expect("Failed to load starship config");
@@ -62,6 +63,61 @@ pub fn update_configuration(name: &str, value: &str) {
     }
 }
 
+pub fn print_configuration(use_default: bool, paths: &[&str]) {
+    let user_config = if use_default {
+        None
+    } else {
+        StarshipConfig::initialize(env::var_os("STARSHIP_CONFIG").as_deref()).config
+    };
+    let config = get_configuration(user_config.as_ref());
+    let config = if paths.is_empty() {
+        config
+    } else {
+        extract_toml_paths(&config, paths)
+    };
+
+    let config_str =
+        toml::to_string_pretty(&config).expect("Failed to serialize the config to string");
+    print!("{}", config_str);
+}
+
+/// Layers the user's config over the defaults of the root and every module config
+fn get_configuration(user_config: Option<&Value>) -> Value {
+    Value::try_from(FullConfig::load(user_config)).expect("Failed to serialize the config")
+}
+
+/// Keeps only the values at the given dotted paths (e.g. `git_status.format`) of `config`
+fn extract_toml_paths(config: &Value, paths: &[&str]) -> Value {
+    let mut subset = Map::new();
+
+    'paths: for path in paths {
+        let keys: Vec<&str> = path.split('.').collect();
+        let value = match keys.iter().try_fold(config, |value, key| value.get(key)) {
+            Some(value) => value.clone(),
+            None => {
+                log::warn!("No config found at \"{}\"", path);
+                continue;
+            }
+        };
+
+        let (last_key, parent_keys) = keys.split_last().expect("split always yields a key");
+        let mut table = &mut subset;
+        for key in parent_keys {
+            let entry = table
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Table(Map::new()));
+            table = match entry.as_table_mut() {
+                Some(table) => table,
+                // A parent of this path was already printed as a whole
+                None => continue 'paths,
+            };
+        }
+        table.insert(last_key.to_string(), value);
+    }
+
+    Value::Table(subset)
+}
+
 pub fn edit_configuration() {
     let config_path = get_config_path();
     let editor_cmd = shell_words::split(&get_editor()).expect("Unmatched quotes found in $EDITOR.");
@@ -167,4 +223,79 @@ mod tests {
         let actual = get_editor_internal(None, None);
         assert_eq!(STD_EDITOR, actual);
     }
+
+    #[test]
+    fn default_git_status_config_round_trips() {
+        use crate::config::RootModuleConfig;
+        use crate::configs::git_status::GitStatusConfig;
+
+        let default_config = GitStatusConfig::new();
+        let serialized = toml::to_string(&Value::try_from(&default_config).unwrap()).unwrap();
+        let value = serialized.parse::<Value>().unwrap();
+
+        assert_eq!(
+            value.get("format").and_then(Value::as_str),
+            Some(default_config.format)
+        );
+        assert_eq!(GitStatusConfig::load(&value).format, default_config.format);
+    }
+
+    #[test]
+    fn user_config_is_layered_over_defaults() {
+        let user_config = toml::toml! {
+            format = "$directory"
+
+            [git_status]
+            style = "blue"
+
+            [custom.greeting]
+            command = "echo hi"
+
+            [env_var.AWS_VAULT]
+            style = "red"
+        };
+        let config = get_configuration(Some(&user_config));
+
+        assert_eq!(config["format"].as_str(), Some("$directory"));
+        assert_eq!(config["add_newline"].as_bool(), Some(true));
+        assert_eq!(config["git_status"]["style"].as_str(), Some("blue"));
+        assert_eq!(config["git_status"]["deleted"].as_str(), Some("✘"));
+        assert_eq!(
+            config["custom"]["greeting"]["command"].as_str(),
+            Some("echo hi")
+        );
+        assert_eq!(
+            config["custom"]["greeting"]["format"].as_str(),
+            Some("[$symbol$output]($style) ")
+        );
+        assert_eq!(
+            config["env_var"]["AWS_VAULT"]["style"].as_str(),
+            Some("red")
+        );
+        assert_eq!(
+            config["env_var"]["AWS_VAULT"]["format"].as_str(),
+            Some("with [$env_value]($style) ")
+        );
+        assert_eq!(
+            config["env_var"]["format"].as_str(),
+            Some("with [$env_value]($style) ")
+        );
+    }
+
+    #[test]
+    fn only_requested_paths_are_extracted() {
+        let config = get_configuration(None);
+        let subset = extract_toml_paths(
+            &config,
+            &["add_newline", "git_status.format", "no_such_module"],
+        );
+
+        let expected = toml::toml! {
+            add_newline = true
+
+            [git_status]
+            format = "([\\[$all_status$ahead_behind$upstream_gone$is_shallow\\]]($style) )"
+        };
+        assert_eq!(subset, expected);
+    }
 }
@@ -2,16 +2,24 @@ use crate::config::StarshipConfig;
 use crate::module::Module;
 
 use crate::modules;
+#[cfg(not(target_os = "windows"))]
+use crate::modules::utils::directory_nix as directory_utils;
+#[cfg(target_os = "windows")]
+use crate::modules::utils::directory_win as directory_utils;
+use crate::utils::{self, exec_cmd, CommandOutput, OsInfo};
+use chrono::{DateTime, Utc};
 use clap::ArgMatches;
-use git2::{ErrorCode::UnbornBranch, Repository, RepositoryState};
+use git2::{ErrorCode::UnbornBranch, Repository, RepositoryOpenFlags, RepositoryState};
 use once_cell::sync::OnceCell;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsString;
 use std::fs;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::string::String;
 use std::time::{Duration, Instant};
+use toml::Value;
 
 /// Context contains data or common methods that may be used by multiple modules.
 /// The data contained within Context will be relevant to this particular rendering
@@ -23,6 +31,11 @@ pub struct Context<'a> {
     /// The current working directory that starship is being called in.
     pub current_dir: PathBuf,
 
+    /// A logical directory path which should represent the same directory as current_dir,
+    /// though may appear different.
+    /// E.g. when navigating to a PSDrive in PowerShell, or a path with symlinks resolved.
+    pub logical_dir: PathBuf,
+
     /// A struct containing directory contents in a lookup-optimised format.
     dir_contents: OnceCell<DirContents>,
 
@@ -32,29 +45,86 @@ pub struct Context<'a> {
     /// Private field to store Git information for modules who need it
     repo: OnceCell<Repo>,
 
+    /// The closest ancestor of `current_dir` containing a version control directory
+    vcs_root: OnceCell<Option<VcsRoot>>,
+
+    /// Whether `current_dir` is read only, so slow mounts are only checked once
+    current_dir_read_only: OnceCell<bool>,
+
+    /// Whether sudo has cached credentials, so `sudo` is only run once
+    sudo_cached: OnceCell<bool>,
+
+    /// The local IPv4 address, looked up on first use
+    pub local_ipv4: OnceCell<Option<Ipv4Addr>>,
+
+    /// The operating system, detected on first use
+    pub os_info: OnceCell<OsInfo>,
+
+    /// Module tables from the repository's `.starship.toml`, merged over the user's config.
+    repo_config: OnceCell<HashMap<String, Value>>,
+
     /// The shell the user is assumed to be running
     pub shell: Shell,
 
     /// A HashMap of environment variable mocks
     pub env: HashMap<&'a str, String>,
+
+    /// A HashMap of command mocks, keyed by the command and its arguments joined by spaces
+    pub cmd: HashMap<&'a str, Option<CommandOutput>>,
+
+    /// The width of the terminal, in columns
+    pub width: usize,
+
+    /// Which part of the prompt is being rendered
+    pub target: Target,
+
+    /// The effective user id starship runs as, if the platform has one
+    pub uid: Option<u32>,
+
+    /// The system hostname, if it could be read
+    pub hostname: Option<String>,
+
+    /// The time the prompt is being rendered at
+    pub now: DateTime<Utc>,
+
+    /// Where the battery module reads the battery status from
+    #[cfg(feature = "battery")]
+    pub battery_info_provider: &'a (dyn modules::BatteryInfoProvider + Send + Sync),
 }
 
 impl<'a> Context<'a> {
     /// Identify the current working directory and create an instance of Context
-    /// for it.
+    /// for it. "logical-path" is used when a shell allows the "current working directory"
+    /// to be something other than a file system path (like powershell provider specific paths).
     pub fn new(arguments: ArgMatches) -> Context {
-        // Retrieve the "path" flag. If unavailable, use the current directory instead.
+        // Retrieve the "current directory".
+        // If the path argument is not set fall back to the OS current directory.
         let path = arguments
             .value_of("path")
-            .map(From::from)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                env::current_dir().unwrap_or_else(|err| {
+                    log::debug!("Unable to get the physical current directory: {}", err);
+                    env::var("PWD")
+                        .map(PathBuf::from)
+                        .expect("Unable to identify current directory. Error")
+                })
+            });
+
+        // Retrieve the "logical directory".
+        // If the path argument is not set fall back to the PWD env variable set by many shells
+        // or to the other path.
+        let logical_path = arguments
+            .value_of("logical_path")
+            .map(PathBuf::from)
             .unwrap_or_else(|| {
                 env::var("PWD").map(PathBuf::from).unwrap_or_else(|err| {
                     log::debug!("Unable to get path from $PWD: {}", err);
-                    env::current_dir().expect("Unable to identify current directory. Error")
+                    path.clone()
                 })
             });
 
-        Context::new_with_dir(arguments, path)
+        Context::new_with_dirs(arguments, path, logical_path)
     }
 
     /// Create a new instance of Context for the provided directory
@@ -62,8 +132,15 @@ impl<'a> Context<'a> {
     where
         T: Into<PathBuf>,
     {
-        let config = StarshipConfig::initialize();
+        let dir = dir.into();
+        Context::new_with_dirs(arguments, dir.clone(), dir)
+    }
 
+    /// Create a new instance of Context for the provided physical and logical directories
+    pub fn new_with_dirs<T>(arguments: ArgMatches, dir: T, logical_dir: T) -> Context
+    where
+        T: Into<PathBuf>,
+    {
         // Unwrap the clap arguments into a simple hashtable
         // we only care about single arguments at this point, there isn't a
         // use-case for a list of arguments yet.
@@ -74,20 +151,57 @@ impl<'a> Context<'a> {
             .map(|(a, b)| (*a, b.vals.first().cloned().unwrap().into_string().unwrap()))
             .collect();
 
-        // TODO: Currently gets the physical directory. Get the logical directory.
         let current_dir = Context::expand_tilde(dir.into());
+        let logical_dir = Context::expand_tilde(logical_dir.into());
 
         let shell = Context::get_shell();
 
-        Context {
-            config,
+        let width = properties
+            .get("terminal_width")
+            .and_then(|width| width.parse().ok())
+            .unwrap_or_else(Context::get_terminal_width);
+
+        let target = if arguments.is_present("right") {
+            Target::Right
+        } else {
+            Target::Main
+        };
+
+        let mut context = Context {
+            config: StarshipConfig { config: None },
             properties,
             current_dir,
+            logical_dir,
             dir_contents: OnceCell::new(),
             repo: OnceCell::new(),
+            vcs_root: OnceCell::new(),
+            current_dir_read_only: OnceCell::new(),
+            sudo_cached: OnceCell::new(),
+            local_ipv4: OnceCell::new(),
+            os_info: OnceCell::new(),
+            repo_config: OnceCell::new(),
             shell,
             env: HashMap::new(),
+            cmd: HashMap::new(),
+            width,
+            target,
+            uid: utils::get_effective_uid(),
+            hostname: utils::get_hostname(),
+            now: Utc::now(),
+            #[cfg(feature = "battery")]
+            battery_info_provider: &modules::BatteryInfoProviderImpl,
+        };
+        context.config = StarshipConfig::initialize(context.get_config_path_os().as_deref());
+        context
+    }
+
+    /// The path of the config file, given either by `--config` or by `$STARSHIP_CONFIG`.
+    /// `None` means the default path is used.
+    pub fn get_config_path_os(&self) -> Option<OsString> {
+        if let Some(config_path) = self.properties.get("config") {
+            return Some(OsString::from(config_path));
         }
+        self.get_env_os("STARSHIP_CONFIG")
     }
 
     // Retrives a environment variable from the os or from a table if in testing mode
@@ -108,6 +222,12 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Whether starship is running inside an SSH session
+    pub fn is_ssh_session(&self) -> bool {
+        const SSH_ENV: [&str; 3] = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
+        SSH_ENV.iter().any(|env| self.get_env(env).is_some())
+    }
+
     /// Convert a `~` in a path to the home directory
     pub fn expand_tilde(dir: PathBuf) -> PathBuf {
         if dir.starts_with("~") {
@@ -119,7 +239,7 @@ impl<'a> Context<'a> {
 
     /// Create a new module
     pub fn new_module(&self, name: &str) -> Module {
-        let config = self.config.get_module_config(name);
+        let config = self.get_module_config(name);
         let desc = modules::description(name);
 
         Module::new(name, desc, config)
@@ -127,7 +247,7 @@ impl<'a> Context<'a> {
 
     /// Check if `disabled` option of the module is true in configuration file.
     pub fn is_module_disabled_in_config(&self, name: &str) -> bool {
-        let config = self.config.get_module_config(name);
+        let config = self.get_module_config(name);
 
         // If the segment has "disabled" set to "true", don't show it
         let disabled = config.and_then(|table| table.as_table()?.get("disabled")?.as_bool());
@@ -135,6 +255,56 @@ impl<'a> Context<'a> {
         disabled == Some(true)
     }
 
+    /// Get the config table for a module. If `follow_repo_config` is set, options
+    /// from the current repository's `.starship.toml` take precedence.
+    fn get_module_config(&self, name: &str) -> Option<&Value> {
+        if is_repo_configurable(name) && self.config.get_root_config().follow_repo_config {
+            if let Some(config) = self.get_repo_module_configs().get(name) {
+                return Some(config);
+            }
+        }
+
+        self.config.get_module_config(name)
+    }
+
+    /// Will lazily load the module tables from the repository's `.starship.toml`,
+    /// merged over the user's configuration.
+    fn get_repo_module_configs(&self) -> &HashMap<String, Value> {
+        self.repo_config
+            .get_or_init(|| self.load_repo_module_configs().unwrap_or_default())
+    }
+
+    fn load_repo_module_configs(&self) -> Option<HashMap<String, Value>> {
+        let root = self.get_repo().ok()?.root.as_ref()?;
+        let content = utils::read_file(root.join(".starship.toml")).ok()?;
+        let repo_config: toml::value::Table = match toml::from_str(&content) {
+            Ok(repo_config) => repo_config,
+            Err(error) => {
+                log::warn!("Unable to parse the repository config file: {}", error);
+                return None;
+            }
+        };
+
+        let module_configs = repo_config
+            .into_iter()
+            .filter(|(name, value)| is_repo_configurable(name) && value.is_table())
+            .map(|(name, value)| {
+                let mut merged = self
+                    .config
+                    .get_module_config(&name)
+                    .and_then(Value::as_table)
+                    .cloned()
+                    .unwrap_or_default();
+                if let Value::Table(repo_table) = value {
+                    merged.extend(repo_table);
+                }
+                (name, Value::Table(merged))
+            })
+            .collect();
+
+        Some(module_configs)
+    }
+
     /// Return whether the specified custom module has a `disabled` option set to true.
     /// If it doesn't exist, `None` is returned.
     pub fn is_custom_module_disabled_in_config(&self, name: &str) -> Option<bool> {
@@ -159,21 +329,22 @@ impl<'a> Context<'a> {
     pub fn get_repo(&self) -> Result<&Repo, std::io::Error> {
         self.repo
             .get_or_try_init(|| -> Result<Repo, std::io::Error> {
-                let repository = if env::var("GIT_DIR").is_ok() {
-                    Repository::open_from_env().ok()
-                } else {
-                    Repository::discover(&self.current_dir).ok()
-                };
+                let repository = self
+                    .get_env("GIT_DIR")
+                    .and_then(|git_dir| self.open_git_dir(git_dir))
+                    .or_else(|| self.discover_repo());
                 let branch = repository
                     .as_ref()
                     .and_then(|repo| get_current_branch(repo));
+                let path = repository.as_ref().map(|repo| repo.path().to_path_buf());
                 let root = repository
                     .as_ref()
                     .and_then(|repo| repo.workdir().map(Path::to_path_buf));
                 let state = repository.as_ref().map(|repo| repo.state());
-                let remote = repository.as_ref().and_then(|repo| get_remote_branch(repo));
+                let remote = repository.as_ref().and_then(get_remote_repository_info);
                 Ok(Repo {
                     branch,
+                    path,
                     root,
                     state,
                     remote,
@@ -181,6 +352,120 @@ impl<'a> Context<'a> {
             })
     }
 
+    /// Walks up from `current_dir` once to find the closest directory containing
+    /// a version control directory, recording every kind found there.
+    pub fn get_vcs_root(&self) -> Option<&VcsRoot> {
+        self.vcs_root
+            .get_or_init(|| {
+                self.current_dir.ancestors().find_map(|dir| {
+                    let markers: Vec<Vcs> = Vcs::ALL
+                        .iter()
+                        .copied()
+                        .filter(|vcs| dir.join(vcs.marker()).exists())
+                        .collect();
+
+                    if markers.is_empty() {
+                        None
+                    } else {
+                        Some(VcsRoot {
+                            path: dir.to_path_buf(),
+                            markers,
+                        })
+                    }
+                })
+            })
+            .as_ref()
+    }
+
+    /// Searches `current_dir` and its parents for a repository, without looking
+    /// in any of the directories listed in `GIT_CEILING_DIRECTORIES`, nor above
+    /// the home directory if `repo_discovery_stop_at_home` is set. A repository
+    /// enclosing a closer root of another version control system is ignored.
+    fn discover_repo(&self) -> Option<Repository> {
+        let mut ceiling_dirs: Vec<PathBuf> = self
+            .get_env_os("GIT_CEILING_DIRECTORIES")
+            .map(|dirs| env::split_paths(&dirs).collect())
+            .unwrap_or_default();
+
+        if self.config.get_root_config().repo_discovery_stop_at_home {
+            ceiling_dirs.extend(dirs_next::home_dir());
+        }
+
+        match Repository::open_ext(
+            &self.current_dir,
+            RepositoryOpenFlags::empty(),
+            &ceiling_dirs,
+        ) {
+            Ok(repository) if self.is_shadowed_by_vcs_root(&repository) => {
+                log::trace!(
+                    "Ignoring repository at {:?} which encloses another version control root",
+                    repository.path()
+                );
+                None
+            }
+            Ok(repository) => Some(repository),
+            Err(e) => {
+                log::trace!("Unable to discover a repository: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether the closest version control root lies inside `repository`'s
+    /// working directory without being part of it, e.g. an hg clone within a git repo.
+    fn is_shadowed_by_vcs_root(&self, repository: &Repository) -> bool {
+        let vcs_root = match self.get_vcs_root() {
+            Some(vcs_root) => vcs_root,
+            None => return false,
+        };
+        let repo_dir = repository.workdir().unwrap_or_else(|| repository.path());
+
+        // libgit2 hands back canonical paths, which `current_dir` may not be
+        match (fs::canonicalize(repo_dir), fs::canonicalize(&vcs_root.path)) {
+            (Ok(repo_dir), Ok(vcs_root)) => vcs_root != repo_dir && vcs_root.starts_with(repo_dir),
+            _ => false,
+        }
+    }
+
+    /// Opens the repository at `GIT_DIR`, using `GIT_WORK_TREE` as its working
+    /// directory if it is set. Paths which don't exist are ignored.
+    fn open_git_dir(&self, git_dir: String) -> Option<Repository> {
+        let repository = match Repository::open(self.current_dir.join(git_dir)) {
+            Ok(repository) => repository,
+            Err(e) => {
+                log::debug!("Unable to open repository from $GIT_DIR: {}", e);
+                return None;
+            }
+        };
+
+        if let Some(work_tree) = self.get_env("GIT_WORK_TREE") {
+            let work_tree = self.current_dir.join(work_tree);
+            if work_tree.is_dir() {
+                repository.set_workdir(&work_tree, false).ok()?;
+            }
+        }
+
+        Some(repository)
+    }
+
+    /// Whether the current user can't write to `current_dir`. The check is only
+    /// done once, since it can be slow on network mounts.
+    pub fn is_current_dir_read_only(&self) -> bool {
+        *self.current_dir_read_only.get_or_init(|| {
+            match directory_utils::is_write_allowed(&self.current_dir) {
+                Ok(res) => !res,
+                Err(e) => {
+                    log::debug!(
+                        "Failed to determine read only status of directory '{:?}': {}",
+                        self.current_dir,
+                        e
+                    );
+                    false
+                }
+            }
+        })
+    }
+
     pub fn dir_contents(&self) -> Result<&DirContents, std::io::Error> {
         self.dir_contents.get_or_try_init(|| {
             let timeout = Duration::from_millis(self.config.get_root_config().scan_timeout);
@@ -188,13 +473,66 @@ impl<'a> Context<'a> {
         })
     }
 
+    /// Execute a command and return the output on stdout and stderr if successful,
+    /// giving up once `command_timeout` has elapsed
+    pub fn exec_cmd(&self, cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+        self.exec_cmd_with_status(cmd, args)
+            .filter(CommandOutput::success)
+    }
+
+    /// Like `exec_cmd`, but also returns the output of a command that exited unsuccessfully
+    pub fn exec_cmd_with_status(&self, cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+        if cfg!(test) {
+            let command = std::iter::once(cmd)
+                .chain(args.iter().copied())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            if let Some(output) = self.cmd.get(command.as_str()) {
+                return output.clone();
+            }
+        }
+        exec_cmd(cmd, args, self.command_timeout())
+    }
+
+    /// The IPv4 address of the interface used to reach `probe`. It is only looked up once.
+    pub fn get_local_ipv4(&self, probe: SocketAddr) -> Option<Ipv4Addr> {
+        *self.local_ipv4.get_or_init(|| utils::get_local_ipv4(probe))
+    }
+
+    /// The operating system starship runs on. It is only detected once.
+    pub fn get_os_info(&self) -> &OsInfo {
+        self.os_info.get_or_init(utils::get_os_info)
+    }
+
+    /// Whether sudo has cached credentials, so running `sudo` won't ask for a password.
+    /// `sudo -n` never prompts, and failing or timing out counts as not cached.
+    pub fn is_sudo_cached(&self) -> bool {
+        *self
+            .sudo_cached
+            .get_or_init(|| self.exec_cmd("sudo", &["-n", "true"]).is_some())
+    }
+
+    /// The maximum amount of time external commands are allowed to run for
+    pub fn command_timeout(&self) -> Duration {
+        Duration::from_millis(self.config.get_root_config().command_timeout)
+    }
+
+    /// Asks the terminal for its width, falling back to 80 columns when it can't be found
+    fn get_terminal_width() -> usize {
+        term_size::dimensions()
+            .map(|(width, _)| width)
+            .unwrap_or(80)
+    }
+
     fn get_shell() -> Shell {
         let shell = env::var("STARSHIP_SHELL").unwrap_or_default();
         match shell.as_str() {
             "bash" => Shell::Bash,
             "fish" => Shell::Fish,
             "ion" => Shell::Ion,
+            "nu" => Shell::Nu,
             "powershell" => Shell::PowerShell,
+            "tcsh" => Shell::Tcsh,
             "zsh" => Shell::Zsh,
             _ => Shell::Unknown,
         }
@@ -300,11 +638,54 @@ impl DirContents {
     }
 }
 
+/// A version control system recognised by the directory it keeps its data in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vcs {
+    Git,
+    Mercurial,
+    Subversion,
+    Pijul,
+}
+
+impl Vcs {
+    const ALL: &'static [Vcs] = &[Vcs::Git, Vcs::Mercurial, Vcs::Subversion, Vcs::Pijul];
+
+    /// The directory marking the root of a repository
+    pub fn marker(self) -> &'static str {
+        match self {
+            Vcs::Git => ".git",
+            Vcs::Mercurial => ".hg",
+            Vcs::Subversion => ".svn",
+            Vcs::Pijul => ".pijul",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VcsRoot {
+    /// The closest ancestor of `current_dir` containing a version control directory
+    pub path: PathBuf,
+
+    /// Every version control system with a directory at `path`
+    pub markers: Vec<Vcs>,
+}
+
+impl VcsRoot {
+    pub fn is(&self, vcs: Vcs) -> bool {
+        self.markers.contains(&vcs)
+    }
+}
+
+#[derive(Clone)]
 pub struct Repo {
     /// If `current_dir` is a git repository or is contained within one,
     /// this is the current branch name of that repo.
     pub branch: Option<String>,
 
+    /// If `current_dir` is a git repository or is contained within one,
+    /// this is the path to the git directory of that repo.
+    pub path: Option<PathBuf>,
+
     /// If `current_dir` is a git repository or is contained within one,
     /// this is the path to the root of that repo.
     pub root: Option<PathBuf>,
@@ -312,8 +693,50 @@ pub struct Repo {
     /// State
     pub state: Option<RepositoryState>,
 
-    /// Remote branch name
-    pub remote: Option<String>,
+    /// Remote repository
+    pub remote: Option<Remote>,
+}
+
+impl Repo {
+    /// Opens the underlying git repository, using `root` as its working directory.
+    pub fn open(&self) -> Result<Repository, git2::Error> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| git2::Error::from_str("Not a git repository"))?;
+        let repository = Repository::open(path)?;
+
+        if let Some(root) = self.root.as_ref() {
+            if repository.workdir() != Some(root.as_path()) {
+                repository.set_workdir(root, false)?;
+            }
+        }
+
+        Ok(repository)
+    }
+
+    /// Whether this is a bare repository, i.e. one without a working directory.
+    pub fn is_bare(&self) -> bool {
+        self.path.is_some() && self.root.is_none()
+    }
+
+    /// Whether this is a shallow clone, i.e. one with truncated history.
+    pub fn is_shallow(&self) -> bool {
+        match &self.path {
+            Some(path) => path.join("shallow").is_file(),
+            None => false,
+        }
+    }
+}
+
+/// The upstream of the current branch
+#[derive(Clone)]
+pub struct Remote {
+    /// The name of the upstream branch on the remote, e.g. `master`
+    pub branch: String,
+
+    /// The name of the remote, e.g. `origin`
+    pub name: String,
 }
 
 // A struct of Criteria which will be used to verify current PathBuf is
@@ -360,15 +783,7 @@ fn get_current_branch(repository: &Repository) -> Option<String> {
                 let mut head_path = repository.path().to_path_buf();
                 head_path.push("HEAD");
 
-                // get first line, then last path segment
-                fs::read_to_string(&head_path)
-                    .ok()?
-                    .lines()
-                    .next()?
-                    .trim()
-                    .split('/')
-                    .last()
-                    .map(|r| r.to_owned())
+                branch_from_head_file(&fs::read_to_string(&head_path).ok()?)
             } else {
                 None
             };
@@ -380,19 +795,55 @@ fn get_current_branch(repository: &Repository) -> Option<String> {
     shorthand.map(std::string::ToString::to_string)
 }
 
-fn get_remote_branch(repository: &Repository) -> Option<String> {
-    if let Ok(head) = repository.head() {
-        if let Some(local_branch_ref) = head.name() {
-            let remote_ref = match repository.branch_upstream_name(local_branch_ref) {
-                Ok(remote_ref) => remote_ref.as_str()?.to_owned(),
-                Err(_) => return None,
-            };
+/// Extracts the branch name from the contents of a `HEAD` file, keeping any
+/// slashes that are part of the branch name itself
+fn branch_from_head_file(contents: &str) -> Option<String> {
+    let reference = contents.lines().next()?.trim().strip_prefix("ref:")?.trim();
+    let branch = reference
+        .strip_prefix("refs/heads/")
+        .or_else(|| reference.strip_prefix("refs/remotes/"))
+        .unwrap_or(reference);
 
-            let remote = remote_ref.split('/').last().map(|r| r.to_owned())?;
-            return Some(remote);
-        }
-    }
-    None
+    Some(branch.to_owned())
+}
+
+fn get_remote_repository_info(repository: &Repository) -> Option<Remote> {
+    let head = repository.head().ok()?;
+    let local_branch_ref = head.name()?;
+
+    let remote_ref = repository.branch_upstream_name(local_branch_ref).ok()?;
+    let remote_ref = remote_ref.as_str()?;
+    let remote_name = repository.branch_upstream_remote(local_branch_ref).ok()?;
+    let remote_name = remote_name.as_str()?;
+
+    // A remote of "." means the upstream is another local branch
+    let remote_prefix = if remote_name == "." {
+        String::from("refs/heads/")
+    } else {
+        format!("refs/remotes/{}/", remote_name)
+    };
+    let branch = remote_ref
+        .strip_prefix(&remote_prefix)
+        .unwrap_or(remote_ref);
+
+    Some(Remote {
+        branch: branch.to_owned(),
+        name: remote_name.to_owned(),
+    })
+}
+
+/// Only these modules may be configured by a repository's `.starship.toml`
+fn is_repo_configurable(module_name: &str) -> bool {
+    module_name == "directory" || module_name.starts_with("git_")
+}
+
+/// Which part of the prompt is being rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    /// The prompt on the left, rendered from `format`
+    Main,
+    /// The prompt on the right, rendered from `right_format`
+    Right,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -400,7 +851,9 @@ pub enum Shell {
     Bash,
     Fish,
     Ion,
+    Nu,
     PowerShell,
+    Tcsh,
     Zsh,
     Unknown,
 }
@@ -408,6 +861,10 @@ pub enum Shell {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use ansi_term::Color;
+    use std::ffi::OsStr;
+    use std::io;
 
     fn testdir(paths: &[&str]) -> Result<tempfile::TempDir, std::io::Error> {
         let dir = tempfile::tempdir()?;
@@ -421,6 +878,34 @@ mod tests {
         Ok(dir)
     }
 
+    #[test]
+    fn test_branch_from_head_file() {
+        assert_eq!(
+            branch_from_head_file("ref: refs/heads/master\n"),
+            Some(String::from("master"))
+        );
+        assert_eq!(
+            branch_from_head_file("ref: refs/heads/feature/login/oauth\n"),
+            Some(String::from("feature/login/oauth"))
+        );
+        assert_eq!(
+            branch_from_head_file("ref: refs/heads/फ़ीचर/日本語\n"),
+            Some(String::from("फ़ीचर/日本語"))
+        );
+        assert_eq!(
+            branch_from_head_file("ref: refs/heads/main  \t\r\n"),
+            Some(String::from("main"))
+        );
+        assert_eq!(
+            branch_from_head_file("ref: refs/remotes/origin/feature/x\n"),
+            Some(String::from("origin/feature/x"))
+        );
+        assert_eq!(
+            branch_from_head_file("35dc38f922e4b6d6596cc7c5a0bb5caf00f1db64\n"),
+            None
+        );
+    }
+
     #[test]
     fn test_scan_dir() -> Result<(), Box<dyn std::error::Error>> {
         let empty = testdir(&[])?;
@@ -482,4 +967,342 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn dir_contents_only_lists_top_level_entries() -> io::Result<()> {
+        let dir = testdir(&[
+            "Cargo.toml",
+            "main.rs",
+            "archive.tar.gz",
+            ".nvmrc",
+            "lib/util.js",
+            "node_modules/lodash/main.js",
+        ])?;
+        let dc = DirContents::from_path(&PathBuf::from(dir.path()))?;
+
+        assert!(dc.has_file_name("Cargo.toml"));
+        assert!(dc.has_file_name(".nvmrc"));
+        assert!(!dc.has_file_name("util.js"));
+
+        assert!(dc.has_extension("rs"));
+        assert!(dc.has_extension("gz"));
+        assert!(!dc.has_extension("nvmrc"));
+        assert!(!dc.has_extension("js"));
+
+        assert!(dc.has_folder("lib"));
+        assert!(dc.has_any_folder(&["vendor", "node_modules"]));
+        assert!(!dc.has_folder("lodash"));
+        dir.close()
+    }
+
+    #[test]
+    fn scan_of_unreadable_dir_does_not_panic() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let missing = dir.path().join("missing");
+        let context = Context::new_with_dir(Default::default(), &missing);
+        assert!(context.dir_contents().is_err());
+        assert!(context.try_begin_scan().is_none());
+
+        let actual = ModuleRenderer::new("rust").path(&missing).collect();
+        assert_eq!(actual, None);
+
+        // Not a directory
+        let file = dir.path().join("Cargo.toml");
+        fs::File::create(&file)?.sync_all()?;
+        let actual = ModuleRenderer::new("rust").path(&file).collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn scan_of_dir_without_permissions_does_not_panic() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = testdir(&["Cargo.toml"])?;
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o000))?;
+        // Root can still read the directory, so only the absence of a panic is checked
+        let _ = ModuleRenderer::new("rust").path(dir.path()).collect();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755))?;
+        dir.close()
+    }
+
+    #[test]
+    fn repo_config_is_ignored_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(
+            repo_dir.path().join(".starship.toml"),
+            "[git_status]\ndisabled = true\n",
+        )?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("[?]")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_config_disables_module() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(
+            repo_dir.path().join(".starship.toml"),
+            "[git_status]\ndisabled = true\n",
+        )?;
+
+        let mut context = Context::new_with_dir(ArgMatches::default(), repo_dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                follow_repo_config = true
+            }),
+        };
+
+        assert!(context.is_module_disabled_in_config("git_status"));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_config_overrides_module_options() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(
+            repo_dir.path().join(".starship.toml"),
+            "[git_status]\nuntracked = \"R\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                follow_repo_config = true
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("[R]")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_config_is_merged_per_option() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(
+            repo_dir.path().join(".starship.toml"),
+            "[git_status]\nformat = \"$untracked\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                follow_repo_config = true
+                [git_status]
+                format = "[$all_status]($style)"
+                untracked = "U"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(String::from("U"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_config_only_applies_to_repo_modules() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(
+            repo_dir.path().join(".starship.toml"),
+            "[character]\nformat = \"repo\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                follow_repo_config = true
+                [character]
+                format = "user"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(String::from("user"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn vcs_root_records_markers_of_closest_ancestor() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let hg_dir = repo_dir.path().join("vendor").join("lib");
+        fs::create_dir_all(hg_dir.join(".hg"))?;
+        fs::create_dir(hg_dir.join(".svn"))?;
+        let sub_dir = hg_dir.join("src");
+        fs::create_dir(&sub_dir)?;
+
+        let context = Context::new_with_dir(ArgMatches::default(), &sub_dir);
+        let expected = VcsRoot {
+            path: hg_dir,
+            markers: vec![Vcs::Mercurial, Vcs::Subversion],
+        };
+
+        assert_eq!(Some(&expected), context.get_vcs_root());
+        assert!(!expected.is(Vcs::Git));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn no_vcs_root_outside_repositories() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let context = Context::new_with_dir(ArgMatches::default(), dir.path());
+
+        assert_eq!(None, context.get_vcs_root());
+        dir.close()
+    }
+
+    #[test]
+    fn git_repo_enclosing_other_vcs_root_is_ignored() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let hg_dir = repo_dir.path().join("vendor");
+        fs::create_dir_all(hg_dir.join(".hg"))?;
+
+        let context = Context::new_with_dir(ArgMatches::default(), &hg_dir);
+
+        assert_eq!(None, context.get_repo()?.root);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn git_repo_is_found_from_plain_subdirectory() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let sub_dir = repo_dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        let context = Context::new_with_dir(ArgMatches::default(), &sub_dir);
+        let root = context.get_repo()?.root.as_ref().map(fs::canonicalize);
+
+        assert_eq!(Some(fs::canonicalize(repo_dir.path())?), root.transpose()?);
+        repo_dir.close()
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn read_only_status_is_checked_once() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // root can write anywhere, so the directory never becomes read-only
+        if nix::unistd::Uid::effective().is_root() {
+            return Ok(());
+        }
+
+        let dir = tempfile::tempdir()?;
+        let context = Context::new_with_dir(ArgMatches::default(), dir.path());
+        assert!(!context.is_current_dir_read_only());
+
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555))?;
+        let read_only = context.is_current_dir_read_only();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755))?;
+
+        assert!(!read_only);
+        dir.close()
+    }
+
+    fn config_file(dir: &Path, content: &str) -> io::Result<PathBuf> {
+        let path = dir.join("starship.toml");
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    #[test]
+    fn config_flag_takes_precedence_over_env() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.properties.insert("config", "/flag.toml".to_owned());
+        context
+            .env
+            .insert("STARSHIP_CONFIG", "/env.toml".to_owned());
+        assert_eq!(
+            context.get_config_path_os(),
+            Some(OsString::from("/flag.toml"))
+        );
+    }
+
+    #[test]
+    fn config_env_is_used_without_flag() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        assert_eq!(context.get_config_path_os(), None);
+
+        context
+            .env
+            .insert("STARSHIP_CONFIG", "/env.toml".to_owned());
+        assert_eq!(
+            context.get_config_path_os(),
+            Some(OsString::from("/env.toml"))
+        );
+    }
+
+    #[test]
+    fn config_given_by_flag_is_loaded() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = config_file(dir.path(), "format = \"$character\"")?;
+        let arguments = clap::App::new("starship")
+            .arg(
+                clap::Arg::with_name("config")
+                    .long("config")
+                    .takes_value(true),
+            )
+            .get_matches_from(vec![
+                OsStr::new("starship"),
+                OsStr::new("--config"),
+                path.as_os_str(),
+            ]);
+
+        let context = Context::new_with_dir(arguments, dir.path());
+        assert_eq!(context.config.get_root_config().format, "$character");
+        dir.close()
+    }
+
+    #[test]
+    fn missing_config_file_falls_back_to_defaults() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let actual = ModuleRenderer::new("character")
+            .config_file(dir.path().join("missing.toml"))
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("❯")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn module_is_rendered_with_config_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = config_file(dir.path(), "[character]\nsuccess_symbol = \"[➜](red)\"")?;
+        let actual = ModuleRenderer::new("character").config_file(path).collect();
+        let expected = Some(format!("{} ", Color::Red.paint("➜")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn exec_cmd_only_returns_successful_output() {
+        let mut context = Context::new_with_dir(Default::default(), PathBuf::new());
+        let failure = CommandOutput {
+            stdout: String::default(),
+            stderr: String::from("error: unknown flag"),
+            status: Some(2),
+        };
+        let success = CommandOutput {
+            stdout: String::from("1.0.0"),
+            stderr: String::default(),
+            status: Some(0),
+        };
+        context.cmd.insert("tool --bad-flag", Some(failure.clone()));
+        context.cmd.insert("tool --version", Some(success.clone()));
+
+        assert_eq!(context.exec_cmd("tool", &["--bad-flag"]), None);
+        assert_eq!(context.exec_cmd("tool", &["--version"]), Some(success));
+        assert_eq!(
+            context.exec_cmd_with_status("tool", &["--bad-flag"]),
+            Some(failure)
+        );
+    }
 }
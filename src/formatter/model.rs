@@ -17,10 +17,31 @@ pub struct TextGroup<'a> {
     pub style: Vec<StyleElement<'a>>,
 }
 
+/// A transformation applied to the value of a variable, e.g. `|upper`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Modifier {
+    Upper,
+    Lower,
+    /// Keeps at most this many grapheme clusters
+    Trunc(usize),
+    /// Pads with spaces to at least this many grapheme clusters
+    Pad(usize),
+}
+
+#[derive(Clone)]
+pub struct ScopedVariable<'a> {
+    pub name: Cow<'a, str>,
+    pub modifiers: Vec<Modifier>,
+    /// The format string shown when the variable is empty
+    pub fallback: Option<Vec<FormatElement<'a>>>,
+}
+
 #[derive(Clone)]
 pub enum FormatElement<'a> {
     Text(Cow<'a, str>),
     Variable(Cow<'a, str>),
+    /// A variable in curly brackets with modifiers or a fallback
+    Scoped(ScopedVariable<'a>),
     TextGroup(TextGroup<'a>),
     Conditional(Vec<FormatElement<'a>>),
 }
@@ -31,6 +52,47 @@ pub enum StyleElement<'a> {
     Variable(Cow<'a, str>),
 }
 
+impl<'a> FormatElement<'a> {
+    /// Copies the borrowed parts of the element, so it no longer depends on the format string
+    pub fn into_owned(self) -> FormatElement<'static> {
+        match self {
+            FormatElement::Text(text) => FormatElement::Text(Cow::Owned(text.into_owned())),
+            FormatElement::Variable(name) => FormatElement::Variable(Cow::Owned(name.into_owned())),
+            FormatElement::Scoped(scoped) => FormatElement::Scoped(ScopedVariable {
+                name: Cow::Owned(scoped.name.into_owned()),
+                modifiers: scoped.modifiers,
+                fallback: scoped.fallback.map(into_owned_elements),
+            }),
+            FormatElement::TextGroup(textgroup) => FormatElement::TextGroup(TextGroup {
+                format: into_owned_elements(textgroup.format),
+                style: textgroup
+                    .style
+                    .into_iter()
+                    .map(StyleElement::into_owned)
+                    .collect(),
+            }),
+            FormatElement::Conditional(format) => {
+                FormatElement::Conditional(into_owned_elements(format))
+            }
+        }
+    }
+}
+
+impl<'a> StyleElement<'a> {
+    /// Copies the borrowed parts of the element, so it no longer depends on the format string
+    pub fn into_owned(self) -> StyleElement<'static> {
+        match self {
+            StyleElement::Text(text) => StyleElement::Text(Cow::Owned(text.into_owned())),
+            StyleElement::Variable(name) => StyleElement::Variable(Cow::Owned(name.into_owned())),
+        }
+    }
+}
+
+/// Copies the borrowed parts of all elements, see `FormatElement::into_owned`
+pub fn into_owned_elements(format: Vec<FormatElement>) -> Vec<FormatElement<'static>> {
+    format.into_iter().map(FormatElement::into_owned).collect()
+}
+
 impl<'a> VariableHolder<Cow<'a, str>> for FormatElement<'a> {
     fn get_variables(&self) -> BTreeSet<Cow<'a, str>> {
         match self {
@@ -39,6 +101,15 @@ impl<'a> VariableHolder<Cow<'a, str>> for FormatElement<'a> {
                 variables.insert(var.clone());
                 variables
             }
+            FormatElement::Scoped(scoped) => {
+                let mut variables = scoped
+                    .fallback
+                    .as_ref()
+                    .map(|fallback| fallback.get_variables())
+                    .unwrap_or_default();
+                variables.insert(scoped.name.clone());
+                variables
+            }
             FormatElement::TextGroup(textgroup) => textgroup.format.get_variables(),
             FormatElement::Conditional(format) => format.get_variables(),
             _ => Default::default(),
@@ -86,7 +157,7 @@ impl<'a> StyleVariableHolder<Cow<'a, str>> for Vec<StyleElement<'a>> {
     }
 }
 
-impl<'a> StyleVariableHolder<Cow<'a, str>> for Vec<FormatElement<'a>> {
+impl<'a> StyleVariableHolder<Cow<'a, str>> for [FormatElement<'a>] {
     fn get_style_variables(&self) -> BTreeSet<Cow<'a, str>> {
         self.iter().fold(BTreeSet::new(), |mut acc, el| match el {
             FormatElement::TextGroup(textgroup) => {
@@ -97,6 +168,13 @@ impl<'a> StyleVariableHolder<Cow<'a, str>> for Vec<FormatElement<'a>> {
                 acc.extend(format.get_style_variables());
                 acc
             }
+            FormatElement::Scoped(ScopedVariable {
+                fallback: Some(format),
+                ..
+            }) => {
+                acc.extend(format.get_style_variables());
+                acc
+            }
             _ => acc,
         })
     }
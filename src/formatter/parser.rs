@@ -1,4 +1,5 @@
-use pest::{error::Error, iterators::Pair, Parser};
+use pest::error::{Error, ErrorVariant};
+use pest::{iterators::Pair, Parser};
 use pest_derive::*;
 
 use super::model::*;
@@ -7,30 +8,78 @@ use super::model::*;
 #[grammar = "formatter/spec.pest"]
 struct IdentParser;
 
-fn parse_value(value: Pair<Rule>) -> FormatElement {
-    match value.as_rule() {
-        Rule::text => FormatElement::Text(parse_text(value).into()),
-        Rule::variable => FormatElement::Variable(parse_variable(value).into()),
-        Rule::textgroup => FormatElement::TextGroup(parse_textgroup(value)),
+fn parse_value(value: Pair<Rule>) -> Result<FormatElement, Box<Error<Rule>>> {
+    Ok(match value.as_rule() {
+        Rule::text | Rule::fallback_text => FormatElement::Text(parse_text(value).into()),
+        Rule::variable => parse_scoped_variable(value)?,
+        Rule::textgroup => FormatElement::TextGroup(parse_textgroup(value)?),
         Rule::conditional => {
-            FormatElement::Conditional(parse_format(value.into_inner().next().unwrap()))
+            FormatElement::Conditional(parse_format(value.into_inner().next().unwrap())?)
         }
         _ => unreachable!(),
+    })
+}
+
+fn parse_scoped_variable(variable: Pair<Rule>) -> Result<FormatElement, Box<Error<Rule>>> {
+    let mut inner_rules = variable.into_inner();
+    let name = inner_rules.next().unwrap().as_str();
+
+    let mut modifiers = Vec::new();
+    let mut fallback = None;
+    for pair in inner_rules {
+        match pair.as_rule() {
+            Rule::modifier_name => modifiers.push(parse_modifier(pair)?),
+            Rule::fallback => fallback = Some(parse_format(pair)?),
+            _ => unreachable!(),
+        }
+    }
+
+    if modifiers.is_empty() && fallback.is_none() {
+        return Ok(FormatElement::Variable(name.into()));
     }
+    Ok(FormatElement::Scoped(ScopedVariable {
+        name: name.into(),
+        modifiers,
+        fallback,
+    }))
 }
 
-fn parse_textgroup(textgroup: Pair<Rule>) -> TextGroup {
+fn parse_modifier(modifier: Pair<Rule>) -> Result<Modifier, Box<Error<Rule>>> {
+    let name = modifier.as_str();
+    let with_length = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .and_then(|length| length.parse::<usize>().ok())
+    };
+
+    match name {
+        "upper" => Ok(Modifier::Upper),
+        "lower" => Ok(Modifier::Lower),
+        _ => with_length("trunc")
+            .map(Modifier::Trunc)
+            .or_else(|| with_length("pad").map(Modifier::Pad))
+            .ok_or_else(|| {
+                Box::new(Error::new_from_span(
+                    ErrorVariant::CustomError {
+                        message: format!("Unknown modifier `{}`", name),
+                    },
+                    modifier.as_span(),
+                ))
+            }),
+    }
+}
+
+fn parse_textgroup(textgroup: Pair<Rule>) -> Result<TextGroup, Box<Error<Rule>>> {
     let mut inner_rules = textgroup.into_inner();
     let format = inner_rules.next().unwrap();
     let style = inner_rules.next().unwrap();
 
-    TextGroup {
-        format: parse_format(format),
+    Ok(TextGroup {
+        format: parse_format(format)?,
         style: parse_style(style),
-    }
+    })
 }
 
-fn parse_variable(variable: Pair<Rule>) -> &str {
+fn parse_variable(variable: Pair<'_, Rule>) -> &str {
     variable.into_inner().next().unwrap().as_str()
 }
 
@@ -41,7 +90,7 @@ fn parse_text(text: Pair<Rule>) -> String {
         .collect()
 }
 
-fn parse_format(format: Pair<Rule>) -> Vec<FormatElement> {
+fn parse_format(format: Pair<Rule>) -> Result<Vec<FormatElement>, Box<Error<Rule>>> {
     format.into_inner().map(parse_value).collect()
 }
 
@@ -56,11 +105,13 @@ fn parse_style(style: Pair<Rule>) -> Vec<StyleElement> {
         .collect()
 }
 
-pub fn parse(format: &str) -> Result<Vec<FormatElement>, Error<Rule>> {
-    IdentParser::parse(Rule::expression, format).map(|pairs| {
-        pairs
-            .take_while(|pair| pair.as_rule() != Rule::EOI)
-            .map(parse_value)
-            .collect()
-    })
+pub fn parse(format: &str) -> Result<Vec<FormatElement<'_>>, Box<Error<Rule>>> {
+    IdentParser::parse(Rule::expression, format)
+        .map_err(Box::new)
+        .and_then(|pairs| {
+            pairs
+                .take_while(|pair| pair.as_rule() != Rule::EOI)
+                .map(parse_value)
+                .collect()
+        })
 }
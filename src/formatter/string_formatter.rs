@@ -1,13 +1,17 @@
 use ansi_term::Style;
-use pest::error::Error as PestError;
+use once_cell::sync::Lazy;
+use pest::error::{Error as PestError, InputLocation, LineColLocation};
 use rayon::prelude::*;
 use std::borrow::Cow;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::error::Error;
 use std::fmt;
 use std::iter::FromIterator;
+use std::sync::{Arc, Mutex};
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::config::parse_style_string;
+use crate::config::parse_style_string_with_base;
+use crate::context::Context;
 use crate::segment::Segment;
 
 use super::model::*;
@@ -17,7 +21,7 @@ use super::parser::{parse, Rule};
 enum VariableValue<'a> {
     Plain(Cow<'a, str>),
     Styled(Vec<Segment>),
-    Meta(Vec<FormatElement<'a>>),
+    Meta(Arc<[FormatElement<'static>]>),
 }
 
 impl<'a> Default for VariableValue<'a> {
@@ -34,7 +38,7 @@ type StyleVariableMapType<'a> =
 #[derive(Debug, Clone)]
 pub enum StringFormatterError {
     Custom(String),
-    Parse(PestError<Rule>),
+    Parse(ParseError),
 }
 
 impl fmt::Display for StringFormatterError {
@@ -46,6 +50,58 @@ impl fmt::Display for StringFormatterError {
     }
 }
 
+/// A format string that failed to parse, along with where it failed
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// Byte offset of the failure in the format string
+    pub offset: usize,
+    /// Line and column of the failure, both starting from 1
+    pub line_col: (usize, usize),
+    /// The text found at the failure, up to the next whitespace. Empty at the end of the string.
+    pub token: String,
+    /// The underlying error, which renders the format string with a caret under the failure
+    pub error: Box<PestError<Rule>>,
+}
+
+impl ParseError {
+    fn new(format: &str, error: Box<PestError<Rule>>) -> Self {
+        let (offset, end) = match error.location {
+            InputLocation::Pos(pos) => (pos, None),
+            InputLocation::Span((start, end)) => (start, Some(end)),
+        };
+        let line_col = match error.line_col {
+            LineColLocation::Pos(line_col) | LineColLocation::Span(line_col, _) => line_col,
+        };
+        let rest = format.get(offset..).unwrap_or_default();
+        let token = match end {
+            Some(end) if end > offset => format.get(offset..end).unwrap_or(rest),
+            _ => match rest.chars().next() {
+                Some(c) if c.is_whitespace() => &rest[..c.len_utf8()],
+                _ => rest.split(char::is_whitespace).next().unwrap_or_default(),
+            },
+        };
+
+        Self {
+            offset,
+            line_col,
+            token: token.to_owned(),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, column) = self.line_col;
+        if self.token.is_empty() {
+            write!(f, "Unexpected end of format string")?;
+        } else {
+            write!(f, "Unexpected `{}`", self.token)?;
+        }
+        write!(f, " at line {}, column {}:\n{}", line, column, self.error)
+    }
+}
+
 impl Error for StringFormatterError {}
 
 impl From<String> for StringFormatterError {
@@ -54,10 +110,36 @@ impl From<String> for StringFormatterError {
     }
 }
 
+/// Format strings that were already parsed, keyed by their source. Every prompt renders the same
+/// format strings, so each of them is only parsed once per process, and formatters share the
+/// parsed elements instead of copying them.
+static PARSED_FORMATS: Lazy<Mutex<HashMap<String, Arc<[FormatElement<'static>]>>>> =
+    Lazy::new(Default::default);
+
+fn parse_cached(format: &str) -> Result<Arc<[FormatElement<'static>]>, Box<PestError<Rule>>> {
+    let cached = PARSED_FORMATS
+        .lock()
+        .ok()
+        .and_then(|parsed_formats| parsed_formats.get(format).cloned());
+    if let Some(parsed) = cached {
+        return Ok(parsed);
+    }
+
+    let parsed: Arc<[FormatElement<'static>]> = into_owned_elements(parse(format)?).into();
+    if let Ok(mut parsed_formats) = PARSED_FORMATS.lock() {
+        parsed_formats.insert(format.to_owned(), Arc::clone(&parsed));
+    }
+    Ok(parsed)
+}
+
+/// Prefix of the variables that are read from the environment, e.g. `${env:HOST}`
+pub const ENV_VARIABLE_PREFIX: &str = "env:";
+
 pub struct StringFormatter<'a> {
-    format: Vec<FormatElement<'a>>,
+    format: Arc<[FormatElement<'static>]>,
     variables: VariableMapType<'a>,
     style_variables: StyleVariableMapType<'a>,
+    context: Option<&'a Context<'a>>,
 }
 
 impl<'a> StringFormatter<'a> {
@@ -65,11 +147,11 @@ impl<'a> StringFormatter<'a> {
     ///
     /// This method will throw an Error when the given format string fails to parse.
     pub fn new(format: &'a str) -> Result<Self, StringFormatterError> {
-        parse(format)
+        parse_cached(format)
             .map(|format| {
                 // Cache all variables
                 let variables = VariableMapType::from_iter(
-                    format
+                    (&format[..])
                         .get_variables()
                         .into_iter()
                         .map(|key| (key.to_string(), None))
@@ -88,8 +170,23 @@ impl<'a> StringFormatter<'a> {
                 format,
                 variables,
                 style_variables,
+                context: None,
             })
-            .map_err(StringFormatterError::Parse)
+            .map_err(|error| StringFormatterError::Parse(ParseError::new(format, error)))
+    }
+
+    /// Creates an instance of StringFormatter from a format string, reading the `env:`-prefixed
+    /// variables from the environment of `context`
+    ///
+    /// This method will throw an Error when the given format string fails to parse.
+    pub fn new_with_context(
+        format: &'a str,
+        context: &'a Context<'a>,
+    ) -> Result<Self, StringFormatterError> {
+        Self::new(format).map(|formatter| Self {
+            context: Some(context),
+            ..formatter
+        })
     }
 
     /// Maps variable name to its value
@@ -144,6 +241,7 @@ impl<'a> StringFormatter<'a> {
                                 format,
                                 mut variables,
                                 mut style_variables,
+                                ..
                             } = formatter;
 
                             // Add variables in meta variables to self
@@ -180,6 +278,26 @@ impl<'a> StringFormatter<'a> {
         self
     }
 
+    /// Maps every `env:`-prefixed variable left unmapped, e.g. `${env:HOST}`, to the value of
+    /// that environment variable in the context, or to an empty string if it is unset.
+    fn map_env(mut self) -> Self {
+        let context = match self.context {
+            Some(context) => context,
+            None => return self,
+        };
+        self.variables
+            .par_iter_mut()
+            .filter(|(_, value)| value.is_none())
+            .for_each(|(key, value)| {
+                *value = key.strip_prefix(ENV_VARIABLE_PREFIX).map(|name| {
+                    Ok(VariableValue::Plain(
+                        context.get_env(name).unwrap_or_default().into(),
+                    ))
+                });
+            });
+        self
+    }
+
     /// Maps variable name in a style string to its value
     ///
     /// See `StringFormatter::map` for description on the parameters.
@@ -204,28 +322,39 @@ impl<'a> StringFormatter<'a> {
     /// - Format string in meta variables fails to parse
     /// - Variable mapper returns an error.
     pub fn parse(self, default_style: Option<Style>) -> Result<Vec<Segment>, StringFormatterError> {
+        let formatter = self.map_env();
+        // Modules routinely leave optional variables unmapped, so this is only worth seeing
+        // while tracking down a misspelled variable
+        if log::log_enabled!(log::Level::Trace) {
+            if let Some(message) = formatter.unmapped_variables_message() {
+                log::trace!("{}", message);
+            }
+        }
+
         fn parse_textgroup<'a>(
-            textgroup: TextGroup<'a>,
+            textgroup: &TextGroup,
+            parent_style: Option<Style>,
             variables: &'a VariableMapType<'a>,
             style_variables: &'a StyleVariableMapType<'a>,
         ) -> Result<Vec<Segment>, StringFormatterError> {
-            let style = parse_style(textgroup.style, style_variables);
+            let style = parse_style(&textgroup.style, parent_style, style_variables);
             parse_format(
-                textgroup.format,
+                &textgroup.format,
                 style.transpose()?,
-                &variables,
-                &style_variables,
+                variables,
+                style_variables,
             )
         }
 
         fn parse_style<'a>(
-            style: Vec<StyleElement>,
+            style: &[StyleElement],
+            parent_style: Option<Style>,
             variables: &'a StyleVariableMapType<'a>,
         ) -> Option<Result<Style, StringFormatterError>> {
             let style_strings = style
-                .into_iter()
+                .iter()
                 .map(|style| match style {
-                    StyleElement::Text(text) => Ok(text),
+                    StyleElement::Text(text) => Ok(Cow::Borrowed(text.as_ref())),
                     StyleElement::Variable(name) => {
                         let variable = variables.get(name.as_ref()).unwrap_or(&None);
                         match variable {
@@ -239,62 +368,88 @@ impl<'a> StringFormatter<'a> {
                 .map(|style_strings| {
                     let style_string: String =
                         style_strings.iter().flat_map(|s| s.chars()).collect();
-                    parse_style_string(&style_string)
+                    // Attributes not set by the group are inherited from the enclosing style
+                    parse_style_string_with_base(&style_string, parent_style.unwrap_or_default())
                 })
                 .transpose()
         }
 
+        fn parse_variable<'a>(
+            name: &str,
+            style: Option<Style>,
+            variables: &'a VariableMapType<'a>,
+            style_variables: &'a StyleVariableMapType<'a>,
+        ) -> Result<Vec<Segment>, StringFormatterError> {
+            variables
+                .get(name)
+                .expect("Uncached variable found")
+                .as_ref()
+                .map(|segments| match segments.clone()? {
+                    VariableValue::Styled(segments) => Ok(segments
+                        .into_iter()
+                        .map(|mut segment| {
+                            // Derive upper style if the style of segments are none, and
+                            // otherwise let the segments override only what they specify.
+                            segment.style = match (style, segment.style) {
+                                (Some(parent), Some(child)) => Some(merge_styles(parent, child)),
+                                (parent, child) => child.or(parent),
+                            };
+                            segment
+                        })
+                        .collect()),
+                    VariableValue::Plain(text) => Ok(vec![Segment::new(style, text)]),
+                    VariableValue::Meta(format) => {
+                        let formatter = StringFormatter {
+                            format,
+                            variables: clone_without_meta(variables),
+                            style_variables: style_variables.clone(),
+                            context: None,
+                        };
+                        formatter.parse(style)
+                    }
+                })
+                .unwrap_or_else(|| Ok(Vec::new()))
+        }
+
         fn parse_format<'a>(
-            format: Vec<FormatElement<'a>>,
+            format: &[FormatElement],
             style: Option<Style>,
             variables: &'a VariableMapType<'a>,
             style_variables: &'a StyleVariableMapType<'a>,
         ) -> Result<Vec<Segment>, StringFormatterError> {
             let results: Result<Vec<Vec<Segment>>, StringFormatterError> = format
-                .into_iter()
+                .iter()
                 .map(|el| {
                     match el {
-                        FormatElement::Text(text) => Ok(vec![Segment::new(style, text)]),
+                        FormatElement::Text(text) => Ok(vec![Segment::new(style, text.as_ref())]),
                         FormatElement::TextGroup(textgroup) => {
-                            let textgroup = TextGroup {
-                                format: textgroup.format,
-                                style: textgroup.style,
-                            };
-                            parse_textgroup(textgroup, &variables, &style_variables)
+                            parse_textgroup(textgroup, style, variables, style_variables)
+                        }
+                        FormatElement::Variable(name) => {
+                            parse_variable(name, style, variables, style_variables)
                         }
-                        FormatElement::Variable(name) => variables
-                            .get(name.as_ref())
-                            .expect("Uncached variable found")
-                            .as_ref()
-                            .map(|segments| match segments.clone()? {
-                                VariableValue::Styled(segments) => Ok(segments
-                                    .into_iter()
-                                    .map(|mut segment| {
-                                        // Derive upper style if the style of segments are none.
-                                        if segment.style.is_none() {
-                                            segment.style = style;
-                                        };
-                                        segment
-                                    })
-                                    .collect()),
-                                VariableValue::Plain(text) => Ok(vec![Segment::new(style, text)]),
-                                VariableValue::Meta(format) => {
-                                    let formatter = StringFormatter {
-                                        format,
-                                        variables: clone_without_meta(variables),
-                                        style_variables: style_variables.clone(),
-                                    };
-                                    formatter.parse(style)
+                        FormatElement::Scoped(scoped) => {
+                            let segments =
+                                parse_variable(&scoped.name, style, variables, style_variables)?;
+                            let is_empty = segments.iter().all(|segment| segment.value.is_empty());
+                            match &scoped.fallback {
+                                Some(fallback) if is_empty => {
+                                    parse_format(fallback, style, variables, style_variables)
                                 }
-                            })
-                            .unwrap_or_else(|| Ok(Vec::new())),
+                                _ if is_empty => Ok(segments),
+                                _ => Ok(apply_modifiers(segments, &scoped.modifiers)),
+                            }
+                        }
                         FormatElement::Conditional(format) => {
-                            // Show the conditional format string if all the variables inside are not
-                            // none.
+                            // Show the conditional format string unless all the variables inside
+                            // are none or empty.
                             fn should_show_elements<'a>(
                                 format_elements: &[FormatElement],
                                 variables: &'a VariableMapType<'a>,
                             ) -> bool {
+                                if has_text_fallback(format_elements) {
+                                    return true;
+                                }
                                 format_elements.get_variables().iter().any(|var| {
                                     variables
                                         .get(var.as_ref())
@@ -309,14 +464,17 @@ impl<'a> StringFormatter<'a> {
                                                         let meta_variables =
                                                             clone_without_meta(variables);
                                                         should_show_elements(
-                                                            &meta_elements,
+                                                            meta_elements,
                                                             &meta_variables,
                                                         )
                                                     }
-                                                    _ => true,
+                                                    VariableValue::Plain(text) => !text.is_empty(),
+                                                    VariableValue::Styled(segments) => segments
+                                                        .iter()
+                                                        .any(|segment| !segment.value.is_empty()),
                                                 })
-                                                // The variable is None or Err, or a meta variable
-                                                // that shouldn't show
+                                                // The variable is None or Err, empty, or a meta
+                                                // variable that shouldn't show
                                                 .unwrap_or(false)
                                         })
                                         // Can't find the variable in format string
@@ -324,7 +482,7 @@ impl<'a> StringFormatter<'a> {
                                 })
                             }
 
-                            let should_show: bool = should_show_elements(&format, variables);
+                            let should_show: bool = should_show_elements(format, variables);
 
                             if should_show {
                                 parse_format(format, style, variables, style_variables)
@@ -339,14 +497,99 @@ impl<'a> StringFormatter<'a> {
         }
 
         parse_format(
-            self.format,
+            &formatter.format,
             default_style,
-            &self.variables,
-            &self.style_variables,
+            &formatter.variables,
+            &formatter.style_variables,
         )
     }
 }
 
+/// Layers `child` on top of `parent`: colors set in `child` win, and text attributes are combined
+fn merge_styles(parent: Style, child: Style) -> Style {
+    Style {
+        foreground: child.foreground.or(parent.foreground),
+        background: child.background.or(parent.background),
+        is_bold: parent.is_bold || child.is_bold,
+        is_dimmed: parent.is_dimmed || child.is_dimmed,
+        is_italic: parent.is_italic || child.is_italic,
+        is_underline: parent.is_underline || child.is_underline,
+        is_blink: parent.is_blink || child.is_blink,
+        is_reverse: parent.is_reverse || child.is_reverse,
+        is_hidden: parent.is_hidden || child.is_hidden,
+        is_strikethrough: parent.is_strikethrough || child.is_strikethrough,
+    }
+}
+
+/// Applies the modifiers of a variable to the segments of its value, in order
+fn apply_modifiers(segments: Vec<Segment>, modifiers: &[Modifier]) -> Vec<Segment> {
+    modifiers
+        .iter()
+        .fold(segments, |mut segments, modifier| match modifier {
+            Modifier::Upper => {
+                for segment in segments.iter_mut() {
+                    segment.value = segment.value.to_uppercase();
+                }
+                segments
+            }
+            Modifier::Lower => {
+                for segment in segments.iter_mut() {
+                    segment.value = segment.value.to_lowercase();
+                }
+                segments
+            }
+            Modifier::Trunc(length) => {
+                let mut remaining = *length;
+                for segment in segments.iter_mut() {
+                    let graphemes: Vec<&str> = segment.value.graphemes(true).collect();
+                    let kept = graphemes.len().min(remaining);
+                    remaining -= kept;
+                    segment.value = graphemes[..kept].concat();
+                }
+                segments
+            }
+            Modifier::Pad(length) => {
+                let width: usize = segments
+                    .iter()
+                    .map(|segment| segment.value.graphemes(true).count())
+                    .sum();
+                if let Some(last) = segments.last_mut() {
+                    if width < *length {
+                        last.value.push_str(&" ".repeat(length - width));
+                    }
+                }
+                segments
+            }
+        })
+}
+
+impl<'a> StringFormatter<'a> {
+    /// Names the variables that no mapper gave a value, which are dropped when rendering, along
+    /// with the variables that do have one. A misspelled variable ends up in the former.
+    fn unmapped_variables_message(&self) -> Option<String> {
+        let (unmapped, mapped): (Vec<_>, Vec<_>) = self
+            .variables
+            .iter()
+            .partition(|(_, value)| value.is_none());
+        if unmapped.is_empty() {
+            return None;
+        }
+
+        let names = |variables: Vec<(&String, _)>| {
+            variables
+                .into_iter()
+                .map(|(name, _)| format!("`${}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Some(format!(
+            "No value for variables {}, which will be empty. Variables with a value: {}",
+            names(unmapped),
+            names(mapped)
+        ))
+    }
+}
+
 impl<'a> VariableHolder<String> for StringFormatter<'a> {
     fn get_variables(&self) -> BTreeSet<String> {
         self.variables.keys().cloned().collect()
@@ -359,6 +602,33 @@ impl<'a> StyleVariableHolder<String> for StringFormatter<'a> {
     }
 }
 
+/// Whether any variable in `format_elements` falls back to text, like `${venv:-system}`. Either
+/// the variable or its fallback is shown, so a conditional containing it is never empty.
+fn has_text_fallback(format_elements: &[FormatElement]) -> bool {
+    format_elements.iter().any(|element| match element {
+        FormatElement::Scoped(ScopedVariable {
+            fallback: Some(fallback),
+            ..
+        }) => has_text(fallback),
+        FormatElement::TextGroup(textgroup) => has_text_fallback(&textgroup.format),
+        FormatElement::Conditional(format) => has_text_fallback(format),
+        _ => false,
+    })
+}
+
+/// Whether `format_elements` always render some text, whatever their variables are
+fn has_text(format_elements: &[FormatElement]) -> bool {
+    format_elements.iter().any(|element| match element {
+        FormatElement::Text(text) => !text.is_empty(),
+        FormatElement::TextGroup(textgroup) => has_text(&textgroup.format),
+        FormatElement::Scoped(ScopedVariable {
+            fallback: Some(fallback),
+            ..
+        }) => has_text(fallback),
+        _ => false,
+    })
+}
+
 fn clone_without_meta<'a>(variables: &VariableMapType<'a>) -> VariableMapType<'a> {
     variables
         .iter()
@@ -457,6 +727,206 @@ mod tests {
         match_next!(result_iter, "${env:PWD}", None);
     }
 
+    #[test]
+    fn test_fallback_for_missing_variable() {
+        const FORMAT_STR: &str = "${virtualenv:-system} ${version:-unknown}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "version" => Some(Ok("v1")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "system", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "v1", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_fallback_for_empty_variable() {
+        const FORMAT_STR: &str = "${empty:-fallback}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "empty" => Some(Ok("")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "fallback", None);
+    }
+
+    #[test]
+    fn test_fallback_format_string() {
+        const FORMAT_STR: &str = "[${none:-[none](red) $other}](bold)";
+        let bold = Some(Style::new().bold());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "other" => Some(Ok("other")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "none", Some(Color::Red.bold()));
+        match_next!(result_iter, " ", bold);
+        match_next!(result_iter, "other", bold);
+    }
+
+    #[test]
+    fn test_fallback_escaped_brace() {
+        const FORMAT_STR: &str = r"${none:-{\}\$\(\)}";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None).unwrap();
+        let value: String = result
+            .iter()
+            .map(|segment| segment.value.as_str())
+            .collect();
+        assert_eq!(value, "{}$()");
+    }
+
+    #[test]
+    fn test_fallback_unclosed_is_error() {
+        assert!(StringFormatter::new("${none:-fallback").is_err());
+        assert!(StringFormatter::new(r"${none:-fallback\}").is_err());
+    }
+
+    #[test]
+    fn test_scoped_variable_without_fallback() {
+        const FORMAT_STR: &str = "${env:HOST}${a-b}${c:d-e}";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap();
+        let expected_variables = BTreeSet::from_iter(
+            vec!["env:HOST", "a-b", "c:d-e"]
+                .into_iter()
+                .map(String::from),
+        );
+        assert_eq!(formatter.get_variables(), expected_variables);
+    }
+
+    #[test]
+    fn test_fallback_variable_holder() {
+        const FORMAT_STR: &str = "${a:-$b [c]($s)}";
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap();
+
+        let expected_variables = BTreeSet::from_iter(vec!["a", "b"].into_iter().map(String::from));
+        assert_eq!(formatter.get_variables(), expected_variables);
+        let expected_style_variables = BTreeSet::from_iter(vec![String::from("s")]);
+        assert_eq!(formatter.get_style_variables(), expected_style_variables);
+    }
+
+    #[test]
+    fn test_case_modifiers() {
+        const FORMAT_STR: &str = "${a|upper} ${b|lower}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "a" => Some(Ok("Main")),
+                "b" => Some(Ok("Ünïcode")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "MAIN", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "ünïcode", None);
+    }
+
+    #[test]
+    fn test_trunc_modifier() {
+        const FORMAT_STR: &str = "${hash|trunc7} ${short|trunc7} ${emoji|trunc2}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "hash" => Some(Ok("35dc38f922e4b6d6")),
+                "short" => Some(Ok("abc")),
+                "emoji" => Some(Ok("👩‍👩‍👦🇬🇧a̐")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "35dc38f", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "abc", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "👩‍👩‍👦🇬🇧", None);
+    }
+
+    #[test]
+    fn test_trunc_modifier_across_segments() {
+        const FORMAT_STR: &str = "${segments|trunc4}";
+        let green = Some(Color::Green.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_variables_to_segments(|variable| match variable {
+                "segments" => Some(Ok(vec![
+                    Segment::new(None, "abc"),
+                    Segment::new(green, "def"),
+                ])),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "abc", None);
+        match_next!(result_iter, "d", green);
+    }
+
+    #[test]
+    fn test_pad_modifier() {
+        const FORMAT_STR: &str = "[${a|pad5}](red)|${b|pad2}|${none|pad3}|";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "a" => Some(Ok("目录")),
+                "b" => Some(Ok("long")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "目录   ", Some(Color::Red.normal()));
+        match_next!(result_iter, "|", None);
+        match_next!(result_iter, "long", None);
+        match_next!(result_iter, "|", None);
+        match_next!(result_iter, "|", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_chained_modifiers_with_fallback() {
+        const FORMAT_STR: &str = "${a|trunc3|upper:-none} ${b|upper:-none}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "a" => Some(Ok("feature")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "FEA", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "none", None);
+    }
+
+    #[test]
+    fn test_unknown_modifier() {
+        let error = StringFormatter::new("${branch|shout}").err().unwrap();
+        assert!(error.to_string().contains("Unknown modifier `shout`"));
+
+        assert!(StringFormatter::new("${hash|trunc}").is_err());
+        assert!(StringFormatter::new("${hash|trunc-1}").is_err());
+    }
+
     #[test]
     fn test_escaped_chars() {
         const FORMAT_STR: &str = r#"\\\[\$text\]\(red bold\)"#;
@@ -472,7 +942,7 @@ mod tests {
         const FORMAT_STR: &str = "outer [middle [inner](blue)](red bold)";
         let outer_style = Some(Color::Green.normal());
         let middle_style = Some(Color::Red.bold());
-        let inner_style = Some(Color::Blue.normal());
+        let inner_style = Some(Color::Blue.bold());
 
         let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
         let result = formatter.parse(outer_style).unwrap();
@@ -482,6 +952,42 @@ mod tests {
         match_next!(result_iter, "inner", inner_style);
     }
 
+    #[test]
+    fn test_nested_textgroup_inherits_attributes() {
+        const FORMAT_STR: &str = "[a [b [c](green)](underline)](bold red)";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a ", Some(Color::Red.bold()));
+        match_next!(result_iter, "b ", Some(Color::Red.bold().underline()));
+        match_next!(result_iter, "c", Some(Color::Green.bold().underline()));
+    }
+
+    #[test]
+    fn test_nested_textgroup_inherits_background() {
+        const FORMAT_STR: &str = "[a [b [c](bg:none)](yellow)](bg:blue)";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a ", Some(Style::new().on(Color::Blue)));
+        match_next!(result_iter, "b ", Some(Color::Yellow.on(Color::Blue)));
+        match_next!(result_iter, "c", Some(Color::Yellow.normal()));
+    }
+
+    #[test]
+    fn test_nested_textgroup_with_none() {
+        const FORMAT_STR: &str = "[outer [inner](none)](italic dimmed yellow)";
+        let outer_style = Some(Color::Yellow.italic().dimmed());
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "outer ", outer_style);
+        match_next!(result_iter, "inner", None);
+    }
+
     #[test]
     fn test_styled_variable_as_text() {
         const FORMAT_STR: &str = "[$var](red bold)";
@@ -518,8 +1024,9 @@ mod tests {
         let result = formatter.parse(None).unwrap();
         let mut result_iter = result.iter();
         match_next!(result_iter, "styless", var_style);
-        match_next!(result_iter, "styled", styled_style);
-        match_next!(result_iter, "styled_no_modifier", styled_no_modifier_style);
+        // Styled segments keep their own colors but inherit the enclosing attributes
+        match_next!(result_iter, "styled", Some(Color::Green.bold().italic()));
+        match_next!(result_iter, "styled_no_modifier", Some(Color::Green.bold()));
     }
 
     #[test]
@@ -603,6 +1110,110 @@ mod tests {
         match_next!(result_iter, "$some", None);
     }
 
+    #[test]
+    fn test_conditional_empty_variable() {
+        const FORMAT_STR: &str = "via $version( \\($empty\\))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "version" => Some(Ok("v1")),
+                "empty" => Some(Ok("")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "via ", None);
+        match_next!(result_iter, "v1", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_conditional_with_fallback() {
+        const FORMAT_STR: &str = "(${venv:-system})( \\(${empty:-$none}\\))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "empty" => Some(Ok("")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "system", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_conditional_empty_styled_variable() {
+        const FORMAT_STR: &str = "(\\[$segments\\])";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_variables_to_segments(|var| match var {
+                "segments" => Some(Ok(vec![Segment::new(None, "")])),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_conditional_textgroup() {
+        const FORMAT_STR: &str = "($none[$count](green)) end";
+        let count_style = Some(Color::Green.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "count" => Some(Ok("1")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "1", count_style);
+        match_next!(result_iter, " end", None);
+
+        let formatter = StringFormatter::new("([+$count](green))")
+            .unwrap()
+            .map(|var| match var {
+                "count" => Some(Ok("")),
+                _ => None,
+            });
+        assert!(formatter.parse(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nested_conditional_with_empty_variables() {
+        const FORMAT_STR: &str = "(outer ($empty) $some) ($empty ($empty))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some(Ok("$some")),
+                "empty" => Some(Ok("")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "outer ", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "$some", None);
+        match_next!(result_iter, " ", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_escaped_parens_are_not_conditional() {
+        const FORMAT_STR: &str = r"\($none\)";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "(", None);
+        match_next!(result_iter, ")", None);
+    }
+
     #[test]
     fn test_conditional_meta_variable() {
         const FORMAT_STR: &str = r"(\[$all\]) ";
@@ -639,6 +1250,91 @@ mod tests {
         assert_eq!(variables, expected_variables);
     }
 
+    #[test]
+    fn test_parse_error_position() {
+        let error = match StringFormatter::new("[$a](red) $-1 [b](blue)")
+            .err()
+            .unwrap()
+        {
+            StringFormatterError::Parse(error) => error,
+            error => panic!("Unexpected error: {}", error),
+        };
+        assert_eq!(error.offset, 11);
+        assert_eq!(error.line_col, (1, 12));
+        assert_eq!(error.token, "-1");
+
+        let message = error.to_string();
+        assert!(message.starts_with("Unexpected `-1` at line 1, column 12:\n"));
+        assert!(message.contains("1 | [$a](red) $-1 [b](blue)\n"));
+        assert!(message.contains("  |            ^---"));
+    }
+
+    #[test]
+    fn test_parse_error_at_end() {
+        let error = StringFormatter::new("line one\n${a").err().unwrap();
+        let message = error.to_string();
+        assert!(message.starts_with("Unexpected end of format string at line 2, column 4:\n"));
+        assert!(message.contains("2 | ${a"));
+    }
+
+    #[test]
+    fn test_unknown_modifier_position() {
+        let error = match StringFormatter::new("on ${branch|shout}").err().unwrap() {
+            StringFormatterError::Parse(error) => error,
+            error => panic!("Unexpected error: {}", error),
+        };
+        assert_eq!(error.offset, 12);
+        assert_eq!(error.token, "shout");
+        assert!(error
+            .to_string()
+            .starts_with("Unexpected `shout` at line 1, column 13:\n"));
+    }
+
+    #[test]
+    fn test_unmapped_variables_message() {
+        let formatter = StringFormatter::new("$branch $brnach $commit")
+            .unwrap()
+            .map(|variable| match variable {
+                "branch" => Some(Ok("master")),
+                "commit" => Some(Ok("abc1234")),
+                _ => None,
+            });
+        assert_eq!(
+            formatter.unmapped_variables_message().unwrap(),
+            "No value for variables `$brnach`, which will be empty. \
+             Variables with a value: `$branch`, `$commit`"
+        );
+
+        let formatter = StringFormatter::new("$branch")
+            .unwrap()
+            .map(|_| Some(Ok("master")));
+        assert_eq!(formatter.unmapped_variables_message(), None);
+    }
+
+    #[test]
+    fn test_parsed_formats_are_cached() {
+        let format = String::from("[cached $var](red) ${other:-fallback}");
+        let formatter = StringFormatter::new(&format).unwrap();
+        assert!(PARSED_FORMATS.lock().unwrap().contains_key(&format));
+        drop(formatter);
+
+        // The cached elements don't borrow from the original format string
+        let copy = format.clone();
+        drop(format);
+        let formatter = StringFormatter::new(&copy)
+            .unwrap()
+            .map(|variable| match variable {
+                "var" => Some(Ok("value")),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "cached ", Some(Color::Red.normal()));
+        match_next!(result_iter, "value", Some(Color::Red.normal()));
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "fallback", None);
+    }
+
     #[test]
     fn test_parse_error() {
         // brackets without escape
@@ -0,0 +1,111 @@
+use super::string_formatter::StringFormatterError;
+use super::StringFormatter;
+
+/// Formats a version according to a `version_format` template
+///
+/// The template can use the following variables:
+///
+/// - `raw`: The version as it was given
+/// - `major`, `minor` and `patch`: The numeric components of the version, when it has them
+pub struct VersionFormatter<'a> {
+    formatter: StringFormatter<'a>,
+}
+
+impl<'a> VersionFormatter<'a> {
+    /// Creates an instance of a VersionFormatter from a `version_format` template
+    ///
+    /// This method will throw an Error when the given template fails to parse.
+    pub fn new(version_format: &'a str) -> Result<Self, StringFormatterError> {
+        let formatter = StringFormatter::new(version_format)?;
+
+        Ok(Self { formatter })
+    }
+
+    /// Formats a version string using the template
+    pub fn format_version(self, version: &str) -> Result<String, StringFormatterError> {
+        // Only the leading digits of a component count, e.g. `0` for the patch of `3.12.0rc1`
+        let mut components = version
+            .split('.')
+            .map(|component| {
+                let digits = component.len()
+                    - component
+                        .trim_start_matches(|c: char| c.is_ascii_digit())
+                        .len();
+                component[..digits].parse::<u64>().ok()
+            })
+            .take_while(Option::is_some)
+            .flatten();
+        let major = components.next();
+        let minor = components.next();
+        let patch = components.next();
+
+        let formatted = self
+            .formatter
+            .map(|variable| match variable {
+                "raw" => Some(Ok(version.to_string())),
+                "major" => major.map(|v| Ok(v.to_string())),
+                "minor" => minor.map(|v| Ok(v.to_string())),
+                "patch" => patch.map(|v| Ok(v.to_string())),
+                _ => None,
+            })
+            .parse(None);
+
+        formatted.map(|segments| segments.into_iter().map(|segment| segment.value).collect())
+    }
+
+    /// Formats the version of a module, logging a warning and falling back to the raw version
+    /// when `version_format` is invalid.
+    pub fn format_module_version(module_name: &str, version: &str, version_format: &str) -> String {
+        match VersionFormatter::new(version_format)
+            .and_then(|formatter| formatter.format_version(version))
+        {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                log::warn!("Error formatting `{}` version:\n{}", module_name, error);
+                version.to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION: &str = "3.2.1-pre.2";
+
+    #[test]
+    fn test_semver_components() {
+        let formatted =
+            VersionFormatter::new("major:${major} minor:${minor} patch:${patch} raw:${raw}")
+                .unwrap()
+                .format_version(VERSION)
+                .unwrap();
+        assert_eq!(formatted, "major:3 minor:2 patch:1 raw:3.2.1-pre.2");
+    }
+
+    #[test]
+    fn test_missing_components() {
+        let formatter = || VersionFormatter::new("v${major}(.${minor})(.${patch})").unwrap();
+        assert_eq!(formatter().format_version("1.2").unwrap(), "v1.2");
+        assert_eq!(formatter().format_version("22").unwrap(), "v22");
+        assert_eq!(formatter().format_version("nightly").unwrap(), "v");
+    }
+
+    #[test]
+    fn test_components_with_suffixes() {
+        let formatter = || VersionFormatter::new("${major}-${minor}-${patch}").unwrap();
+        assert_eq!(
+            formatter().format_version("1.52.0-nightly").unwrap(),
+            "1-52-0"
+        );
+        assert_eq!(formatter().format_version("3.12.0rc1").unwrap(), "3-12-0");
+        assert_eq!(formatter().format_version("2.5.1p57").unwrap(), "2-5-1");
+    }
+
+    #[test]
+    fn test_invalid_template_falls_back_to_raw() {
+        let formatted = VersionFormatter::format_module_version("test", VERSION, "v${raw");
+        assert_eq!(formatted, VERSION);
+    }
+}
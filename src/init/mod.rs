@@ -31,11 +31,20 @@ init code. The stub produces the main init script, then evaluates it with
 pub fn init_stub(shell_name: &str) -> io::Result<()> {
     log::debug!("Shell name: {}", shell_name);
 
-    let shell_basename = Path::new(shell_name).file_stem().and_then(OsStr::to_str);
-
     let starship = path_to_starship()?.replace("\"", "\"'\"'\"");
 
-    let setup_stub = match shell_basename {
+    if let Some(script) = get_setup_stub(shell_name, &starship) {
+        print!("{}", script);
+    };
+    Ok(())
+}
+
+/* Returns the setup stub for `shell_name`, where `starship` is the path to the
+starship binary, already escaped to be placed between double quotes */
+fn get_setup_stub(shell_name: &str, starship: &str) -> Option<String> {
+    let shell_basename = Path::new(shell_name).file_stem().and_then(OsStr::to_str);
+
+    match shell_basename {
         Some("bash") => {
             /*
              * The standard bash bootstrap is:
@@ -133,11 +142,7 @@ fi"#,
             );
             None
         }
-    };
-    if let Some(script) = setup_stub {
-        print!("{}", script);
-    };
-    Ok(())
+    }
 }
 
 /* This function (called when `--print-full-init` is passed to `starship init`)
@@ -145,6 +150,15 @@ prints out the main initialization script */
 pub fn init_main(shell_name: &str) -> io::Result<()> {
     let starship_path = path_to_starship()?.replace("\"", "\"'\"'\"");
 
+    if let Some(script) = get_main_script(shell_name, &starship_path) {
+        print!("{}", script);
+    };
+    Ok(())
+}
+
+/* Returns the main initialization script for `shell_name`, with `::STARSHIP::`
+replaced by `starship_path` between double quotes */
+fn get_main_script(shell_name: &str, starship_path: &str) -> Option<String> {
     let setup_script = match shell_name {
         "bash" => Some(BASH_INIT),
         "zsh" => Some(ZSH_INIT),
@@ -160,13 +174,10 @@ pub fn init_main(shell_name: &str) -> io::Result<()> {
             None
         }
     };
-    if let Some(script) = setup_script {
-        // Set up quoting for starship path in case it has spaces.
-        let starship_path_string = format!("\"{}\"", starship_path);
-        let script = script.replace("::STARSHIP::", &starship_path_string);
-        print!("{}", script);
-    };
-    Ok(())
+
+    // Set up quoting for starship path in case it has spaces.
+    let starship_path_string = format!("\"{}\"", starship_path);
+    setup_script.map(|script| script.replace("::STARSHIP::", &starship_path_string))
 }
 
 /* GENERAL INIT SCRIPT NOTES
@@ -193,3 +204,103 @@ const FISH_INIT: &str = include_str!("starship.fish");
 const PWSH_INIT: &str = include_str!("starship.ps1");
 
 const ION_INIT: &str = include_str!("starship.ion");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STARSHIP: &str = "/usr/local/bin/starship";
+
+    fn main_script(shell_name: &str) -> String {
+        get_main_script(shell_name, STARSHIP).expect("shell should be supported")
+    }
+
+    #[test]
+    fn setup_stubs_source_the_full_init() {
+        assert_eq!(
+            get_setup_stub("zsh", STARSHIP),
+            Some(r#"source <("/usr/local/bin/starship" init zsh --print-full-init)"#.to_owned())
+        );
+        assert_eq!(
+            get_setup_stub("/usr/bin/fish", STARSHIP),
+            Some(
+                r#"source ("/usr/local/bin/starship" init fish --print-full-init | psub)"#
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            get_setup_stub("powershell", STARSHIP),
+            Some(
+                r#"Invoke-Expression (@(&"/usr/local/bin/starship" init powershell --print-full-init) -join "`n")"#
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            get_setup_stub("ion", STARSHIP),
+            Some("eval $(/usr/local/bin/starship init ion --print-full-init)".to_owned())
+        );
+        let bash_stub = get_setup_stub("bash", STARSHIP).unwrap();
+        assert!(bash_stub
+            .contains(r#"source <("/usr/local/bin/starship" init bash --print-full-init)"#));
+        assert!(bash_stub.contains(
+            r#"source /dev/stdin <<<"$("/usr/local/bin/starship" init bash --print-full-init)""#
+        ));
+    }
+
+    #[test]
+    fn unsupported_shell_has_no_scripts() {
+        assert_eq!(get_setup_stub("tcsh", STARSHIP), None);
+        assert_eq!(get_main_script("tcsh", STARSHIP), None);
+    }
+
+    #[test]
+    fn main_scripts_call_the_starship_binary() {
+        for shell_name in &["bash", "zsh", "fish", "powershell", "ion"] {
+            let script = main_script(shell_name);
+            assert!(!script.contains("::STARSHIP::"), "{}", shell_name);
+            assert!(
+                script.contains(r#""/usr/local/bin/starship" prompt"#),
+                "{}",
+                shell_name
+            );
+        }
+    }
+
+    #[test]
+    fn bash_passes_status_and_duration() {
+        let script = main_script("bash");
+        assert!(script.contains("STATUS=$? STARSHIP_PIPE_STATUS=(${PIPESTATUS[@]})"));
+        assert!(script.contains("trap 'starship_preexec \"$_\"' DEBUG"));
+        assert!(script.contains("STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))"));
+        assert!(script
+            .contains("--status=$STATUS --pipestatus=\"${STARSHIP_PIPE_STATUS[*]}\" --jobs=\"$NUM_JOBS\" --cmd-duration=$STARSHIP_DURATION"));
+        assert!(script.contains("export STARSHIP_SHELL=\"bash\""));
+    }
+
+    #[test]
+    fn zsh_passes_status_and_duration() {
+        let script = main_script("zsh");
+        assert!(script.contains("STATUS=$? STARSHIP_PIPE_STATUS=(${pipestatus[@]})"));
+        assert!(script.contains("preexec_functions+=(starship_preexec)"));
+        assert!(script.contains("STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))"));
+        assert!(script.contains("--status=$STATUS --pipestatus=\"${STARSHIP_PIPE_STATUS[*]}\" --cmd-duration=${STARSHIP_DURATION-}"));
+        assert!(script.contains("export STARSHIP_SHELL=\"zsh\""));
+    }
+
+    #[test]
+    fn fish_passes_status_and_duration() {
+        let script = main_script("fish");
+        assert!(script.contains("set -g STARSHIP_CMD_STATUS $status"));
+        assert!(script.contains("set -g STARSHIP_DURATION \"$CMD_DURATION$cmd_duration\""));
+        assert!(script.contains("--status=$STARSHIP_CMD_STATUS"));
+        assert!(script.contains("--cmd-duration=$STARSHIP_DURATION"));
+        assert!(script.contains("export STARSHIP_SHELL=\"fish\""));
+    }
+
+    #[test]
+    fn powershell_passes_status_and_duration() {
+        let script = main_script("powershell");
+        assert!(script.contains("--status=$lastExitCodeForPrompt"));
+        assert!(script.contains("--cmd-duration=$duration"));
+    }
+}
@@ -21,6 +21,8 @@ starship_preexec() {
     if [ "$PREEXEC_READY" = "true" ]; then
         PREEXEC_READY=false
         STARSHIP_START_TIME=$(::STARSHIP:: time)
+        # The prompts drawn from now on follow a command
+        unset STARSHIP_FIRST_PROMPT
     fi
 
     : "$PREV_LAST_ARG"
@@ -28,9 +30,13 @@ starship_preexec() {
 
 # Will be run before the prompt is drawn
 starship_precmd() {
+    # Save the status first, because any other command, even `local`, will change $? and $PIPESTATUS
+    STATUS=$? STARSHIP_PIPE_STATUS=(${PIPESTATUS[@]})
     local NUM_JOBS
-    # Save the status, because commands in this pipeline will change $?
-    STATUS=$?
+    # bash-preexec runs its own commands first, but keeps the pipeline's status codes
+    if [[ ${BP_PIPESTATUS-} && ${#BP_PIPESTATUS[@]} -gt ${#STARSHIP_PIPE_STATUS[@]} ]]; then
+        STARSHIP_PIPE_STATUS=(${BP_PIPESTATUS[@]})
+    fi
 
     # Evaluate the number of jobs before running the preseved prompt command, so that tools
     # like z/autojump, which background certain jobs, do not cause spurious background jobs
@@ -46,10 +52,10 @@ starship_precmd() {
     if [[ $STARSHIP_START_TIME ]]; then
         STARSHIP_END_TIME=$(::STARSHIP:: time)
         STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))
-        PS1="$(::STARSHIP:: prompt --status=$STATUS --jobs="$NUM_JOBS" --cmd-duration=$STARSHIP_DURATION)"
+        PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS" --cmd-duration=$STARSHIP_DURATION)"
         unset STARSHIP_START_TIME
     else
-        PS1="$(::STARSHIP:: prompt --status=$STATUS --jobs="$NUM_JOBS")"
+        PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS")"
     fi
     PREEXEC_READY=true  # Signal that we can safely restart the timer
 }
@@ -91,6 +97,9 @@ fi
 STARSHIP_START_TIME=$(::STARSHIP:: time)
 export STARSHIP_SHELL="bash"
 
+# The first prompt of a session is never preceded by a new line
+export STARSHIP_FIRST_PROMPT=1
+
 # Set up the session key that will be used to store logs
 STARSHIP_SESSION_KEY="$RANDOM$RANDOM$RANDOM$RANDOM$RANDOM"; # Random generates a number b/w 0 - 32767
 STARSHIP_SESSION_KEY="${STARSHIP_SESSION_KEY}0000000000000000" # Pad it to 16+ chars.
@@ -1,14 +1,25 @@
 function fish_prompt
+    set -g STARSHIP_CMD_STATUS $status
     switch "$fish_key_bindings"
         case fish_hybrid_key_bindings fish_vi_key_bindings
-            set keymap "$fish_bind_mode"
+            set -g STARSHIP_KEYMAP "$fish_bind_mode"
         case '*'
-            set keymap insert
+            set -g STARSHIP_KEYMAP insert
     end
-    set -l exit_code $status
     # Account for changes in variable name between v2.7 and v3.0
-    set -l starship_duration "$CMD_DURATION$cmd_duration"
-    ::STARSHIP:: prompt --status=$exit_code --keymap=$keymap --cmd-duration=$starship_duration --jobs=(count (jobs -p))
+    set -g STARSHIP_DURATION "$CMD_DURATION$cmd_duration"
+    set -g STARSHIP_JOBS (count (jobs -p))
+    ::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
+end
+
+# Runs after fish_prompt, so it reuses the state that fish_prompt saved
+function fish_right_prompt
+    ::STARSHIP:: prompt --right --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
+end
+
+# The prompts drawn after the first command follow a command
+function starship_preexec --on-event fish_preexec
+    set -e STARSHIP_FIRST_PROMPT
 end
 
 # disable virtualenv prompt, it breaks starship
@@ -17,5 +28,8 @@ set VIRTUAL_ENV_DISABLE_PROMPT 1
 function fish_mode_prompt; end
 export STARSHIP_SHELL="fish"
 
+# The first prompt of a session is never preceded by a new line
+set -gx STARSHIP_FIRST_PROMPT 1
+
 # Set up the session key that will be used to store logs
 export STARSHIP_SESSION_KEY=(::STARSHIP:: session)
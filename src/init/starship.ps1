@@ -29,9 +29,9 @@ function global:prompt {
 
         $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
         # & ensures the path is interpreted as something to execute
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastExitCodeForPrompt --jobs=$jobs --cmd-duration=$duration)
+        $out = @(&::STARSHIP:: prompt "--path=$current_directory" "--logical-path=$PWD" --status=$lastExitCodeForPrompt --jobs=$jobs --cmd-duration=$duration)
     } else {
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastExitCodeForPrompt --jobs=$jobs)
+        $out = @(&::STARSHIP:: prompt "--path=$current_directory" "--logical-path=$PWD" --status=$lastExitCodeForPrompt --jobs=$jobs)
     }
     # Restore old output encoding
     [Console]::OutputEncoding = $origOutputEncoding
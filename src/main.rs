@@ -21,6 +21,12 @@ fn main() {
         .help("The status code of the previously run command")
         .takes_value(true);
 
+    let pipestatus_arg = Arg::with_name("pipestatus")
+        .long("pipestatus")
+        .value_name("PIPESTATUS")
+        .help("The space separated status codes of the commands in the previously run pipeline")
+        .takes_value(true);
+
     let path_arg = Arg::with_name("path")
         .short("p")
         .long("path")
@@ -28,6 +34,13 @@ fn main() {
         .help("The path that the prompt should render for")
         .takes_value(true);
 
+    let logical_path_arg = Arg::with_name("logical_path")
+        .short("P")
+        .long("logical-path")
+        .value_name("LOGICAL_PATH")
+        .help("The logical path that the prompt should render for. This path should be a virtual/logical representation of the PATH argument.")
+        .takes_value(true);
+
     let shell_arg = Arg::with_name("shell")
         .value_name("SHELL")
         .help(
@@ -57,6 +70,19 @@ fn main() {
         .help("The number of currently running jobs")
         .takes_value(true);
 
+    let terminal_width_arg = Arg::with_name("terminal_width")
+        .short("w")
+        .long("terminal-width")
+        .value_name("TERMINAL_WIDTH")
+        .help("The width of the current interactive terminal.")
+        .takes_value(true);
+
+    let config_arg = Arg::with_name("config")
+        .long("config")
+        .value_name("CONFIG")
+        .help("The config file to use instead of $STARSHIP_CONFIG or ~/.config/starship.toml")
+        .takes_value(true);
+
     let init_scripts_arg = Arg::with_name("print_full_init")
         .long("print-full-init")
         .help("Print the main initialization script (as opposed to the init stub)");
@@ -78,11 +104,20 @@ fn main() {
         .subcommand(
             SubCommand::with_name("prompt")
                 .about("Prints the full starship prompt")
+                .arg(
+                    Arg::with_name("right")
+                        .long("right")
+                        .help("Print the right prompt (instead of the standard left prompt)"),
+                )
                 .arg(&status_code_arg)
+                .arg(&pipestatus_arg)
                 .arg(&path_arg)
+                .arg(&logical_path_arg)
                 .arg(&cmd_duration_arg)
                 .arg(&keymap_arg)
-                .arg(&jobs_arg),
+                .arg(&jobs_arg)
+                .arg(&terminal_width_arg)
+                .arg(&config_arg),
         )
         .subcommand(
             SubCommand::with_name("module")
@@ -100,10 +135,14 @@ fn main() {
                         .help("List out all supported modules"),
                 )
                 .arg(&status_code_arg)
+                .arg(&pipestatus_arg)
                 .arg(&path_arg)
+                .arg(&logical_path_arg)
                 .arg(&cmd_duration_arg)
                 .arg(&keymap_arg)
-                .arg(&jobs_arg),
+                .arg(&jobs_arg)
+                .arg(&terminal_width_arg)
+                .arg(&config_arg),
         )
         .subcommand(
             SubCommand::with_name("config")
@@ -117,6 +156,22 @@ fn main() {
                 )
                 .arg(Arg::with_name("value").help("Value to place into that key")),
         )
+        .subcommand(
+            SubCommand::with_name("print-config")
+                .about("Prints the computed starship configuration")
+                .arg(
+                    Arg::with_name("default")
+                        .short("d")
+                        .long("default")
+                        .help("Print the default instead of the computed config"),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .help("Configuration keys to print, e.g. git_status or git_status.format")
+                        .multiple(true)
+                        .required(false),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("bug-report").about(
                 "Create a pre-populated GitHub issue with information about your configuration",
@@ -128,9 +183,24 @@ fn main() {
                 .settings(&[AppSettings::Hidden]),
         )
         .subcommand(
-            SubCommand::with_name("explain").about("Explains the currently showing modules"),
+            SubCommand::with_name("explain")
+                .about("Explains the currently showing modules")
+                .arg(
+                    Arg::with_name("all")
+                        .short("a")
+                        .long("all")
+                        .help("Also list the modules that aren't shown in the current directory"),
+                )
+                .arg(&path_arg)
+                .arg(&logical_path_arg)
+                .arg(&terminal_width_arg)
+                .arg(&config_arg),
+        )
+        .subcommand(
+            SubCommand::with_name("timings")
+                .about("Prints timings of all active modules")
+                .arg(&config_arg),
         )
-        .subcommand(SubCommand::with_name("timings").about("Prints timings of all active modules"))
         .subcommand(
             SubCommand::with_name("completions")
                 .about("Generate starship shell completions for your shell to stdout")
@@ -179,6 +249,14 @@ fn main() {
                 configure::edit_configuration()
             }
         }
+        ("print-config", Some(sub_m)) => {
+            let print_default = sub_m.is_present("default");
+            let paths = sub_m
+                .values_of("name")
+                .map(|paths| paths.collect::<Vec<&str>>())
+                .unwrap_or_default();
+            configure::print_configuration(print_default, &paths)
+        }
         ("bug-report", Some(_)) => bug_report::create(),
         ("time", _) => {
             match SystemTime::now()
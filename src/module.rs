@@ -24,9 +24,11 @@ pub const ALL_MODULES: &[&str] = &[
     "elm",
     "erlang",
     "env_var",
+    "fill",
     "gcloud",
     "git_branch",
     "git_commit",
+    "git_metrics",
     "git_state",
     "git_status",
     "golang",
@@ -38,6 +40,7 @@ pub const ALL_MODULES: &[&str] = &[
     "julia",
     "kubernetes",
     "line_break",
+    "localip",
     "lua",
     "memory_usage",
     "nim",
@@ -45,6 +48,7 @@ pub const ALL_MODULES: &[&str] = &[
     "nodejs",
     "ocaml",
     "openstack",
+    "os",
     "package",
     "perl",
     "purescript",
@@ -55,9 +59,11 @@ pub const ALL_MODULES: &[&str] = &[
     "php",
     "swift",
     "terraform",
+    "shell",
     "shlvl",
     "singularity",
     "status",
+    "sudo",
     "time",
     "username",
     "zig",
@@ -135,12 +141,11 @@ impl<'a> Module<'a> {
         let ansi_strings = self
             .segments
             .iter()
-            .map(Segment::ansi_string)
+            .map(|segment| segment.ansi_string_for_shell(shell))
             .collect::<Vec<ANSIString>>();
 
         match shell {
-            Shell::Bash => ansi_strings_modified(ansi_strings, shell),
-            Shell::Zsh => ansi_strings_modified(ansi_strings, shell),
+            Shell::Bash | Shell::Zsh | Shell::Tcsh => ansi_strings_modified(ansi_strings, shell),
             _ => ansi_strings,
         }
     }
@@ -226,4 +231,49 @@ mod tests {
 
         assert!(!module.is_empty());
     }
+
+    fn styled_module() -> Module<'static> {
+        let mut module = Module::new("unit_test", "This is a unit test", None);
+        module.set_segments(vec![
+            Segment::new(Some(ansi_term::Color::Red.bold()), "100%"),
+            Segment::new(None, " !"),
+        ]);
+        module
+    }
+
+    fn render(module: &Module, shell: Shell) -> String {
+        ANSIStrings(&module.ansi_strings_for_shell(shell)).to_string()
+    }
+
+    #[test]
+    fn styled_module_is_not_wrapped_for_unknown_shell() {
+        assert_eq!(
+            render(&styled_module(), Shell::Unknown),
+            "\u{1b}[1;31m100%\u{1b}[0m !"
+        );
+    }
+
+    #[test]
+    fn styled_module_is_wrapped_for_bash() {
+        assert_eq!(
+            render(&styled_module(), Shell::Bash),
+            "\\[\u{1b}[1;31m\\]100%\\[\u{1b}[0m\\] !"
+        );
+    }
+
+    #[test]
+    fn styled_module_is_wrapped_and_escaped_for_zsh() {
+        assert_eq!(
+            render(&styled_module(), Shell::Zsh),
+            "%{\u{1b}[1;31m%}100%%%{\u{1b}[0m%} !"
+        );
+    }
+
+    #[test]
+    fn styled_module_is_wrapped_and_escaped_for_tcsh() {
+        assert_eq!(
+            render(&styled_module(), Shell::Tcsh),
+            "%{\u{1b}[1;31m%}100%%%{\u{1b}[0m%} \\!"
+        );
+    }
 }
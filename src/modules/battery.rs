@@ -1,18 +1,11 @@
-use super::{Context, Module, RootModuleConfig, Shell};
+use super::{Context, Module, RootModuleConfig};
 use crate::configs::battery::BatteryConfig;
 
 use crate::formatter::StringFormatter;
 
 /// Creates a module for the battery percentage and charging state
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    // TODO: Update when v1.0 printing refactor is implemented to only
-    // print escapes in a prompt context.
-    let percentage_char = match context.shell {
-        Shell::Zsh => "%%", // % is an escape in zsh, see PROMPT in `man zshmisc`
-        _ => "%",
-    };
-
-    let battery_status = get_battery_status()?;
+    let battery_status = context.battery_info_provider.get_battery_info()?;
     let BatteryStatus { state, percentage } = battery_status;
 
     let mut module = context.new_module("battery");
@@ -26,22 +19,25 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .iter()
         .find(|display_style| percentage <= display_style.threshold as f32)?;
 
+    // The uncommon states are hidden unless they have a symbol configured
+    let symbol = match state {
+        battery::State::Full => Some(config.full_symbol),
+        battery::State::Charging => Some(config.charging_symbol),
+        battery::State::Discharging => Some(config.discharging_symbol),
+        battery::State::Unknown => Some(config.unknown_symbol?),
+        battery::State::Empty => Some(config.empty_symbol?),
+        _ => {
+            log::debug!("Unhandled battery state `{}`", state);
+            None
+        }
+    };
+
     // Parse the format string and build the module
-    match StringFormatter::new(config.format) {
+    match StringFormatter::new_with_context(config.format, context) {
         Ok(formatter) => {
             let formatter = formatter
                 .map_meta(|variable, _| match variable {
-                    "symbol" => match state {
-                        battery::State::Full => Some(config.full_symbol),
-                        battery::State::Charging => Some(config.charging_symbol),
-                        battery::State::Discharging => Some(config.discharging_symbol),
-                        battery::State::Unknown => config.unknown_symbol,
-                        battery::State::Empty => config.empty_symbol,
-                        _ => {
-                            log::debug!("Unhandled battery state `{}`", state);
-                            None
-                        }
-                    },
+                    "symbol" => symbol,
                     _ => None,
                 })
                 .map_style(|style| match style {
@@ -49,7 +45,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     _ => None,
                 })
                 .map(|variable| match variable {
-                    "percentage" => Some(Ok(format!("{}{}", percentage.round(), percentage_char))),
+                    "percentage" => Some(Ok(format!("{}%", percentage.round()))),
                     _ => None,
                 });
 
@@ -71,6 +67,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
+/// A source of battery readings, so they can be faked in tests
+pub trait BatteryInfoProvider {
+    fn get_battery_info(&self) -> Option<BatteryStatus>;
+}
+
+/// Reads the status of the batteries attached to the machine
+pub struct BatteryInfoProviderImpl;
+
+impl BatteryInfoProvider for BatteryInfoProviderImpl {
+    fn get_battery_info(&self) -> Option<BatteryStatus> {
+        get_battery_status()
+    }
+}
+
 fn get_battery_status() -> Option<BatteryStatus> {
     let battery_manager = battery::Manager::new().ok()?;
     let batteries = battery_manager.batteries().ok()?;
@@ -85,7 +95,7 @@ fn get_battery_status() -> Option<BatteryStatus> {
                 })
             }
             Err(e) => {
-                log::warn!("Unable to access battery information:\n{}", &e);
+                log::debug!("Unable to access battery information:\n{}", &e);
                 None
             }
         })
@@ -143,7 +153,173 @@ struct BatteryInfo {
 }
 
 #[derive(Debug)]
-struct BatteryStatus {
-    percentage: f32,
-    state: battery::State,
+pub struct BatteryStatus {
+    pub percentage: f32,
+    pub state: battery::State,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatteryInfoProvider, BatteryStatus};
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use battery::State;
+    use std::io;
+
+    struct FakeBattery(Option<(f32, State)>);
+
+    impl BatteryInfoProvider for FakeBattery {
+        fn get_battery_info(&self) -> Option<BatteryStatus> {
+            self.0
+                .map(|(percentage, state)| BatteryStatus { percentage, state })
+        }
+    }
+
+    #[test]
+    fn no_battery() -> io::Result<()> {
+        let battery = FakeBattery(None);
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn above_default_threshold() -> io::Result<()> {
+        let battery = FakeBattery(Some((50.0, State::Discharging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn below_default_threshold() -> io::Result<()> {
+        let battery = FakeBattery(Some((4.6, State::Discharging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("↓5%")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn at_threshold() -> io::Result<()> {
+        let battery = FakeBattery(Some((10.0, State::Charging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("↑10%")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn first_matching_display_is_used() -> io::Result<()> {
+        let config = toml::toml! {
+            [[battery.display]]
+            threshold = 10
+            style = "red"
+
+            [[battery.display]]
+            threshold = 20
+            style = "yellow"
+        };
+
+        let battery = FakeBattery(Some((15.0, State::Discharging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{} ", Color::Yellow.paint("↓15%")));
+        assert_eq!(expected, actual);
+
+        let battery = FakeBattery(Some((5.0, State::Discharging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.paint("↓5%")));
+        assert_eq!(expected, actual);
+
+        let battery = FakeBattery(Some((25.0, State::Discharging)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(config)
+            .collect();
+        assert_eq!(None, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn full_battery() -> io::Result<()> {
+        let battery = FakeBattery(Some((100.0, State::Full)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(toml::toml! {
+                [battery]
+                full_symbol = "🔋"
+                [[battery.display]]
+                threshold = 100
+                style = "green"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.paint("🔋100%")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_state_without_symbol() -> io::Result<()> {
+        let battery = FakeBattery(Some((5.0, State::Unknown)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_state_with_symbol() -> io::Result<()> {
+        let battery = FakeBattery(Some((0.0, State::Empty)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(toml::toml! {
+                [battery]
+                empty_symbol = "!"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("!0%")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_state_with_symbol() -> io::Result<()> {
+        let battery = FakeBattery(Some((5.0, State::Unknown)));
+        let actual = ModuleRenderer::new("battery")
+            .battery_info_provider(&battery)
+            .config(toml::toml! {
+                [battery]
+                unknown_symbol = "?"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("?5%")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
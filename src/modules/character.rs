@@ -10,11 +10,13 @@ use crate::formatter::StringFormatter;
 ///   (green arrow by default)
 /// - If the exit-code was anything else, it will be formatted with
 ///   `error_symbol` (red arrow by default)
+/// - In the normal mode of zsh and fish's vi key bindings, given by `--keymap`,
+///   it will be formatted with `vicmd_symbol` instead (green left arrow by default)
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     enum ShellEditMode {
         Normal,
         Insert,
-    };
+    }
     const ASSUMED_MODE: ShellEditMode = ShellEditMode::Insert;
     // TODO: extend config to more modes
 
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "symbol" => Some(symbol),
@@ -91,6 +93,28 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn env_variable_in_format() -> io::Result<()> {
+        let config = toml::toml! {
+            [character]
+            format = "(\\[${env:CI_JOB}\\] )$symbol "
+        };
+
+        let expected = Some(format!("[build] {} ", Color::Green.bold().paint("❯")));
+        let actual = ModuleRenderer::new("character")
+            .config(config.clone())
+            .env("CI_JOB", "build")
+            .collect();
+        assert_eq!(expected, actual);
+
+        // The conditional group collapses when the variable is unset
+        let expected = Some(format!("{} ", Color::Green.bold().paint("❯")));
+        let actual = ModuleRenderer::new("character").config(config).collect();
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
     #[test]
     fn failure_status() -> io::Result<()> {
         let expected = Some(format!("{} ", Color::Red.bold().paint("❯")));
@@ -139,6 +163,39 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn symbols_are_format_strings() {
+        let config = toml::toml! {
+            [character]
+            success_symbol = "[ok](bold green) ❯"
+            error_symbol = "[err](bold red) ❯"
+            vicmd_symbol = "[normal](bold yellow) ❮"
+        };
+
+        let expected = Some(format!("{} ❯ ", Color::Green.bold().paint("ok")));
+        let actual = ModuleRenderer::new("character")
+            .config(config.clone())
+            .status(0)
+            .collect();
+        assert_eq!(expected, actual);
+
+        let expected = Some(format!("{} ❯ ", Color::Red.bold().paint("err")));
+        let actual = ModuleRenderer::new("character")
+            .config(config.clone())
+            .status(1)
+            .collect();
+        assert_eq!(expected, actual);
+
+        let expected = Some(format!("{} ❮ ", Color::Yellow.bold().paint("normal")));
+        let actual = ModuleRenderer::new("character")
+            .config(config)
+            .shell(Shell::Zsh)
+            .keymap("vicmd")
+            .status(1)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn zsh_keymap() -> io::Result<()> {
         let expected_vicmd = Some(format!("{} ", Color::Green.bold().paint("❮")));
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::crystal::CrystalConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Crystal version
@@ -10,22 +10,23 @@ use crate::utils;
 ///     - Current directory contains a `.cr` file
 ///     - Current directory contains a `shard.yml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("crystal");
+    let config: CrystalConfig = CrystalConfig::try_load(module.config);
+
     let is_crystal_project = context
         .try_begin_scan()?
-        .set_files(&["shard.yml"])
-        .set_extensions(&["cr"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_crystal_project {
         return None;
     }
 
-    let crystal_version = utils::exec_cmd("crystal", &["--version"])?.stdout;
-
-    let mut module = context.new_module("crystal");
-    let config: CrystalConfig = CrystalConfig::try_load(module.config);
+    let crystal_version = context.exec_cmd("crystal", &["--version"])?.stdout;
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "symbol" => Some(config.symbol),
@@ -36,7 +37,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => format_crystal_version(&crystal_version).map(Ok),
+                "version" => utils::parse_version(&crystal_version)
+                    .map(|version| {
+                        VersionFormatter::format_module_version(
+                            module.get_name(),
+                            version,
+                            config.version_format,
+                        )
+                    })
+                    .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -53,19 +62,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_crystal_version(crystal_version: &str) -> Option<String> {
-    let version = crystal_version
-        // split into ["Crystal", "0.35.1", ...]
-        .split_whitespace()
-        // return "0.35.1"
-        .nth(1)?;
-
-    let mut formatted_version = String::with_capacity(version.len() + 1);
-    formatted_version.push('v');
-    formatted_version.push_str(version);
-    Some(formatted_version)
-}
-
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
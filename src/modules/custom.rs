@@ -1,10 +1,13 @@
 use std::io::Write;
-use std::process::{Command, Output, Stdio};
-use std::time::Instant;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
 use super::{Context, Module, RootModuleConfig};
 
-use crate::{configs::custom::CustomConfig, formatter::StringFormatter};
+use crate::config::ModuleConfig;
+use crate::configs::custom::{CustomConfig, Directories, Extensions, Files};
+use crate::formatter::StringFormatter;
+use crate::utils::{self, CommandOutput};
 
 /// Creates a custom module with some configuration
 ///
@@ -18,25 +21,27 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     let toml_config = context.config.get_custom_module_config(name).expect(
         "modules::custom::module should only be called after ensuring that the module exists",
     );
-    let config = CustomConfig::load(toml_config);
+    let mut config = CustomConfig::load(toml_config);
+    load_renamed_options(name, toml_config, &mut config);
+    let time_limit = context.command_timeout();
 
     let mut scan_dir = context.try_begin_scan()?;
 
-    if !config.files.0.is_empty() {
-        scan_dir = scan_dir.set_files(&config.files.0);
+    if !config.detect_files.0.is_empty() {
+        scan_dir = scan_dir.set_files(&config.detect_files.0);
     }
-    if !config.extensions.0.is_empty() {
-        scan_dir = scan_dir.set_extensions(&config.extensions.0);
+    if !config.detect_extensions.0.is_empty() {
+        scan_dir = scan_dir.set_extensions(&config.detect_extensions.0);
     }
-    if !config.directories.0.is_empty() {
-        scan_dir = scan_dir.set_folders(&config.directories.0);
+    if !config.detect_folders.0.is_empty() {
+        scan_dir = scan_dir.set_folders(&config.detect_folders.0);
     }
 
     let mut is_match = scan_dir.is_match();
 
     if !is_match {
         if let Some(when) = config.when {
-            is_match = exec_when(when, &config.shell.0);
+            is_match = exec_when(when, &config.shell.0, time_limit);
         }
 
         if !is_match {
@@ -46,28 +51,37 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
 
     let mut module = Module::new(name, config.description, Some(toml_config));
 
-    let output = exec_command(config.command, &config.shell.0)?;
+    let output = exec_command(config.command, &config.shell.0, time_limit)?;
 
-    let trimmed = output.trim();
+    let trimmed = match config.line_separator {
+        Some(separator) => output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>()
+            .join(separator),
+        None => output.trim().to_owned(),
+    };
     if !trimmed.is_empty() {
-        let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-            formatter
-                .map_meta(|var, _| match var {
-                    "symbol" => Some(config.symbol),
-                    _ => None,
-                })
-                .map_style(|variable| match variable {
-                    "style" => Some(Ok(config.style)),
-                    _ => None,
-                })
-                .map(|variable| match variable {
-                    // This may result in multiple calls to `get_module_version` when a user have
-                    // multiple `$version` variables defined in `format`.
-                    "output" => Some(Ok(trimmed)),
-                    _ => None,
-                })
-                .parse(None)
-        });
+        let parsed =
+            StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+                formatter
+                    .map_meta(|var, _| match var {
+                        "symbol" => Some(config.symbol),
+                        _ => None,
+                    })
+                    .map_style(|variable| match variable {
+                        "style" => Some(Ok(config.style)),
+                        _ => None,
+                    })
+                    .map(|variable| match variable {
+                        // This may result in multiple calls to `get_module_version` when a user have
+                        // multiple `$version` variables defined in `format`.
+                        "output" => Some(Ok(&trimmed)),
+                        _ => None,
+                    })
+                    .parse(None)
+            });
 
         match parsed {
             Ok(segments) => module.set_segments(segments),
@@ -82,6 +96,41 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// `files`, `extensions` and `directories` were renamed to `detect_files`, `detect_extensions`
+/// and `detect_folders`. The old names are still used when the new ones aren't set.
+fn load_renamed_options<'a>(
+    name: &str,
+    toml_config: &'a toml::Value,
+    config: &mut CustomConfig<'a>,
+) {
+    let renamed = |old: &str, new: &str| {
+        let value = toml_config.get(old)?;
+        log::warn!(
+            "`custom.{}.{}` has been renamed to `{}`, please update your config",
+            name,
+            old,
+            new
+        );
+        Some(value)
+    };
+
+    if let Some(files) = renamed("files", "detect_files") {
+        if config.detect_files.0.is_empty() {
+            config.detect_files = Files::from_config(files).unwrap_or_default();
+        }
+    }
+    if let Some(extensions) = renamed("extensions", "detect_extensions") {
+        if config.detect_extensions.0.is_empty() {
+            config.detect_extensions = Extensions::from_config(extensions).unwrap_or_default();
+        }
+    }
+    if let Some(directories) = renamed("directories", "detect_folders") {
+        if config.detect_folders.0.is_empty() {
+            config.detect_folders = Directories::from_config(directories).unwrap_or_default();
+        }
+    }
+}
+
 /// Return the invoking shell, using `shell` and fallbacking in order to STARSHIP_SHELL and "sh"
 #[cfg(not(windows))]
 fn get_shell<'a, 'b>(shell_args: &'b [&'a str]) -> (std::borrow::Cow<'a, str>, &'b [&'a str]) {
@@ -94,9 +143,10 @@ fn get_shell<'a, 'b>(shell_args: &'b [&'a str]) -> (std::borrow::Cow<'a, str>, &
     }
 }
 
-/// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`
+/// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
+/// giving up after `time_limit`
 #[cfg(not(windows))]
-fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
+fn shell_command(cmd: &str, shell_args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
     let (shell, shell_args) = get_shell(shell_args);
     let mut command = Command::new(shell.as_ref());
 
@@ -127,13 +177,13 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
     };
 
     child.stdin.as_mut()?.write_all(cmd.as_bytes()).ok()?;
-    child.wait_with_output().ok()
+    utils::wait_with_time_limit(child, cmd, time_limit)
 }
 
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
-/// or by invoking cmd.exe /C.
+/// or by invoking cmd.exe /C, giving up after `time_limit`
 #[cfg(windows)]
-fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
+fn shell_command(cmd: &str, shell_args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
     let (shell, shell_args) = if !shell_args.is_empty() {
         (
             Some(std::borrow::Cow::Borrowed(shell_args[0])),
@@ -159,7 +209,7 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
         if let Ok(mut child) = command.spawn() {
             child.stdin.as_mut()?.write_all(cmd.as_bytes()).ok()?;
 
-            return child.wait_with_output().ok();
+            return utils::wait_with_time_limit(child, cmd, time_limit);
         }
 
         log::debug!(
@@ -175,27 +225,21 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
         .stderr(Stdio::piped())
         .spawn();
 
-    command.ok()?.wait_with_output().ok()
+    utils::wait_with_time_limit(command.ok()?, cmd, time_limit)
 }
 
 /// Execute the given command capturing all output, and return whether it return 0
-fn exec_when(cmd: &str, shell_args: &[&str]) -> bool {
+fn exec_when(cmd: &str, shell_args: &[&str], time_limit: Duration) -> bool {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell_args) {
-        if !output.status.success() {
-            log::trace!("non-zero exit code '{:?}'", output.status.code());
-            log::trace!(
-                "stdout: {}",
-                std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
-            );
-            log::trace!(
-                "stderr: {}",
-                std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
-            );
+    if let Some(output) = shell_command(cmd, shell_args, time_limit) {
+        if !output.success() {
+            log::trace!("non-zero exit code '{:?}'", output.status);
+            log::trace!("stdout: {}", output.stdout);
+            log::trace!("stderr: {}", output.stderr);
         }
 
-        output.status.success()
+        output.success()
     } else {
         log::debug!("Cannot start command");
 
@@ -204,24 +248,18 @@ fn exec_when(cmd: &str, shell_args: &[&str]) -> bool {
 }
 
 /// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, shell_args: &[&str]) -> Option<String> {
+fn exec_command(cmd: &str, shell_args: &[&str], time_limit: Duration) -> Option<String> {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell_args) {
-        if !output.status.success() {
-            log::trace!("Non-zero exit code '{:?}'", output.status.code());
-            log::trace!(
-                "stdout: {}",
-                std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
-            );
-            log::trace!(
-                "stderr: {}",
-                std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
-            );
+    if let Some(output) = shell_command(cmd, shell_args, time_limit) {
+        if !output.success() {
+            log::trace!("Non-zero exit code '{:?}'", output.status);
+            log::trace!("stdout: {}", output.stdout);
+            log::trace!("stderr: {}", output.stderr);
             return None;
         }
 
-        Some(String::from_utf8_lossy(&output.stdout).into())
+        Some(output.stdout)
     } else {
         None
     }
@@ -244,6 +282,11 @@ fn handle_powershell(command: &mut Command, shell: &str, shell_args: &[&str]) {
 mod tests {
     use super::*;
 
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
     #[cfg(not(windows))]
     const SHELL: &[&str] = &["/bin/sh"];
     #[cfg(windows)]
@@ -256,23 +299,28 @@ mod tests {
 
     const UNKNOWN_COMMAND: &str = "ydelsyiedsieudleylse dyesdesl";
 
+    const TIME_LIMIT: Duration = Duration::from_secs(10);
+
     #[test]
     fn when_returns_right_value() {
-        assert!(exec_when("echo hello", SHELL));
-        assert!(!exec_when(FAILING_COMMAND, SHELL));
+        assert!(exec_when("echo hello", SHELL, TIME_LIMIT));
+        assert!(!exec_when(FAILING_COMMAND, SHELL, TIME_LIMIT));
     }
 
     #[test]
     fn when_returns_false_if_invalid_command() {
-        assert!(!exec_when(UNKNOWN_COMMAND, SHELL));
+        assert!(!exec_when(UNKNOWN_COMMAND, SHELL, TIME_LIMIT));
     }
 
     #[test]
     #[cfg(not(windows))]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, TIME_LIMIT),
+            Some("hello\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, TIME_LIMIT),
             Some("강남스타일\n".into())
         );
     }
@@ -280,9 +328,12 @@ mod tests {
     #[test]
     #[cfg(windows)]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\r\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, TIME_LIMIT),
+            Some("hello\r\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, TIME_LIMIT),
             Some("강남스타일\r\n".into())
         );
     }
@@ -291,11 +342,11 @@ mod tests {
     #[cfg(not(windows))]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2; echo bar", SHELL),
+            exec_command("echo foo 1>&2; echo bar", SHELL, TIME_LIMIT),
             Some("bar\n".into())
         );
         assert_eq!(
-            exec_command("echo foo; echo bar 1>&2", SHELL),
+            exec_command("echo foo; echo bar 1>&2", SHELL, TIME_LIMIT),
             Some("foo\n".into())
         );
     }
@@ -304,18 +355,200 @@ mod tests {
     #[cfg(windows)]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2 & echo bar", SHELL),
+            exec_command("echo foo 1>&2 & echo bar", SHELL, TIME_LIMIT),
             Some("bar\r\n".into())
         );
         assert_eq!(
-            exec_command("echo foo& echo bar 1>&2", SHELL),
+            exec_command("echo foo& echo bar 1>&2", SHELL, TIME_LIMIT),
             Some("foo\r\n".into())
         );
     }
 
     #[test]
     fn command_can_fail() {
-        assert_eq!(exec_command(FAILING_COMMAND, SHELL), None);
-        assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL), None);
+        assert_eq!(exec_command(FAILING_COMMAND, SHELL, TIME_LIMIT), None);
+        assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL, TIME_LIMIT), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn command_is_killed_after_time_limit() {
+        let start = Instant::now();
+        assert_eq!(
+            exec_command("sleep 5", SHELL, Duration::from_millis(100)),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn shown_when_detected_file_exists() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Chart.yaml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo helm"
+                detect_files = ["Chart.yaml"]
+                symbol = "⎈ "
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("⎈ helm")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn not_shown_without_a_detected_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo helm"
+                detect_files = ["Chart.yaml"]
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn shown_when_detected_extension_or_folder_exists() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.tf"))?.sync_all()?;
+        std::fs::create_dir(dir.path().join(".helm"))?;
+
+        for config in &[
+            toml::toml! {
+                [custom.test]
+                command = "echo found"
+                detect_extensions = ["tf"]
+            },
+            toml::toml! {
+                [custom.test]
+                command = "echo found"
+                detect_folders = [".helm"]
+            },
+        ] {
+            let actual = ModuleRenderer::new("custom.test")
+                .path(dir.path())
+                .config(config.clone())
+                .collect();
+            let expected = Some(format!("{} ", Color::Green.bold().paint("found")));
+            assert_eq!(expected, actual);
+        }
+        dir.close()
+    }
+
+    #[test]
+    fn renamed_detect_options_still_work() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Chart.yaml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo helm"
+                files = ["Chart.yaml"]
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("helm")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn when_gates_the_module() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo shown"
+                when = "echo yes"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("shown")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo shown"
+                when = FAILING_COMMAND
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn output_is_trimmed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "echo '  spaced  '"
+                when = "true"
+                format = "<$output>"
+            })
+            .collect();
+        let expected = Some(String::from("<spaced>"));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn multiline_output_is_joined_with_line_separator() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = "printf 'one\n two \n\nthree\n'"
+                when = "true"
+                line_separator = ", "
+                format = "<$output>"
+            })
+            .collect();
+        let expected = Some(String::from("<one, two, three>"));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn failing_command_hides_the_module() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                command = FAILING_COMMAND
+                when = "echo yes"
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        dir.close()
     }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::dart::DartConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Dart version
@@ -11,23 +11,23 @@ use crate::utils;
 ///     - Current directory contains a `.dart_tool` directory
 ///     - Current directory contains a `pubspec.yaml`/`pubspec.yml` or `pubspec.lock` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("dart");
+    let config: DartConfig = DartConfig::try_load(module.config);
+
     let is_dart_project = context
         .try_begin_scan()?
-        .set_extensions(&["dart"])
-        .set_folders(&[".dart_tool"])
-        .set_files(&["pubspec.yaml", "pubspec.yml", "pubspec.lock"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_dart_project {
         return None;
     }
 
-    let dart_version = utils::exec_cmd("dart", &["--version"])?.stderr;
+    let dart_version = context.exec_cmd("dart", &["--version"])?.stderr;
 
-    let mut module = context.new_module("dart");
-    let config: DartConfig = DartConfig::try_load(module.config);
-
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -38,7 +38,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => parse_dart_version(&dart_version).map(Ok),
+                "version" => utils::parse_version(&dart_version)
+                    .map(|version| {
+                        VersionFormatter::format_module_version(
+                            module.get_name(),
+                            version,
+                            config.version_format,
+                        )
+                    })
+                    .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -55,19 +63,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn parse_dart_version(dart_version: &str) -> Option<String> {
-    let version = dart_version
-        // split into ["Dart", "VM", "version:", "2.8.4", "(stable)", ...]
-        .split_whitespace()
-        // return "2.8.4"
-        .nth(3)?;
-
-    Some(format!("v{}", version))
-}
-
 #[cfg(test)]
 mod tests {
-    use super::parse_dart_version;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::fs::{self, File};
@@ -76,7 +73,7 @@ mod tests {
     #[test]
     fn test_parse_dart_version() {
         let input = "Dart VM version: 2.8.4 (stable)";
-        assert_eq!(parse_dart_version(input), Some("v2.8.4".to_string()));
+        assert_eq!(crate::utils::parse_version(input), Some("2.8.4"));
     }
 
     #[test]
@@ -1,7 +1,3 @@
-#[cfg(not(target_os = "windows"))]
-use super::utils::directory_nix as directory_utils;
-#[cfg(target_os = "windows")]
-use super::utils::directory_win as directory_utils;
 use indexmap::IndexMap;
 use path_slash::PathExt;
 use std::iter::FromIterator;
@@ -11,12 +7,11 @@ use unicode_segmentation::UnicodeSegmentation;
 use super::{Context, Module};
 
 use super::utils::directory::truncate;
-use crate::config::RootModuleConfig;
+use crate::config::{parse_style_string, RootModuleConfig};
 use crate::configs::directory::DirectoryConfig;
 use crate::context::Shell;
-use crate::formatter::StringFormatter;
-
-const HOME_SYMBOL: &str = "~";
+use crate::formatter::{StringFormatter, VariableHolder};
+use crate::segment::Segment;
 
 /// Creates a module with the current directory
 ///
@@ -43,55 +38,62 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     log::debug!("Current directory: {:?}", current_dir);
 
     let repo = &context.get_repo().ok()?;
-    let dir_string = match &repo.root {
+    let (dir_string, contracted_to_repo) = match &repo.root {
         Some(repo_root) if config.truncate_to_repo && (repo_root != &home_dir) => {
             log::debug!("Repo root: {:?}", repo_root);
             // Contract the path to the git repo root
-            contract_repo_path(current_dir, repo_root)
-                .unwrap_or_else(|| contract_path(current_dir, &home_dir, HOME_SYMBOL))
+            match contract_repo_path(current_dir, repo_root) {
+                Some(dir_string) => (dir_string, true),
+                None => (contract_home(current_dir, &home_dir, &config), false),
+            }
         }
         // Contract the path to the home directory
-        _ => contract_path(current_dir, &home_dir, HOME_SYMBOL),
+        _ => (contract_home(current_dir, &home_dir, &config), false),
     };
     log::debug!("Dir string: {}", dir_string);
 
-    let substituted_dir = substitute_path(dir_string, &config.substitutions);
-
-    // Truncate the dir string to the maximum number of path components
-    let truncated_dir_string = truncate(substituted_dir, config.truncation_length as usize);
-
-    let prefix = if is_truncated(&truncated_dir_string) {
-        // Substitutions could have changed the prefix, so don't allow them and
-        // fish-style path contraction together
-        if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
-            // If user is using fish style path, we need to add the segment first
-            let contracted_home_dir = contract_path(&current_dir, &home_dir, HOME_SYMBOL);
-            to_fish_style(
-                config.fish_style_pwd_dir_length as usize,
-                contracted_home_dir,
-                &truncated_dir_string,
-            )
-        } else {
-            String::from(config.truncation_symbol)
-        }
+    let repo_root_style = config.repo_root_style.filter(|_| contracted_to_repo);
+    let (repo_root, displayed_path) = if repo_root_style.is_some() {
+        let (repo_root, path) = split_repo_path(&dir_string, &config);
+        (Some(repo_root), path)
     } else {
-        String::from("")
+        (
+            None,
+            truncate_dir_string(dir_string, current_dir, &home_dir, &config),
+        )
     };
-
-    let displayed_path = prefix + &truncated_dir_string;
     let lock_symbol = String::from(config.read_only);
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        // Unless the format places `$repo_root` itself, the repo root leads `$path`
+        let root_in_path = repo_root
+            .as_ref()
+            .filter(|_| !formatter.get_variables().contains("repo_root"));
         formatter
+            .map_variables_to_segments(|variable| match variable {
+                "path" => root_in_path.map(|repo_root| {
+                    let root_style = repo_root_style.and_then(parse_style_string);
+                    let mut segments = vec![Segment::new(root_style, repo_root.as_str())];
+                    if !displayed_path.is_empty() {
+                        segments.push(Segment::new(None, displayed_path.as_str()));
+                    }
+                    Ok(segments)
+                }),
+                _ => None,
+            })
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
+                "repo_root_style" => repo_root_style.map(Ok),
                 "read_only_style" => Some(Ok(config.read_only_style)),
                 _ => None,
             })
             .map(|variable| match variable {
+                // At the repo root, only the repo name is shown
+                "path" if displayed_path.is_empty() => None,
                 "path" => Some(Ok(&displayed_path)),
+                "repo_root" => repo_root.as_ref().map(Ok),
                 "read_only" => {
-                    if is_readonly_dir(&context.current_dir) {
+                    if context.is_current_dir_read_only() {
                         Some(Ok(&lock_symbol))
                     } else {
                         None
@@ -113,57 +115,118 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn is_truncated(path: &str) -> bool {
-    !(path.starts_with(HOME_SYMBOL)
+/// Substitutes and truncates the contracted path, prefixing it with the
+/// truncation symbol or fish-style path if any components were dropped
+fn truncate_dir_string(
+    dir_string: String,
+    current_dir: &Path,
+    home_dir: &Path,
+    config: &DirectoryConfig,
+) -> String {
+    let substituted_dir = substitute_path(dir_string, &config.substitutions);
+
+    // Truncate the dir string to the maximum number of path components
+    let truncated_dir_string = truncate(substituted_dir, config.truncation_length as usize);
+
+    let prefix = if is_truncated(&truncated_dir_string, config) {
+        // Substitutions could have changed the prefix, so don't allow them and
+        // fish-style path contraction together
+        if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
+            // If user is using fish style path, we need to add the segment first
+            let contracted_home_dir = contract_home(current_dir, home_dir, config);
+            to_fish_style(
+                config.fish_style_pwd_dir_length as usize,
+                contracted_home_dir,
+                &truncated_dir_string,
+            )
+        } else {
+            String::from(config.truncation_symbol)
+        }
+    } else {
+        String::from("")
+    };
+
+    prefix + &truncated_dir_string
+}
+
+/// Splits a path contracted to the repo root into the repo name and the path
+/// inside the repo, e.g. `starship/src/modules` into `starship` and `/src/modules`
+///
+/// Only the path inside the repo is substituted and truncated, so the repo name
+/// is always shown.
+fn split_repo_path(dir_string: &str, config: &DirectoryConfig) -> (String, String) {
+    let (repo_name, inner_path) = match dir_string.find('/') {
+        Some(index) => (&dir_string[..index], &dir_string[index + 1..]),
+        None => return (dir_string.to_string(), String::new()),
+    };
+
+    let substituted_path = substitute_path(inner_path.to_string(), &config.substitutions);
+    let truncated_path = truncate(substituted_path.clone(), config.truncation_length as usize);
+    let prefix = if truncated_path != substituted_path {
+        config.truncation_symbol
+    } else {
+        ""
+    };
+
+    (
+        repo_name.to_string(),
+        format!("/{}{}", prefix, truncated_path),
+    )
+}
+
+fn is_truncated(path: &str, config: &DirectoryConfig) -> bool {
+    let is_home = |symbol: &str| !symbol.is_empty() && path.starts_with(symbol);
+
+    !(is_home(config.home_symbol)
+        || (config.contract_other_users_home && is_home("~"))
         || PathBuf::from(path).has_root()
         || (cfg!(target_os = "windows") && PathBuf::from(String::from(path) + r"\").has_root()))
 }
 
 fn get_current_dir(context: &Context, config: &DirectoryConfig) -> PathBuf {
-    // Using environment PWD is the standard approach for determining logical path
-    // If this is None for any reason, we fall back to reading the os-provided path
-    let physical_current_dir = if config.use_logical_path {
-        match context.get_env("PWD") {
-            Some(mut x) => {
-                // Prevent Powershell from prepending "Microsoft.PowerShell.Core\FileSystem::" to some paths
-                if cfg!(windows) && context.shell == Shell::PowerShell {
-                    if let Some(no_prefix) =
-                        x.strip_prefix(r"Microsoft.PowerShell.Core\FileSystem::")
-                    {
-                        x = no_prefix.to_string();
-                    }
-                }
-                Some(PathBuf::from(x))
-            }
-            None => {
-                log::debug!("Error getting PWD environment variable!");
-                None
-            }
-        }
-    } else {
-        match std::env::current_dir() {
-            Ok(x) => Some(x),
-            Err(e) => {
-                log::debug!("Error getting physical current directory: {}", e);
-                None
-            }
+    if !config.use_logical_path {
+        return context.current_dir.clone();
+    }
+
+    // Prevent Powershell from prepending "Microsoft.PowerShell.Core\FileSystem::" to some paths
+    if cfg!(windows) && context.shell == Shell::PowerShell {
+        if let Some(no_prefix) = context
+            .logical_dir
+            .to_str()
+            .and_then(|path| path.strip_prefix(r"Microsoft.PowerShell.Core\FileSystem::"))
+        {
+            return PathBuf::from(no_prefix);
         }
-    };
-    physical_current_dir.unwrap_or_else(|| PathBuf::from(&context.current_dir))
+    }
+    context.logical_dir.clone()
 }
 
-fn is_readonly_dir(path: &Path) -> bool {
-    match directory_utils::is_write_allowed(path) {
-        Ok(res) => !res,
-        Err(e) => {
-            log::debug!(
-                "Failed to determine read only status of directory '{:?}': {}",
-                path,
-                e
-            );
-            false
+/// Contract the path to the current user's home directory, or to another
+/// user's home directory if `contract_other_users_home` is set
+fn contract_home(full_path: &Path, home_dir: &Path, config: &DirectoryConfig) -> String {
+    if config.contract_other_users_home && !full_path.starts_with(home_dir) {
+        if let Some(contracted) = contract_other_users_home(full_path) {
+            return contracted;
         }
     }
+    contract_path(full_path, home_dir, config.home_symbol)
+}
+
+/// Contract another user's home directory to `~user`
+///
+/// Home directories are assumed to live in `/home`, or in `/Users` on macOS.
+fn contract_other_users_home(full_path: &Path) -> Option<String> {
+    let homes_dir = if cfg!(target_os = "macos") {
+        "/Users"
+    } else {
+        "/home"
+    };
+
+    let mut components = full_path.strip_prefix(homes_dir).ok()?.components();
+    let user = components.next()?.as_os_str().to_str()?;
+    let home_dir = Path::new(homes_dir).join(user);
+
+    Some(contract_path(full_path, &home_dir, &format!("~{}", user)))
 }
 
 /// Contract the root component of a path
@@ -244,16 +307,64 @@ fn real_path<P: AsRef<Path>>(path: P) -> PathBuf {
 
 /// Perform a list of string substitutions on the path
 ///
-/// Given a list of (from, to) pairs, this will perform the string
-/// substitutions, in order, on the path. Any non-pair of strings is ignored.
+/// The path is scanned from left to right, and wherever substitutions match the
+/// one matching the most text is applied, earlier ones winning ties. Replaced
+/// text is never substituted again, so overlapping substitutions apply once.
 fn substitute_path(dir_string: String, substitutions: &IndexMap<String, &str>) -> String {
-    let mut substituted_dir = dir_string;
-    for substitution_pair in substitutions.iter() {
-        substituted_dir = substituted_dir.replace(substitution_pair.0, substitution_pair.1);
+    let mut substituted_dir = String::with_capacity(dir_string.len());
+    let mut rest = dir_string.as_str();
+
+    while let Some(next_char) = rest.chars().next() {
+        let longest_match = substitutions
+            .iter()
+            .filter_map(|(pattern, replacement)| {
+                let len = substitution_match_len(pattern, rest)?;
+                Some((len, *replacement))
+            })
+            .filter(|(len, _)| *len > 0)
+            .fold(None, |longest, (len, replacement)| match longest {
+                Some((longest_len, _)) if longest_len >= len => longest,
+                _ => Some((len, replacement)),
+            });
+
+        match longest_match {
+            Some((len, replacement)) => {
+                substituted_dir.push_str(replacement);
+                rest = &rest[len..];
+            }
+            None => {
+                substituted_dir.push(next_char);
+                rest = &rest[next_char.len_utf8()..];
+            }
+        }
     }
     substituted_dir
 }
 
+/// Returns the length of the text matched by `pattern` at the start of `text`
+///
+/// A `*` in the pattern matches any characters within a single path component,
+/// preferring the longest run that lets the rest of the pattern match.
+fn substitution_match_len(pattern: &str, text: &str) -> Option<usize> {
+    let star = match pattern.find('*') {
+        Some(star) => star,
+        None if text.starts_with(pattern) => return Some(pattern.len()),
+        None => return None,
+    };
+
+    let (literal, rest_pattern) = (&pattern[..star], &pattern[star + 1..]);
+    let after_literal = text.strip_prefix(literal)?;
+    let component_len = after_literal.find('/').unwrap_or(after_literal.len());
+
+    (0..=component_len)
+        .rev()
+        .filter(|len| after_literal.is_char_boundary(*len))
+        .find_map(|len| {
+            substitution_match_len(rest_pattern, &after_literal[len..])
+                .map(|rest_len| literal.len() + len + rest_len)
+        })
+}
+
 /// Takes part before contracted path and replaces it with fish style path
 ///
 /// Will take the first letter of each directory before the contracted path and
@@ -292,8 +403,8 @@ fn to_fish_style(pwd_dir_length: usize, dir_string: String, truncated_dir_string
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::ModuleRenderer;
-    use ansi_term::Color;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use ansi_term::{ANSIStrings, Color};
     use dirs_next::home_dir;
     #[cfg(not(target_os = "windows"))]
     use std::os::unix::fs::symlink;
@@ -362,6 +473,21 @@ mod tests {
         assert_eq!(output, "C:");
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn contract_other_users_home_directory() {
+        assert_eq!(
+            contract_other_users_home(Path::new("/home/alice/.config")),
+            Some(String::from("~alice/.config"))
+        );
+        assert_eq!(
+            contract_other_users_home(Path::new("/home/alice")),
+            Some(String::from("~alice"))
+        );
+        assert_eq!(contract_other_users_home(Path::new("/home")), None);
+        assert_eq!(contract_other_users_home(Path::new("/srv/alice")), None);
+    }
+
     #[test]
     fn substitute_prefix_and_middle() {
         let full_path = "/absolute/path/foo/bar/baz";
@@ -373,6 +499,42 @@ mod tests {
         assert_eq!(output, "/foo/baz");
     }
 
+    #[test]
+    fn substitute_longest_match_first() {
+        let full_path = "/mnt/c/Users/me/Projects/starship";
+        let mut substitutions = IndexMap::new();
+        substitutions.insert("/mnt/c".to_string(), "c:");
+        substitutions.insert("/mnt/c/Users/me/Projects".to_string(), "win:Projects");
+
+        let output = substitute_path(full_path.to_string(), &substitutions);
+        assert_eq!(output, "win:Projects/starship");
+    }
+
+    #[test]
+    fn substitute_replaced_text_only_once() {
+        let full_path = "/work/src";
+        let mut substitutions = IndexMap::new();
+        substitutions.insert("/work".to_string(), "/work/src");
+        substitutions.insert("/src".to_string(), "/s");
+
+        let output = substitute_path(full_path.to_string(), &substitutions);
+        assert_eq!(output, "/work/src/s");
+    }
+
+    #[test]
+    fn substitute_glob_within_component() {
+        let full_path = "/home/me/go/src/github.com/starship/starship";
+        let mut substitutions = IndexMap::new();
+        substitutions.insert("/home/*/go/src/".to_string(), "go:");
+
+        let output = substitute_path(full_path.to_string(), &substitutions);
+        assert_eq!(output, "go:github.com/starship/starship");
+
+        let nested_path = "/home/me/nested/go/src/starship";
+        let output = substitute_path(nested_path.to_string(), &substitutions);
+        assert_eq!(output, nested_path);
+    }
+
     #[test]
     fn fish_style_with_user_home_contracted_path() {
         let path = "~/starship/engines/booster/rocket";
@@ -410,6 +572,13 @@ mod tests {
         assert_eq!(output, "~/s/t/C/C/");
     }
 
+    #[test]
+    fn fish_style_with_cjk_and_dot_dirs() {
+        let path = "~/.配置/项目目录/.git/src";
+        let output = to_fish_style(2, path.to_string(), "src");
+        assert_eq!(output, "~/.配置/项目/.gi/");
+    }
+
     #[test]
     fn fish_style_with_unicode() {
         let path = "~/starship/tmp/目录/a̐éö̲/目录";
@@ -445,7 +614,7 @@ mod tests {
         let actual = ModuleRenderer::new("directory")
             // use a different physical path here as a sentinel value
             .path("/")
-            .env("PWD", with_prefix)
+            .logical_path(with_prefix)
             .shell(Shell::PowerShell)
             .config(toml::toml! {
                 [directory]
@@ -470,7 +639,7 @@ mod tests {
         let actual = ModuleRenderer::new("directory")
             // use a different physical path here as a sentinel value
             .path("/")
-            .env("PWD", with_prefix)
+            .logical_path(with_prefix)
             .shell(Shell::Bash)
             .config(toml::toml! {
                 [directory]
@@ -483,6 +652,56 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn logical_path_of_symlinked_directory() -> io::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let physical_dir = tmp_dir.path().join("physical/rocket");
+        let logical_dir = tmp_dir.path().join("logical");
+        fs::create_dir_all(&physical_dir)?;
+        symlink(&physical_dir, &logical_dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(&physical_dir)
+            .logical_path(&logical_dir)
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+                truncation_length = 2
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}/logical",
+            tmp_dir.path().file_name().unwrap().to_string_lossy()
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn physical_path_of_symlinked_directory() -> io::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let physical_dir = tmp_dir.path().join("physical/rocket");
+        let logical_dir = tmp_dir.path().join("logical");
+        fs::create_dir_all(&physical_dir)?;
+        symlink(&physical_dir, &logical_dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(&physical_dir)
+            .logical_path(&logical_dir)
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+                truncation_length = 2
+                use_logical_path = false
+            })
+            .collect();
+        let expected = Some(String::from("physical/rocket"));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
     #[cfg(not(target_os = "windows"))]
     mod linux {
         use super::*;
@@ -571,6 +790,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn home_directory_with_home_symbol() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(home_dir().unwrap().as_path())?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(tmp_dir.path())
+            .config(toml::toml! {
+                [directory]
+                home_symbol = "🏠"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(format!("🏠/{}", name))
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn other_users_home_not_contracted_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/home/alice/.config")
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+            })
+            .collect();
+        let expected = Some(String::from("/home/alice/.config"));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn other_users_home_contracted() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/home/alice/.config")
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+                contract_other_users_home = true
+            })
+            .collect();
+        let expected = Some(String::from("~alice/.config"));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn other_users_home_contracted_then_truncated() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/home/alice/.config/nvim/lua")
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+                truncation_length = 2
+                truncation_symbol = "…/"
+                contract_other_users_home = true
+            })
+            .collect();
+        let expected = Some(String::from("…/nvim/lua"));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn other_users_home_substituted() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/home/alice/project")
+            .config(toml::toml! {
+                [directory]
+                format = "$path"
+                contract_other_users_home = true
+                [directory.substitutions]
+                "~alice" = "alice:"
+            })
+            .collect();
+        let expected = Some(String::from("alice:/project"));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn home_directory() -> io::Result<()> {
         let actual = ModuleRenderer::new("directory")
@@ -623,6 +933,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn substitution_in_home_directory() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(home_dir().unwrap().as_path())?;
+        let dir = tmp_dir.path().join("engine/schematics");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(dir)
+            .config(
+                toml::from_str(&format!(
+                    r#"
+                    [directory]
+                    truncation_length = 3
+                    [directory.substitutions]
+                    "~/{}" = "rocket"
+                    "~" = "home"
+                    "#,
+                    name
+                ))
+                .unwrap(),
+            )
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("rocket/engine/schematics")
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn substitution_of_home_directory_contraction() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(home_dir().unwrap().as_path())?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(tmp_dir.path())
+            .config(toml::toml! {
+                [directory.substitutions]
+                "~" = "home"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(format!("home/{}", name))
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
     #[test]
     fn strange_substitution() -> io::Result<()> {
         let strange_sub = "/\\/;,!";
@@ -706,6 +1067,31 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn fish_directory_in_home_with_hidden_and_cjk_dirs() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(home_dir().unwrap().as_path())?;
+        let dir = tmp_dir.path().join(".config/项目目录/src/modules");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 2
+                fish_style_pwd_dir_length = 1
+            })
+            .path(&dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint(format!("~/{}/.c/项/src/modules", name.split_at(2).0))
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
     #[test]
     fn root_directory() -> io::Result<()> {
         let actual = ModuleRenderer::new("directory").path("/").collect();
@@ -1377,6 +1763,207 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn writable_directory_has_no_lock_symbol() -> io::Result<()> {
+        let tmp_dir = TempDir::new()?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(tmp_dir.path())
+            .config(toml::toml! {
+                [directory]
+                format = "$read_only"
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn read_only_directory_has_lock_symbol() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // root can write anywhere, regardless of the permissions
+        if nix::unistd::Uid::effective().is_root() {
+            return Ok(());
+        }
+
+        let tmp_dir = TempDir::new()?;
+        fs::set_permissions(tmp_dir.path(), fs::Permissions::from_mode(0o555))?;
+
+        let actual = ModuleRenderer::new("directory")
+            .path(tmp_dir.path())
+            .config(toml::toml! {
+                [directory]
+                format = "[$read_only]($read_only_style)"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Red.paint("🔒")));
+
+        fs::set_permissions(tmp_dir.path(), fs::Permissions::from_mode(0o755))?;
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    fn repo_name(repo_dir: &TempDir) -> String {
+        repo_dir
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn repo_root_style_in_subdirectory() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let dir = repo_dir.path().join("src/modules");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            ANSIStrings(&[
+                Color::Red.bold().paint(repo_name(&repo_dir)),
+                Color::Cyan.bold().paint("/src/modules"),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_at_repo_root() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                repo_root_style = "bold red"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint(repo_name(&repo_dir))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_with_custom_format() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let dir = repo_dir.path().join("src/modules");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                format = "in [$path]($style) "
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "in {} ",
+            ANSIStrings(&[
+                Color::Red.bold().paint(repo_name(&repo_dir)),
+                Color::Cyan.bold().paint("/src/modules"),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_placed_by_format() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let dir = repo_dir.path().join("src/modules");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                format = "[$repo_root]($repo_root_style) [$path]($style) "
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} {} ",
+            Color::Red.bold().paint(repo_name(&repo_dir)),
+            Color::Cyan.bold().paint("/src/modules"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_truncates_path_inside_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let dir = repo_dir.path().join("src/meters/fuel-gauge/needle");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 2
+                truncation_symbol = "…/"
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            ANSIStrings(&[
+                Color::Red.bold().paint(repo_name(&repo_dir)),
+                Color::Cyan.bold().paint("/…/fuel-gauge/needle"),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_ignored_when_truncate_to_repo_false() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let dir = repo_dir.path().join("src");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 2
+                truncate_to_repo = false
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint(format!("{}/src", repo_name(&repo_dir)))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn truncation_symbol_windows_root_not_truncated() -> io::Result<()> {
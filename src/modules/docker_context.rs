@@ -44,22 +44,23 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             let current_context = root.get("currentContext")?;
             match current_context {
                 serde_json::Value::String(ctx) => {
-                    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-                        formatter
-                            .map_meta(|variable, _| match variable {
-                                "symbol" => Some(config.symbol),
-                                _ => None,
-                            })
-                            .map_style(|variable| match variable {
-                                "style" => Some(Ok(config.style)),
-                                _ => None,
-                            })
-                            .map(|variable| match variable {
-                                "context" => Some(Ok(ctx)),
-                                _ => None,
-                            })
-                            .parse(None)
-                    });
+                    let parsed = StringFormatter::new_with_context(config.format, context)
+                        .and_then(|formatter| {
+                            formatter
+                                .map_meta(|variable, _| match variable {
+                                    "symbol" => Some(config.symbol),
+                                    _ => None,
+                                })
+                                .map_style(|variable| match variable {
+                                    "style" => Some(Ok(config.style)),
+                                    _ => None,
+                                })
+                                .map(|variable| match variable {
+                                    "context" => Some(Ok(ctx)),
+                                    _ => None,
+                                })
+                                .parse(None)
+                        });
 
                     module.set_segments(match parsed {
                         Ok(segments) => segments,
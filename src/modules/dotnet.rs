@@ -8,7 +8,7 @@ use std::str;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::dotnet::DotnetConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 type JValue = serde_json::Value;
@@ -52,7 +52,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // Typically it is twice as fast as running `dotnet --version`.
     let enable_heuristic = config.heuristic;
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
@@ -66,11 +66,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "version" => {
                     let version = if enable_heuristic {
                         let repo_root = context.get_repo().ok().and_then(|r| r.root.as_deref());
-                        estimate_dotnet_version(&dotnet_files, &context.current_dir, repo_root)
+                        estimate_dotnet_version(
+                            context,
+                            &dotnet_files,
+                            &context.current_dir,
+                            repo_root,
+                        )
                     } else {
-                        get_version_from_cli()
+                        get_version_from_cli(context)
                     };
-                    version.map(|v| Ok(v.0))
+                    version.map(|v| {
+                        Ok(VersionFormatter::format_module_version(
+                            module.get_name(),
+                            &v.0,
+                            config.version_format,
+                        ))
+                    })
                 }
                 "tfm" => find_current_tfm(&dotnet_files).map(Ok),
                 _ => None,
@@ -136,6 +147,7 @@ fn get_tfm_from_project_file(path: &Path) -> Option<String> {
 }
 
 fn estimate_dotnet_version(
+    context: &Context,
     files: &[DotNetFile],
     current_dir: &Path,
     repo_root: Option<&Path>,
@@ -150,17 +162,18 @@ fn estimate_dotnet_version(
 
     match relevant_file.file_type {
         FileType::GlobalJson => get_pinned_sdk_version_from_file(relevant_file.path.as_path())
-            .or_else(get_latest_sdk_from_cli),
+            .or_else(|| get_latest_sdk_from_cli(context)),
         FileType::SolutionFile => {
             // With this heuristic, we'll assume that a "global.json" won't
             // be found in any directory above the solution file.
-            get_latest_sdk_from_cli()
+            get_latest_sdk_from_cli(context)
         }
         _ => {
             // If we see a dotnet project, we'll check a small number of neighboring
             // directories to see if we can find a global.json. Otherwise, assume the
             // latest SDK is in use.
-            try_find_nearby_global_json(current_dir, repo_root).or_else(get_latest_sdk_from_cli)
+            try_find_nearby_global_json(current_dir, repo_root)
+                .or_else(|| get_latest_sdk_from_cli(context))
         }
     }
 }
@@ -234,12 +247,7 @@ fn get_pinned_sdk_version(json: &str) -> Option<Version> {
                 JValue::Object(sdk) => {
                     let version = sdk.get("version")?;
                     match version {
-                        JValue::String(version_string) => {
-                            let mut buffer = String::with_capacity(version_string.len() + 1);
-                            buffer.push('v');
-                            buffer.push_str(version_string);
-                            Some(Version(buffer))
-                        }
+                        JValue::String(version_string) => Some(Version(version_string.clone())),
                         _ => None,
                     }
                 }
@@ -288,13 +296,13 @@ fn map_str_to_lower(value: Option<&OsStr>) -> Option<String> {
     Some(value?.to_str()?.to_ascii_lowercase())
 }
 
-fn get_version_from_cli() -> Option<Version> {
-    let version_output = utils::exec_cmd("dotnet", &["--version"])?;
-    Some(Version(format!("v{}", version_output.stdout.trim())))
+fn get_version_from_cli(context: &Context) -> Option<Version> {
+    let version_output = context.exec_cmd("dotnet", &["--version"])?;
+    Some(Version(version_output.stdout.trim().to_string()))
 }
 
-fn get_latest_sdk_from_cli() -> Option<Version> {
-    match utils::exec_cmd("dotnet", &["--list-sdks"]) {
+fn get_latest_sdk_from_cli(context: &Context) -> Option<Version> {
+    match context.exec_cmd("dotnet", &["--list-sdks"]) {
         Some(sdks_output) => {
             fn parse_failed<T>() -> Option<T> {
                 log::warn!("Unable to parse the output from `dotnet --list-sdks`.");
@@ -309,11 +317,7 @@ fn get_latest_sdk_from_cli() -> Option<Version> {
                 .or_else(parse_failed)?;
             let take_until = latest_sdk.find('[').or_else(parse_failed)? - 1;
             if take_until > 1 {
-                let version = &latest_sdk[..take_until];
-                let mut buffer = String::with_capacity(version.len() + 1);
-                buffer.push('v');
-                buffer.push_str(version);
-                Some(Version(buffer))
+                Some(Version(latest_sdk[..take_until].to_string()))
             } else {
                 parse_failed()
             }
@@ -325,7 +329,7 @@ fn get_latest_sdk_from_cli() -> Option<Version> {
                 "Received a non-success exit code from `dotnet --list-sdks`. \
                  Falling back to `dotnet --version`.",
             );
-            get_version_from_cli()
+            get_version_from_cli(context)
         }
     }
 }
@@ -621,7 +625,7 @@ mod tests {
     "#;
 
         let version = get_pinned_sdk_version(json_text).unwrap();
-        assert_eq!("v1.2.3", version.0);
+        assert_eq!("1.2.3", version.0);
     }
 
     #[test]
@@ -1,8 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::elm::ElmConfig;
-use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current Elm version
 ///
@@ -13,24 +12,23 @@ use crate::utils;
 ///     - The current directory contains a `elm-stuff` folder
 ///     - The current directory contains a `*.elm` files
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("elm");
+    let config: ElmConfig = ElmConfig::try_load(module.config);
+
     let is_elm_project = context
         .try_begin_scan()?
-        .set_files(&["elm.json", "elm-package.json", ".elm-version"])
-        .set_extensions(&["elm"])
-        .set_folders(&["elm-stuff"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_elm_project {
         return None;
     }
 
-    let elm_version = utils::exec_cmd("elm", &["--version"])?.stdout;
-    let module_version = Some(format!("v{}", elm_version.trim()))?;
-
-    let mut module = context.new_module("elm");
-    let config: ElmConfig = ElmConfig::try_load(module.config);
+    let elm_version = context.exec_cmd("elm", &["--version"])?.stdout;
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -41,7 +39,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&module_version)),
+                "version" => Some(Ok(VersionFormatter::format_module_version(
+                    module.get_name(),
+                    elm_version.trim(),
+                    config.version_format,
+                ))),
                 _ => None,
             })
             .parse(None)
@@ -6,16 +6,30 @@ use crate::formatter::StringFormatter;
 
 /// Creates a module with the value of the chosen environment variable
 ///
+/// `name` selects the module configured under `[env_var.<name>]`, while `None` uses the
+/// options set directly under `[env_var]`.
+///
 /// Will display the environment variable's value if all of the following criteria are met:
 ///     - env_var.disabled is absent or false
-///     - env_var.variable is defined
-///     - a variable named as the value of env_var.variable is defined
-pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let mut module = context.new_module("env_var");
+///     - env_var.variable is defined, or the module has a name to default to
+///     - a variable named as the value of env_var.variable is defined, or env_var.default is set
+pub fn module<'a>(name: Option<&str>, context: &'a Context) -> Option<Module<'a>> {
+    let mut module = match name {
+        Some(name) => Module::new(
+            &format!("env_var.{}", name),
+            super::description("env_var"),
+            Some(context.config.get_env_var_module_config(name)?),
+        ),
+        None => context.new_module("env_var"),
+    };
     let config: EnvVarConfig = EnvVarConfig::try_load(module.config);
+    if config.disabled {
+        return None;
+    }
 
-    let env_value = get_env_value(context, config.variable?, config.default)?;
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let variable = config.variable.or(name)?;
+    let env_value = get_env_value(context, variable, config.default)?;
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -188,6 +202,123 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn env_namespace_alongside_env_value() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+                format = "with [$env_value@${env:OTHER_VAR}](black bold dimmed) "
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .env("OTHER_VAR", "moon")
+            .collect();
+        let expected = Some(format!(
+            "with {} ",
+            style().paint(format!("{}@moon", TEST_VAR_VALUE))
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_module_defined_variable() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var.TEST_VAR")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                symbol = "■ "
+                format = "with [$symbol$env_value]($style) "
+                style = "red"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!(
+            "with {} ",
+            Color::Red.paint(format!("■ {}", TEST_VAR_VALUE))
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_module_undefined_variable_with_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var.TEST_VAR")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                default = "N/A"
+            })
+            .collect();
+        let expected = Some(format!("with {} ", style().paint("N/A")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_module_undefined_variable_without_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var.TEST_VAR")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_module_with_variable() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var.vault")
+            .config(toml::toml! {
+                [env_var.vault]
+                variable = "TEST_VAR"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .env("vault", "wrong")
+            .collect();
+        let expected = Some(format!("with {} ", style().paint(TEST_VAR_VALUE)));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_module_disabled() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var.TEST_VAR")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                disabled = true
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn named_modules_do_not_affect_the_legacy_module() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+
+                [env_var.OTHER_VAR]
+                style = "red"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .env("OTHER_VAR", "moon")
+            .collect();
+        let expected = Some(format!("with {} ", style().paint(TEST_VAR_VALUE)));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     fn style() -> Style {
         // default style
         Color::Black.bold().dimmed()
@@ -0,0 +1,42 @@
+use super::{Context, Module};
+
+use crate::config::{parse_style_string, RootModuleConfig};
+use crate::configs::fill::FillConfig;
+use crate::segment::Segment;
+
+/// Creates a module that fills the rest of the line
+///
+/// The symbol is only repeated to the width of the terminal once the whole prompt is rendered,
+/// so that the fill takes up all the space left by the other modules.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("fill");
+    let config: FillConfig = FillConfig::try_load(module.config);
+
+    let style = parse_style_string(config.style);
+
+    module.set_segments(vec![Segment::fill(style, config.symbol)]);
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::io;
+
+    #[test]
+    fn basic() -> io::Result<()> {
+        let actual = ModuleRenderer::new("fill")
+            .config(toml::toml! {
+                [fill]
+                style = "bold green"
+                symbol = "*-"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Green.bold().paint("*-")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+}
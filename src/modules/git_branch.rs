@@ -1,7 +1,8 @@
+use regex::Regex;
+use std::convert::TryFrom;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::{Context, Module, RootModuleConfig};
-use git2::Repository;
 
 use crate::configs::git_branch::GitBranchConfig;
 use crate::formatter::StringFormatter;
@@ -22,24 +23,31 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         );
         std::usize::MAX
     } else {
-        config.truncation_length as usize
+        // Lengths that don't fit in a usize can't truncate anything anyway
+        usize::try_from(config.truncation_length).unwrap_or(usize::MAX)
     };
 
     let repo = context.get_repo().ok()?;
 
-    let repo_root = repo.root.as_ref()?;
-    let git_repo = Repository::open(repo_root).ok()?;
+    let git_repo = repo.open().ok()?;
     let is_detached = git_repo.head_detached().ok()?;
     if config.only_attached && is_detached {
         return None;
     };
 
     let branch_name = repo.branch.as_ref()?;
+    let style = get_branch_style(branch_name, &config);
+    let remote_symbol = git_repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|origin| get_remote_symbol(origin.url()?, &config));
     let mut graphemes: Vec<&str> = branch_name.graphemes(true).collect();
 
     let mut remote_graphemes: Vec<&str> = Vec::new();
-    if let Some(remote_branch) = repo.remote.as_ref() {
-        remote_graphemes = remote_branch.graphemes(true).collect();
+    let mut remote_name_graphemes: Vec<&str> = Vec::new();
+    if let Some(remote) = repo.remote.as_ref() {
+        remote_graphemes = remote.branch.graphemes(true).collect();
+        remote_name_graphemes = remote.name.graphemes(true).collect();
     }
 
     let trunc_len = len.min(graphemes.len());
@@ -56,17 +64,25 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         remote_graphemes.truncate(trunc_len + 1);
     }
 
+    let trunc_len = len.min(remote_name_graphemes.len());
+    if trunc_len < remote_name_graphemes.len() {
+        // The truncation symbol should only be added if we truncate
+        remote_name_graphemes[trunc_len] = truncation_symbol;
+        remote_name_graphemes.truncate(trunc_len + 1);
+    }
+
     let show_remote = config.always_show_remote
         || (!graphemes.eq(&remote_graphemes) && !remote_graphemes.is_empty());
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "remote_symbol" => remote_symbol,
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style)),
                 _ => None,
             })
             .map(|variable| match variable {
@@ -78,6 +94,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                         None
                     }
                 }
+                "remote_name" => {
+                    if remote_name_graphemes.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(remote_name_graphemes.concat()))
+                    }
+                }
+                "remote_branch" => {
+                    if remote_graphemes.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(remote_graphemes.concat()))
+                    }
+                }
                 _ => None,
             })
             .parse(None)
@@ -94,6 +124,37 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Returns the style of the first `branch_styles` pattern matching the branch,
+/// where `*` matches any sequence of characters, or the default `style`
+fn get_branch_style<'a>(branch_name: &str, config: &GitBranchConfig<'a>) -> &'a str {
+    config
+        .branch_styles
+        .iter()
+        .find(|(pattern, _)| glob_matches(pattern, branch_name))
+        .map(|(_, style)| *style)
+        .unwrap_or(config.style)
+}
+
+/// Returns the symbol of the first `remote_symbols` pattern found in the remote's URL
+fn get_remote_symbol<'a>(url: &str, config: &GitBranchConfig<'a>) -> Option<&'a str> {
+    config
+        .remote_symbols
+        .iter()
+        .find(|(pattern, _)| glob_matches(&format!("*{}*", pattern), url))
+        .map(|(_, symbol)| *symbol)
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = regex::escape(pattern).replace(r"\*", ".*");
+    match Regex::new(&format!("^{}$", pattern)) {
+        Ok(re) => re.is_match(text),
+        Err(error) => {
+            log::warn!("Invalid pattern in git_branch config: {}", error);
+            false
+        }
+    }
+}
+
 fn get_first_grapheme(text: &str) -> &str {
     UnicodeSegmentation::graphemes(text, true)
         .next()
@@ -102,7 +163,7 @@ fn get_first_grapheme(text: &str) -> &str {
 
 #[cfg(test)]
 mod tests {
-    use ansi_term::Color;
+    use ansi_term::{Color, Style};
     use std::io;
     use std::process::Command;
 
@@ -121,6 +182,41 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn finds_repo_from_physical_path_of_symlink() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let link_dir = tempfile::tempdir()?;
+        let logical_dir = link_dir.path().join("repo-link");
+        std::os::unix::fs::symlink(repo_dir.path(), &logical_dir)?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(repo_dir.path())
+            .logical_path(&logical_dir)
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        link_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_in_hg_repo_inside_git_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let hg_dir = repo_dir.path().join("vendor");
+        std::fs::create_dir_all(hg_dir.join(".hg"))?;
+
+        let actual = ModuleRenderer::new("git_branch").path(&hg_dir).collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_changed_truncation_symbol() -> io::Result<()> {
         test_truncate_length_with_config(
@@ -199,6 +295,59 @@ mod tests {
         test_truncate_length("がんばってね", 4, "がんばっ", "…")
     }
 
+    #[test]
+    fn test_emoji_truncation() -> io::Result<()> {
+        test_truncate_length("👨‍👩‍👧‍👦🚀_release", 2, "👨‍👩‍👧‍👦🚀", "…")
+    }
+
+    #[test]
+    fn test_huge_length_truncation() -> io::Result<()> {
+        test_truncate_length("1337_hello_world", i64::MAX, "1337_hello_world", "")
+    }
+
+    #[test]
+    fn test_remote_symbol_ssh_url() -> io::Result<()> {
+        test_remote_symbol(Some("git@github.com:starship/starship.git"), "GH ")
+    }
+
+    #[test]
+    fn test_remote_symbol_https_url() -> io::Result<()> {
+        test_remote_symbol(
+            Some("https://gitlab.example.com/starship/starship.git"),
+            "GL ",
+        )
+    }
+
+    #[test]
+    fn test_remote_symbol_glob() -> io::Result<()> {
+        test_remote_symbol(Some("ssh://git@bitbucket.org/starship/starship.git"), "BB ")
+    }
+
+    #[test]
+    fn test_remote_symbol_unknown_host() -> io::Result<()> {
+        test_remote_symbol(Some("https://git.example.com/starship.git"), "")
+    }
+
+    #[test]
+    fn test_remote_symbol_no_remote() -> io::Result<()> {
+        test_remote_symbol(None, "")
+    }
+
+    #[test]
+    fn test_branch_style_exact_match() -> io::Result<()> {
+        test_branch_style("production", Color::Red.bold())
+    }
+
+    #[test]
+    fn test_branch_style_glob_match() -> io::Result<()> {
+        test_branch_style("release/2024-Q3/hotfix", Color::Yellow.normal())
+    }
+
+    #[test]
+    fn test_branch_style_no_match() -> io::Result<()> {
+        test_branch_style("feature/production", Color::Purple.bold())
+    }
+
     #[test]
     fn test_format_no_branch() -> io::Result<()> {
         test_format("1337_hello_world", "no_branch", "", "no_branch")
@@ -209,6 +358,121 @@ mod tests {
         test_format("1337_hello_world", "$branch", "", "1337_hello_world")
     }
 
+    #[test]
+    fn test_format_branch_name_with_slashes() -> io::Result<()> {
+        test_format("feature/login/oauth", "$branch", "", "feature/login/oauth")
+    }
+
+    #[test]
+    fn test_format_remote_branch_name_with_slashes() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["update-ref", "refs/remotes/origin/feature/remote", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        Command::new("git")
+            .args(&[
+                "checkout",
+                "-b",
+                "feature/local",
+                "--track",
+                "origin/feature/remote",
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "$branch:$remote"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(Some(String::from("feature/local:feature/remote")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_format_remote_name_and_branch() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["update-ref", "refs/remotes/fork/main", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        Command::new("git")
+            .args(&["remote", "add", "fork", "https://example.com/fork.git"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        Command::new("git")
+            .args(&["branch", "--set-upstream-to", "fork/main"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "$branch(:$remote_name/$remote_branch)"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(Some(String::from("master:fork/main")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_format_remote_with_packed_refs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", "local", "--track", "origin/master"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        Command::new("git")
+            .args(&["pack-refs", "--all", "--prune"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "$branch(:$remote_name/$remote_branch)"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(Some(String::from("local:origin/master")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_format_remote_name_and_branch_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", "no-upstream"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "$branch(:$remote_name/$remote_branch)"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(Some(String::from("no-upstream")), actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_format_just_branch_name_color() -> io::Result<()> {
         test_format(
@@ -249,6 +513,34 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_format_with_trunc_modifier() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", "feature/fuel-gauge"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                format = "on [$symbol${branch|upper|trunc7}]($style) "
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple
+                .bold()
+                .paint(format!("\u{e0a0} {}", "FEATURE")),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_works_with_unborn_default_branch() -> io::Result<()> {
         let repo_dir = tempfile::tempdir()?;
@@ -327,34 +619,296 @@ mod tests {
         repo_dir.close()
     }
 
-    // This test is not possible until we switch to `git status --porcelain`
-    // where we can mock the env for the specific git process. This is because
-    // git2 does not care about our mocking and when we set the real `GIT_DIR`
-    // variable it will interfere with the other tests.
-    // #[test]
-    // fn test_git_dir_env_variable() -> io::Result<()> {let repo_dir =
-    // tempfile::tempdir()?;
+    #[test]
+    fn test_render_branch_on_detached() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "b703eb3"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(&repo_dir.path())
+            .collect();
+
+        // The commit hash of a detached HEAD is rendered by the `git_commit` module
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "HEAD")),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_branch_in_worktree() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree_path = worktree_dir.path().join("worktree");
+
+        Command::new("git")
+            .args(&["worktree", "add", "-b", "test_worktree"])
+            .arg(&worktree_path)
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(&worktree_path)
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple
+                .bold()
+                .paint(format!("\u{e0a0} {}", "test_worktree")),
+        ));
+
+        assert_eq!(expected, actual);
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_dir_env_variable() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let other_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .env("GIT_DIR", repo_dir.path().join(".git").to_string_lossy())
+            .path(other_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        other_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_dir_and_work_tree_env_variables() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let bare_dir = tempfile::tempdir()?;
+        let work_tree = tempfile::tempdir()?;
+
+        Command::new("git")
+            .args(&["clone", "--bare"])
+            .arg(repo_dir.path())
+            .arg(bare_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .env("GIT_DIR", bare_dir.path().to_string_lossy())
+            .env("GIT_WORK_TREE", work_tree.path().to_string_lossy())
+            .path(work_tree.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        work_tree.close()?;
+        bare_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_path() -> io::Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let parent_dir = tempfile::tempdir()?;
+        let clone_dir = parent_dir.path().join(OsStr::from_bytes(b"non_utf8_\xff"));
+
+        Command::new("git")
+            .arg("clone")
+            .arg(repo_dir.path())
+            .arg(&clone_dir)
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch").path(&clone_dir).collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        parent_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_ceiling_directories() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let sub_dir = repo_dir.path().join("sub_dir");
+        std::fs::create_dir(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .env("GIT_CEILING_DIRECTORIES", repo_dir.path().to_string_lossy())
+            .path(&sub_dir)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_ceiling_directories_in_repo_root() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .env("GIT_CEILING_DIRECTORIES", repo_dir.path().to_string_lossy())
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
 
-    //     Command::new("git")
-    //         .args(&["init"])
-    //         .current_dir(&repo_dir)
-    //         .output()?;
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
 
-    //     // git2 does not care about our mocking
-    //     std::env::set_var("GIT_DIR", repo_dir.path().join(".git"));
+    #[test]
+    fn test_bare_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let bare_dir = tempfile::tempdir()?;
 
-    //     let actual = ModuleRenderer::new("git_branch").collect();
+        Command::new("git")
+            .args(&["clone", "--bare"])
+            .arg(repo_dir.path())
+            .arg(bare_dir.path())
+            .output()?;
 
-    //     std::env::remove_var("GIT_DIR");
+        let actual = ModuleRenderer::new("git_branch")
+            .path(bare_dir.path())
+            .collect();
 
-    //     let expected = Some(format!(
-    //         "on {} ",
-    //         Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
-    //     ));
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
 
-    //     assert_eq!(expected, actual);
-    //     repo_dir.close()
-    // }
+        assert_eq!(expected, actual);
+        bare_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_bare_repo_without_commits() -> io::Result<()> {
+        let bare_dir = tempfile::tempdir()?;
+
+        Command::new("git")
+            .args(&["-c", "init.defaultBranch=master", "init", "--bare"])
+            .current_dir(bare_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(bare_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        bare_dir.close()
+    }
+
+    #[test]
+    fn test_ignores_nonexistent_git_dir_env_variable() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .env(
+                "GIT_DIR",
+                repo_dir.path().join("nonexistent").to_string_lossy(),
+            )
+            .env(
+                "GIT_WORK_TREE",
+                repo_dir.path().join("nonexistent").to_string_lossy(),
+            )
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint(format!("\u{e0a0} {}", "master")),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn test_remote_symbol(origin_url: Option<&str>, expected_symbol: &str) -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        match origin_url {
+            Some(url) => Command::new("git")
+                .args(&["remote", "set-url", "origin", url])
+                .current_dir(repo_dir.path())
+                .output()?,
+            None => Command::new("git")
+                .args(&["remote", "remove", "origin"])
+                .current_dir(repo_dir.path())
+                .output()?,
+        };
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "$remote_symbol$branch"
+                    [git_branch.remote_symbols]
+                    "github.com" = "GH "
+                    gitlab = "GL "
+                    "bitbucket.*/" = "BB "
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("{}master", expected_symbol));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn test_branch_style(branch_name: &str, expected_style: Style) -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", branch_name])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                    format = "[$branch]($style)"
+                    [git_branch.branch_styles]
+                    production = "bold red"
+                    "release/*" = "yellow"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(expected_style.paint(branch_name).to_string());
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
 
     fn test_truncate_length(
         branch_name: &str,
@@ -1,8 +1,17 @@
+use chrono::Utc;
+use git2::{DescribeOptions, Repository, StatusOptions};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
 use super::{Context, Module, RootModuleConfig};
-use git2::Repository;
 
 use crate::configs::git_commit::GitCommitConfig;
+use crate::context::Repo;
 use crate::formatter::StringFormatter;
+use crate::utils;
 
 /// Creates a module with the Git commit in the current directory
 ///
@@ -12,8 +21,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: GitCommitConfig = GitCommitConfig::try_load(module.config);
 
     let repo = context.get_repo().ok()?;
-    let repo_root = repo.root.as_ref()?;
-    let git_repo = Repository::open(repo_root).ok()?;
+    let git_repo = repo.open().ok()?;
 
     let is_detached = git_repo.head_detached().ok()?;
     if config.only_detached && !is_detached {
@@ -23,10 +31,40 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let git_head = git_repo.head().ok()?;
     let head_commit = git_head.peel_to_commit().ok()?;
     let commit_oid = head_commit.id();
+    let author = head_commit.author();
+    let author_name = author.name().map(String::from);
+    let author_email = author.email().map(String::from);
+    let commit_time = head_commit.time().seconds();
+
+    let mut tag_name = String::new();
+    if !config.tag_disabled {
+        // Let's get repo tags names
+        let tag_names = git_repo.tag_names(None).ok()?;
+        // Tag names which aren't valid UTF-8 can't be displayed, so skip them
+        let tag_and_refs = tag_names.iter().flatten().flat_map(|name| {
+            let full_tag = format!("refs/tags/{}", name);
+            git_repo
+                .find_reference(&full_tag)
+                .map(|reference| (String::from(name), reference))
+        });
+
+        // Let's check if HEAD has some tag. If several, only gets first...
+        // Tags pointing at trees or blobs can't match HEAD and are skipped.
+        for (name, reference) in tag_and_refs {
+            if reference.peel_to_commit().map(|commit| commit.id()).ok() == Some(commit_oid) {
+                tag_name = name;
+                break;
+            }
+        }
+    }
 
-    let mut parsed;
+    let description = if config.describe_disabled {
+        None
+    } else {
+        describe(repo, &config, context.command_timeout())
+    };
 
-    parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
@@ -37,53 +75,34 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     commit_oid.as_bytes(),
                     config.commit_hash_length,
                 ))),
+                // If we have tag...
+                "tag" if !tag_name.is_empty() => {
+                    Some(Ok(format!(" {}{}", &config.tag_symbol, &tag_name)))
+                }
+                "describe" => description
+                    .as_ref()
+                    .map(|description| description.text.clone())
+                    .map(Ok),
+                "commits_since_tag" => description
+                    .as_ref()
+                    .and_then(|description| description.commits_since_tag)
+                    .map(|count| count.to_string())
+                    .map(Ok),
+                "author" => author_name.clone().map(Ok),
+                "email" => author_email.clone().map(Ok),
+                "age" => Some(Ok(utils::humanize_age(commit_time, Utc::now().timestamp()))),
+                "signature" if config.show_signature => {
+                    let symbol = match get_signature_status(context, repo)? {
+                        SignatureStatus::Good => config.signed_symbol,
+                        SignatureStatus::Unsigned | SignatureStatus::Bad => config.unsigned_symbol,
+                    };
+                    Some(Ok(format!(" {}", symbol)))
+                }
                 _ => None,
             })
             .parse(None)
     });
 
-    if !config.tag_disabled {
-        // Let's get repo tags names
-        let tag_names = git_repo.tag_names(None).ok()?;
-        let tag_and_refs = tag_names.iter().flat_map(|name| {
-            let full_tag = format!("refs/tags/{}", name.unwrap());
-            git_repo
-                .find_reference(&full_tag)
-                .map(|reference| (String::from(name.unwrap()), reference))
-        });
-
-        let mut tag_name = String::new();
-        // Let's check if HEAD has some tag. If several, only gets first...
-        for (name, reference) in tag_and_refs {
-            if commit_oid == reference.peel_to_commit().ok()?.id() {
-                tag_name = name;
-                break;
-            }
-        }
-        // If we have tag...
-        if !tag_name.is_empty() {
-            parsed = StringFormatter::new(config.format).and_then(|formatter| {
-                formatter
-                    .map_style(|variable| match variable {
-                        "style" => Some(Ok(config.style)),
-                        _ => None,
-                    })
-                    .map(|variable| match variable {
-                        "hash" => Some(Ok(id_to_hex_abbrev(
-                            commit_oid.as_bytes(),
-                            config.commit_hash_length,
-                        ))),
-                        _ => None,
-                    })
-                    .map(|variable| match variable {
-                        "tag" => Some(Ok(format!(" {}{}", &config.tag_symbol, &tag_name))),
-                        _ => None,
-                    })
-                    .parse(None)
-            });
-        }
-    };
-
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
@@ -95,6 +114,134 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+struct Description {
+    /// `git describe --tags --always` style description of HEAD
+    text: String,
+    /// Number of commits on top of the closest tag, if there is one
+    commits_since_tag: Option<usize>,
+}
+
+/// Describes HEAD relative to the closest tag, falling back to the abbreviated commit hash
+/// in repositories without tags. `describe_dirty_suffix` is appended if tracked files are
+/// modified, which is only checked if it's set since it takes a scan of the working tree.
+/// Describing runs on a separate thread and is given up on after `timeout`.
+fn describe(repo: &Repo, config: &GitCommitConfig, timeout: Duration) -> Option<Description> {
+    let repo = repo.clone();
+    let describe_tags = config.describe_tags;
+    let describe_match = config.describe_match.to_owned();
+    let dirty_suffix = config.describe_dirty_suffix.to_owned();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let description = repo.open().and_then(|git_repo| {
+            describe_head(&git_repo, describe_tags, &describe_match, &dirty_suffix)
+        });
+        let _ = sender.send(description);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(description)) => Some(description),
+        Ok(Err(error)) => {
+            log::debug!("Unable to describe HEAD: {}", error);
+            None
+        }
+        Err(_) => {
+            log::warn!(
+                "Describing HEAD timed out after {:?}. You can set command_timeout in your config to a higher value.",
+                timeout
+            );
+            None
+        }
+    }
+}
+
+fn describe_head(
+    git_repo: &Repository,
+    describe_tags: bool,
+    describe_match: &str,
+    dirty_suffix: &str,
+) -> Result<Description, git2::Error> {
+    let mut options = DescribeOptions::new();
+    options.show_commit_oid_as_fallback(true);
+    if describe_tags {
+        options.describe_tags();
+    }
+    if !describe_match.is_empty() {
+        options.pattern(describe_match);
+    }
+    let text = git_repo.describe(&options)?.format(None)?;
+
+    static DESCRIBE_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?P<tag>.+)-(?P<count>\d+)-g[0-9a-f]+$").unwrap());
+    let commits_since_tag = match DESCRIBE_RE.captures(&text) {
+        Some(caps) => caps["count"].parse().ok(),
+        None => {
+            // Either HEAD is tagged, or there is no tag and this is the commit hash
+            let is_tag = git_repo
+                .find_reference(&format!("refs/tags/{}", text))
+                .is_ok();
+            if is_tag {
+                Some(0)
+            } else {
+                None
+            }
+        }
+    };
+
+    let is_dirty = || {
+        is_dirty(git_repo).unwrap_or_else(|error| {
+            log::debug!("Unable to check if the working tree is dirty: {}", error);
+            false
+        })
+    };
+    let text = if !dirty_suffix.is_empty() && is_dirty() {
+        text + dirty_suffix
+    } else {
+        text
+    };
+
+    Ok(Description {
+        text,
+        commits_since_tag,
+    })
+}
+
+/// Whether tracked files are modified, like `git describe --dirty` checks
+fn is_dirty(git_repo: &Repository) -> Result<bool, git2::Error> {
+    let statuses = git_repo.statuses(Some(
+        StatusOptions::new()
+            .include_untracked(false)
+            .include_ignored(false)
+            .exclude_submodules(true),
+    ))?;
+    Ok(!statuses.is_empty())
+}
+
+#[derive(Debug, PartialEq)]
+enum SignatureStatus {
+    Good,
+    Unsigned,
+    Bad,
+}
+
+/// Checks the signature of HEAD with git, which runs gpg or ssh-keygen to verify it
+fn get_signature_status(context: &Context, repo: &Repo) -> Option<SignatureStatus> {
+    let dir = repo.root.as_ref().or(repo.path.as_ref())?.to_str()?;
+    let output = context.exec_cmd("git", &["-C", dir, "log", "-1", "--format=%G?"])?;
+    parse_signature_status(&output.stdout)
+}
+
+/// Parses the `%G?` placeholder of `git log`
+fn parse_signature_status(status: &str) -> Option<SignatureStatus> {
+    match status.trim() {
+        // Good signatures, even if the key is untrusted, expired or revoked
+        "G" | "U" | "X" | "Y" | "R" => Some(SignatureStatus::Good),
+        "N" => Some(SignatureStatus::Unsigned),
+        // Bad signatures and ones which can't be checked, e.g. without the key
+        "B" | "E" => Some(SignatureStatus::Bad),
+        _ => None,
+    }
+}
+
 /// len specifies length of hex encoded string
 pub fn id_to_hex_abbrev(bytes: &[u8], len: usize) -> String {
     bytes
@@ -110,9 +257,11 @@ pub fn id_to_hex_abbrev(bytes: &[u8], len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use ansi_term::Color;
+    use chrono::Utc;
     use std::process::Command;
     use std::{io, str};
 
+    use super::{parse_signature_status, SignatureStatus};
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
 
     #[test]
@@ -161,6 +310,97 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn test_render_unsigned_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let mut git_output = Command::new("git")
+            .args(&["rev-parse", "HEAD"])
+            .current_dir(&repo_dir.path())
+            .output()?
+            .stdout;
+        git_output.truncate(7);
+        let expected_hash = str::from_utf8(&git_output).unwrap();
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    show_signature = true
+            })
+            .path(&repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Green
+                .bold()
+                .paint(format!("({} ✗)", expected_hash))
+                .to_string()
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_author_and_age() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let three_days_ago = Utc::now().timestamp() - 3 * 24 * 60 * 60 - 60;
+        let date = format!("@{}", three_days_ago);
+
+        Command::new("git")
+            .args(&[
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Review me",
+                "--no-gpg-sign",
+            ])
+            .args(&["--author", "Alice <alice@example.com>", "--date", &date])
+            .env("GIT_COMMITTER_DATE", &date)
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    format = "[\\($age ago, $author <$email>\\)]($style)"
+            })
+            .path(&repo_dir.path())
+            .collect();
+
+        let expected = Some(
+            Color::Green
+                .bold()
+                .paint("(3d ago, Alice <alice@example.com>)")
+                .to_string(),
+        );
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_parse_signature_status() {
+        let cases = [
+            ("G\n", Some(SignatureStatus::Good)),
+            ("U\n", Some(SignatureStatus::Good)),
+            ("X\n", Some(SignatureStatus::Good)),
+            ("Y\n", Some(SignatureStatus::Good)),
+            ("R\n", Some(SignatureStatus::Good)),
+            ("N\n", Some(SignatureStatus::Unsigned)),
+            ("B\n", Some(SignatureStatus::Bad)),
+            ("E\n", Some(SignatureStatus::Bad)),
+            ("", None),
+        ];
+
+        for (status, expected) in cases.iter() {
+            assert_eq!(*expected, parse_signature_status(status), "{:?}", status);
+        }
+    }
+
     #[test]
     fn test_render_commit_hash_len_override() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
@@ -284,6 +524,225 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_render_commit_hash_with_non_commit_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["tag", "a-tree-tag", "HEAD^{tree}"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let mut git_commit = Command::new("git")
+            .args(&["rev-parse", "HEAD"])
+            .current_dir(&repo_dir.path())
+            .output()?
+            .stdout;
+        git_commit.truncate(7);
+        let commit_output = str::from_utf8(&git_commit).unwrap().trim();
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    tag_disabled = false
+                    tag_symbol = ""
+            })
+            .path(&repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Green
+                .bold()
+                .paint(format!("({})", commit_output))
+                .to_string()
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_commit_hash_with_packed_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["tag", "packedTag", "-m", "Testing packed tags"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        Command::new("git")
+            .args(&["pack-refs", "--all", "--prune"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let mut git_commit = Command::new("git")
+            .args(&["rev-parse", "HEAD"])
+            .current_dir(&repo_dir.path())
+            .output()?
+            .stdout;
+        git_commit.truncate(7);
+        let commit_output = str::from_utf8(&git_commit).unwrap().trim();
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    tag_disabled = false
+                    tag_symbol = ""
+            })
+            .path(&repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Green
+                .bold()
+                .paint(format!("({} packedTag)", commit_output))
+                .to_string()
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_without_tags() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = render_describe(repo_dir.path());
+        let expected = Some(format!("{} ", git_describe(repo_dir.path(), &[])?));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_with_commits_since_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["tag", "v1.0.0", "HEAD~1"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let actual = render_describe(repo_dir.path());
+        let expected = Some(format!("{} 1", git_describe(repo_dir.path(), &[])?));
+
+        assert!(expected.as_ref().unwrap().starts_with("v1.0.0-1-g"));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_on_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["tag", "v1.0.0"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let actual = render_describe(repo_dir.path());
+        let expected = Some(String::from("v1.0.0 0"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_dirty() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        std::fs::write(repo_dir.path().join("readme.md"), "modified")?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    describe_disabled = false
+                    describe_dirty_suffix = "-dirty"
+                    format = "$describe $commits_since_tag"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!("{} ", git_describe(repo_dir.path(), &["--dirty"])?));
+
+        assert!(expected.as_ref().unwrap().contains("-dirty"));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_dirty_without_suffix() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        std::fs::write(repo_dir.path().join("readme.md"), "modified")?;
+
+        let actual = render_describe(repo_dir.path());
+        let expected = Some(format!("{} ", git_describe(repo_dir.path(), &[])?));
+
+        assert!(!expected.as_ref().unwrap().contains("-dirty"));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_describe_with_match() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["tag", "v1.0.0", "HEAD~1"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(&["tag", "nightly"])
+            .current_dir(&repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    describe_disabled = false
+                    describe_match = "v*"
+                    format = "$describe $commits_since_tag"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "{} 1",
+            git_describe(repo_dir.path(), &["--match", "v*"])?
+        ));
+
+        assert!(expected.as_ref().unwrap().starts_with("v1.0.0-1-g"));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn render_describe(repo_dir: &std::path::Path) -> Option<String> {
+        ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    describe_disabled = false
+                    format = "$describe $commits_since_tag"
+            })
+            .path(repo_dir)
+            .collect()
+    }
+
+    fn git_describe(repo_dir: &std::path::Path, args: &[&str]) -> io::Result<String> {
+        let output = Command::new("git")
+            .args(&["describe", "--tags", "--always"])
+            .args(args)
+            .current_dir(repo_dir)
+            .output()?
+            .stdout;
+        Ok(str::from_utf8(&output).unwrap().trim().to_string())
+    }
+
     #[test]
     fn test_render_commit_hash_only_detached_on_detached_with_tag_enabled() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
@@ -0,0 +1,191 @@
+use git2::Repository;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::git_metrics::GitMetricsConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the number of lines added and deleted in the working tree
+///
+/// Like `git diff --shortstat`, only changes which haven't been staged are counted
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("git_metrics");
+    let config: GitMetricsConfig = GitMetricsConfig::try_load(module.config);
+
+    let repo = context.get_repo().ok()?;
+    // A bare repository has no working tree to compare against
+    if repo.is_bare() {
+        return None;
+    }
+    let git_repo = repo.open().ok()?;
+
+    let stats = match get_diff_stats(&git_repo) {
+        Ok(stats) => stats,
+        Err(error) => {
+            log::debug!("Unable to compute the git diff stats: {}", error);
+            return None;
+        }
+    };
+
+    let format_count = |count: usize| {
+        if config.only_nonzero_diffs && count == 0 {
+            None
+        } else {
+            Some(Ok(count.to_string()))
+        }
+    };
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
+            .map_style(|variable| match variable {
+                "added_style" => Some(Ok(config.added_style)),
+                "deleted_style" => Some(Ok(config.deleted_style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "added" => format_count(stats.insertions),
+                "deleted" => format_count(stats.deletions),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `git_metrics`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+struct DiffStats {
+    insertions: usize,
+    deletions: usize,
+}
+
+fn get_diff_stats(repository: &Repository) -> Result<DiffStats, git2::Error> {
+    let stats = repository.diff_index_to_workdir(None, None)?.stats()?;
+
+    Ok(DiffStats {
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+
+    #[test]
+    fn shows_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        let actual = render_metrics(repo_dir.path());
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_nothing_without_changes() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = render_metrics(repo_dir.path());
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_added_lines() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let readme = repo_dir.path().join("readme.md");
+        let content = fs::read_to_string(&readme)?;
+        fs::write(&readme, format!("{}first\nsecond\n", content))?;
+
+        let actual = render_metrics(repo_dir.path());
+        let expected = Some(format!("{} ", Color::Green.bold().paint("+2")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_deleted_lines() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let readme = repo_dir.path().join("readme.md");
+        let content = fs::read_to_string(&readme)?;
+        let deleted = content.lines().count();
+        fs::write(&readme, "")?;
+
+        let actual = render_metrics(repo_dir.path());
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint(format!("-{}", deleted))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_added_and_deleted_lines() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let readme = repo_dir.path().join("readme.md");
+        let content = fs::read_to_string(&readme)?;
+        let deleted = content.lines().count();
+        fs::write(&readme, "replaced\n")?;
+
+        let actual = render_metrics(repo_dir.path());
+        let expected = Some(format!(
+            "{} {} ",
+            Color::Green.bold().paint("+1"),
+            Color::Red.bold().paint(format!("-{}", deleted))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_zero_counts_when_configured() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_metrics")
+            .config(toml::toml! {
+                [git_metrics]
+                disabled = false
+                only_nonzero_diffs = false
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "{} {} ",
+            Color::Green.bold().paint("+0"),
+            Color::Red.bold().paint("-0")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn render_metrics(path: &Path) -> Option<String> {
+        ModuleRenderer::new("git_metrics")
+            .config(toml::toml! {
+                [git_metrics]
+                disabled = false
+            })
+            .path(path)
+            .collect()
+    }
+}
@@ -19,7 +19,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let state_description = get_state_description(repo_state, repo_root, &config)?;
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "state" => Some(state_description.label),
@@ -210,6 +210,75 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_interactive_rebasing_progress() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        // Put two non-conflicting commits ahead of the conflicting one on master
+        run_git_cmd(&["reset", "--hard", "HEAD~1"], Some(path), true)?;
+        for name in &["first_file", "second_file"] {
+            std::fs::write(path.join(name), name)?;
+            run_git_cmd(&["add", name], Some(path), true)?;
+            run_git_cmd(
+                &["commit", "--message", name, "--no-gpg-sign"],
+                Some(path),
+                true,
+            )?;
+        }
+        run_git_cmd(&["cherry-pick", "master@{3}"], Some(path), true)?;
+
+        run_git_cmd(
+            &["-c", "sequence.editor=true", "rebase", "-i", "other-branch"],
+            Some(path),
+            false,
+        )?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING 3/3")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_progress_of_rebase_stopped_midway() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        // Surround the conflicting commit on master with non-conflicting ones
+        run_git_cmd(&["reset", "--hard", "HEAD~1"], Some(path), true)?;
+        std::fs::write(path.join("first_file"), "first_file")?;
+        run_git_cmd(&["add", "first_file"], Some(path), true)?;
+        run_git_cmd(
+            &["commit", "--message", "first_file", "--no-gpg-sign"],
+            Some(path),
+            true,
+        )?;
+        run_git_cmd(&["cherry-pick", "master@{2}"], Some(path), true)?;
+        std::fs::write(path.join("third_file"), "third_file")?;
+        run_git_cmd(&["add", "third_file"], Some(path), true)?;
+        run_git_cmd(
+            &["commit", "--message", "third_file", "--no-gpg-sign"],
+            Some(path),
+            true,
+        )?;
+
+        run_git_cmd(
+            &["-c", "sequence.editor=true", "rebase", "-i", "other-branch"],
+            Some(path),
+            false,
+        )?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING 2/3")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_merging() -> io::Result<()> {
         let repo_dir = create_repo_with_conflict()?;
@@ -1,4 +1,4 @@
-use git2::{Repository, Status};
+use git2::{Branch, ErrorCode, Repository, Status, SubmoduleIgnore, SubmoduleStatus};
 
 use super::{Context, Module, RootModuleConfig};
 
@@ -6,9 +6,39 @@ use crate::configs::git_status::GitStatusConfig;
 use crate::context::Repo;
 use crate::formatter::StringFormatter;
 use crate::segment::Segment;
-use std::sync::{Arc, RwLock};
-
-const ALL_STATUS_FORMAT: &str = "$conflicted$stashed$deleted$renamed$modified$staged$untracked";
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsStr;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Set for the detached process refreshing a status that exceeded `async_timeout_ms`,
+/// to the lock file it removes when done
+const STATUS_REFRESH_ENV: &str = "STARSHIP_GIT_STATUS_REFRESH";
+
+/// How long the detached refresh waits for the status before giving up. A lock file older
+/// than this was left behind by a refresh that didn't exit cleanly.
+const STATUS_REFRESH_TIME_LIMIT: Duration = Duration::from_secs(60);
+
+/// The variables `$all_status` is made of, in order
+const ALL_STATUS: &[&str] = &[
+    "conflicted",
+    "stashed",
+    "deleted",
+    "worktree_deleted",
+    "renamed",
+    "modified",
+    "typechanged",
+    "staged",
+    "untracked",
+    "ignored",
+];
 
 /// Creates a module with the Git branch in the current directory
 ///
@@ -18,71 +48,216 @@ const ALL_STATUS_FORMAT: &str = "$conflicted$stashed$deleted$renamed$modified$st
 ///   - `⇡` – This branch is ahead of the branch being tracked
 ///   - `⇣` – This branch is behind of the branch being tracked
 ///   - `⇕` – This branch has diverged from the branch being tracked
+///   - `✗` – The branch being tracked no longer exists
 ///   - `?` — There are untracked files in the working directory
 ///   - `$` — A stash exists for the local repository
 ///   - `!` — There are file modifications in the working directory
+///   - `⇢` — A file's type has changed, e.g. it was replaced with a symlink
 ///   - `+` — A new file has been added to the staging area
 ///   - `»` — A renamed file has been added to the staging area
 ///   - `✘` — A file's deletion has been added to the staging area
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let repo = context.get_repo().ok()?;
-    let info = Arc::new(GitStatusInfo::load(repo));
+    // A bare repository has no working directory to report the status of
+    if repo.is_bare() {
+        return None;
+    }
 
     let mut module = context.new_module("git_status");
     let config: GitStatusConfig = GitStatusConfig::try_load(module.config);
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-        formatter
-            .map_meta(|variable, _| match variable {
-                "all_status" => Some(ALL_STATUS_FORMAT),
-                _ => None,
+    if let Some(max_index_size) = config.max_index_size {
+        if is_index_larger_than(repo, max_index_size) {
+            log::debug!(
+                "Skipping git status, the index is larger than {} bytes",
+                max_index_size
+            );
+            return placeholder_module(module, config.large_repo_symbol, config.style);
+        }
+    }
+
+    let untracked_files = config.untracked_files.and_then(|mode| {
+        let parsed = UntrackedFiles::from_config(mode);
+        if parsed.is_none() {
+            log::warn!(
+                "Invalid git_status.untracked_files value `{}`, expected `no`, `normal` or `all`",
+                mode
+            );
+        }
+        parsed
+    });
+    let count_mode = CountMode::from_config(config.count_mode).unwrap_or_else(|| {
+        log::warn!(
+            "Invalid git_status.count_mode value `{}`, expected `files` or `changes`",
+            config.count_mode
+        );
+        CountMode::Changes
+    });
+    let ignore_submodules =
+        IgnoreSubmodules::from_config(config.ignore_submodules).unwrap_or_else(|| {
+            log::warn!(
+                "Invalid git_status.ignore_submodules value `{}`, expected `none`, `untracked`, `dirty` or `all`",
+                config.ignore_submodules
+            );
+            IgnoreSubmodules::None
+        });
+    let options = RepoStatusOptions {
+        update_index: config.update_index,
+        untracked_files,
+        count_mode,
+        ignore_submodules,
+        count_submodules: !config.submodule.is_empty(),
+        count_ignored: !config.ignored.is_empty(),
+    };
+    // The commits ahead and behind can't be counted past the truncated history
+    let show_ahead_behind = config.show_ahead_behind_when_shallow || !repo.is_shallow();
+    // A detached process started to finish a status that took too long waits for it
+    let refresh_lock = context
+        .get_env(STATUS_REFRESH_ENV)
+        .map(|lock_file| RefreshLock(PathBuf::from(lock_file)));
+    let is_refresh = refresh_lock.is_some();
+    let async_timeout = match config.async_timeout_ms {
+        Some(timeout) if !is_refresh => Some(Duration::from_millis(timeout)),
+        _ => None,
+    };
+    let cache_dir = if config.use_cache || config.async_timeout_ms.is_some() {
+        get_cache_dir(context)
+    } else {
+        None
+    };
+    let load_options = StatusLoadOptions {
+        timeout: if is_refresh {
+            STATUS_REFRESH_TIME_LIMIT
+        } else {
+            async_timeout.unwrap_or_else(|| context.command_timeout())
+        },
+        cache_dir: cache_dir.clone(),
+        reuse_cache: config.use_cache && !is_refresh,
+        is_async: async_timeout.is_some(),
+    };
+    let info = Arc::new(GitStatusInfo::load(repo, options, load_options));
+
+    if async_timeout.is_some() && info.is_loading() {
+        // The refresh only helps the next prompt through the cache
+        if let (Some(root), Some(cache_dir), Some(starship)) =
+            (repo.root.as_ref(), cache_dir.as_ref(), refresh_executable())
+        {
+            let config_path = context.get_config_path_os();
+            spawn_status_refresh(&starship, root, cache_dir, config_path.as_deref());
+        }
+        return placeholder_module(module, config.loading_symbol, config.style);
+    }
+
+    let status_segments = |variable: &str| match variable {
+        "stashed" => info
+            .get_stashed()
+            .and_then(|count| format_count(config.stashed, "git_status.stashed", count)),
+        "ahead_behind" if !show_ahead_behind => None,
+        "ahead_behind" => info.get_ahead_behind().and_then(|(ahead, behind)| {
+            if ahead > 0 && behind > 0 {
+                format_text(
+                    config.diverged,
+                    "git_status.diverged",
+                    |variable| match variable {
+                        "ahead_count" => Some(ahead.to_string()),
+                        "behind_count" => Some(behind.to_string()),
+                        _ => None,
+                    },
+                )
+            } else if ahead > 0 && behind == 0 {
+                format_count(config.ahead, "git_status.ahead", ahead)
+            } else if behind > 0 && ahead == 0 {
+                format_count(config.behind, "git_status.behind", behind)
+            } else if !config.up_to_date.is_empty() && info.is_up_to_date() {
+                format_text(config.up_to_date, "git_status.up_to_date", |_| None)
+            } else {
+                None
+            }
+        }),
+        "submodule" => info.get_submodules().and_then(|(modified, untracked)| {
+            if modified == 0 && untracked == 0 {
+                return None;
+            }
+            format_text(
+                config.submodule,
+                "git_status.submodule",
+                |variable| match variable {
+                    "count" => Some(modified.to_string()),
+                    "untracked_count" => Some(untracked.to_string()),
+                    _ => None,
+                },
+            )
+        }),
+        "conflicted" => info
+            .get_conflicted()
+            .and_then(|count| format_count(config.conflicted, "git_status.conflicted", count)),
+        "deleted" => info.get_deleted().and_then(|(staged, worktree)| {
+            // Without a separate format, worktree deletions are shown as `deleted`
+            let count = if config.worktree_deleted.is_some() {
+                staged
+            } else {
+                staged + worktree
+            };
+            format_count(config.deleted, "git_status.deleted", count)
+        }),
+        "worktree_deleted" => config.worktree_deleted.and_then(|format| {
+            info.get_deleted().and_then(|(_, worktree)| {
+                format_count(format, "git_status.worktree_deleted", worktree)
             })
+        }),
+        "renamed" => info
+            .get_renamed()
+            .and_then(|count| format_count(config.renamed, "git_status.renamed", count)),
+        "modified" => info
+            .get_modified()
+            .and_then(|count| format_count(config.modified, "git_status.modified", count)),
+        "typechanged" => info
+            .get_typechanged()
+            .and_then(|count| format_count(config.typechanged, "git_status.typechanged", count)),
+        "staged" => info
+            .get_staged()
+            .and_then(|count| format_count(config.staged, "git_status.staged", count)),
+        "ignored" => info
+            .get_ignored()
+            .and_then(|count| format_count(config.ignored, "git_status.ignored", count)),
+        "untracked" => info
+            .get_untracked()
+            .and_then(|count| format_count(config.untracked, "git_status.untracked", count)),
+        "upstream_gone" => {
+            if info.is_upstream_gone() {
+                format_text(config.upstream_gone, "git_status.upstream_gone", |_| None)
+            } else {
+                None
+            }
+        }
+        "is_shallow" => {
+            if !config.shallow_symbol.is_empty() && repo.is_shallow() {
+                format_text(config.shallow_symbol, "git_status.shallow_symbol", |_| None)
+            } else {
+                None
+            }
+        }
+        "upstream" => info
+            .get_upstream()
+            .map(|upstream| vec![Segment::new(None, upstream)]),
+        _ => None,
+    };
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
             .map_style(|variable: &str| match variable {
                 "style" => Some(Ok(config.style)),
                 _ => None,
             })
             .map_variables_to_segments(|variable: &str| {
-                let info = Arc::clone(&info);
                 let segments = match variable {
-                    "stashed" => info.get_stashed().and_then(|count| {
-                        format_count(config.stashed, "git_status.stashed", count)
-                    }),
-                    "ahead_behind" => info.get_ahead_behind().and_then(|(ahead, behind)| {
-                        if ahead > 0 && behind > 0 {
-                            format_text(config.diverged, "git_status.diverged", |variable| {
-                                match variable {
-                                    "ahead_count" => Some(ahead.to_string()),
-                                    "behind_count" => Some(behind.to_string()),
-                                    _ => None,
-                                }
-                            })
-                        } else if ahead > 0 && behind == 0 {
-                            format_count(config.ahead, "git_status.ahead", ahead)
-                        } else if behind > 0 && ahead == 0 {
-                            format_count(config.behind, "git_status.behind", behind)
-                        } else {
-                            None
-                        }
-                    }),
-                    "conflicted" => info.get_conflicted().and_then(|count| {
-                        format_count(config.conflicted, "git_status.conflicted", count)
-                    }),
-                    "deleted" => info.get_deleted().and_then(|count| {
-                        format_count(config.deleted, "git_status.deleted", count)
-                    }),
-                    "renamed" => info.get_renamed().and_then(|count| {
-                        format_count(config.renamed, "git_status.renamed", count)
-                    }),
-                    "modified" => info.get_modified().and_then(|count| {
-                        format_count(config.modified, "git_status.modified", count)
-                    }),
-                    "staged" => info
-                        .get_staged()
-                        .and_then(|count| format_count(config.staged, "git_status.staged", count)),
-                    "untracked" => info.get_untracked().and_then(|count| {
-                        format_count(config.untracked, "git_status.untracked", count)
-                    }),
-                    _ => None,
+                    "all_status" => join_segments(
+                        ALL_STATUS
+                            .iter()
+                            .filter_map(|status| status_segments(status)),
+                        config.separator,
+                    ),
+                    _ => status_segments(variable),
                 };
                 segments.map(Ok)
             })
@@ -106,45 +281,186 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Checks the size of the index, a cheap estimate of how expensive a status scan will be
+fn is_index_larger_than(repo: &Repo, max_size: u64) -> bool {
+    let index_size = repo
+        .path
+        .as_ref()
+        .and_then(|path| fs::metadata(path.join("index")).ok())
+        .map(|metadata| metadata.len());
+
+    match index_size {
+        Some(size) => size > max_size,
+        None => false,
+    }
+}
+
+/// Renders `symbol` in place of the status, e.g. of a repository that is too large
+fn placeholder_module<'a>(mut module: Module<'a>, symbol: &str, style: &str) -> Option<Module<'a>> {
+    if symbol.is_empty() {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(symbol).and_then(|formatter| {
+        formatter
+            .map_style(|variable: &str| match variable {
+                "style" => Some(Ok(style)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `git_status`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// The starship executable started to refresh the status
+#[cfg(not(test))]
+fn refresh_executable() -> Option<PathBuf> {
+    std::env::current_exe()
+        .map_err(|error| log::debug!("Unable to find the starship executable: {}", error))
+        .ok()
+}
+
+// The test binary can't refresh the status, so tests call `spawn_status_refresh` directly
+#[cfg(test)]
+fn refresh_executable() -> Option<PathBuf> {
+    None
+}
+
+/// Starts a detached `starship module git_status` which waits for the status of the
+/// repository at `root` and caches it for the next prompt, unless one is already running.
+/// The refresh reads the same config file as the prompt that started it.
+fn spawn_status_refresh(
+    starship: &Path,
+    root: &Path,
+    cache_dir: &Path,
+    config_path: Option<&OsStr>,
+) {
+    let lock_file = match acquire_refresh_lock(root, cache_dir, STATUS_REFRESH_TIME_LIMIT) {
+        Some(lock_file) => lock_file,
+        None => {
+            log::trace!("The git status of {:?} is already being refreshed", root);
+            return;
+        }
+    };
+
+    let mut command = process::Command::new(starship);
+    command
+        .arg("module")
+        .arg("git_status")
+        .arg("--path")
+        .arg(root);
+    if let Some(config_path) = config_path {
+        command.arg("--config").arg(config_path);
+    }
+    let result = command
+        .env(STATUS_REFRESH_ENV, &lock_file)
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .spawn();
+    if let Err(error) = result {
+        log::debug!("Unable to start refreshing the git status: {}", error);
+        drop(RefreshLock(lock_file));
+    }
+}
+
+/// Creates the lock file marking that the status of the repository at `root` is being
+/// refreshed. Returns `None` if another refresh holds a lock younger than `time_limit`.
+fn acquire_refresh_lock(root: &Path, cache_dir: &Path, time_limit: Duration) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    let lock_file = cache_dir.join(format!("git_status_{:016x}.lock", hasher.finish()));
+
+    let is_stale = fs::metadata(&lock_file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age >= time_limit);
+    if is_stale {
+        let _ = fs::remove_file(&lock_file);
+    }
+
+    fs::create_dir_all(cache_dir).ok()?;
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lock_file)
+        .ok()?;
+    Some(lock_file)
+}
+
+/// Removes the refresh lock file when dropped
+struct RefreshLock(PathBuf);
+
+impl Drop for RefreshLock {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.0) {
+            log::debug!("Unable to remove {:?}: {}", self.0, error);
+        }
+    }
+}
+
+/// Controls how long to wait for the repo status and how it is cached between prompts
+struct StatusLoadOptions {
+    /// How long to wait for the status
+    timeout: Duration,
+    /// Where to cache the repo status between prompts, if enabled
+    cache_dir: Option<PathBuf>,
+    /// Reuse a status cached for the same index and HEAD instead of computing it
+    reuse_cache: bool,
+    /// Fall back to the cached status, or else show that it's loading, on timeout
+    is_async: bool,
+}
+
 struct GitStatusInfo<'a> {
     repo: &'a Repo,
-    ahead_behind: RwLock<Option<Result<(usize, usize), git2::Error>>>,
+    options: RepoStatusOptions,
+    load_options: StatusLoadOptions,
+    upstream_status: RwLock<Option<Result<UpstreamStatus, git2::Error>>>,
     repo_status: RwLock<Option<Result<RepoStatus, git2::Error>>>,
     stashed_count: RwLock<Option<Result<usize, git2::Error>>>,
+    /// Whether the status is still being computed after an async timeout
+    loading: AtomicBool,
 }
 
 impl<'a> GitStatusInfo<'a> {
-    pub fn load(repo: &'a Repo) -> Self {
+    pub fn load(
+        repo: &'a Repo,
+        options: RepoStatusOptions,
+        load_options: StatusLoadOptions,
+    ) -> Self {
         Self {
             repo,
-            ahead_behind: RwLock::new(None),
+            options,
+            load_options,
+            upstream_status: RwLock::new(None),
             repo_status: RwLock::new(None),
             stashed_count: RwLock::new(None),
+            loading: AtomicBool::new(false),
         }
     }
 
-    fn get_branch_name(&self) -> String {
-        self.repo
-            .branch
-            .clone()
-            .unwrap_or_else(|| String::from("master"))
-    }
-
     fn get_repository(&self) -> Option<Repository> {
-        // bare repos don't have a branch name, so `repo.branch.as_ref` would return None,
-        // but git treats "master" as the default branch name
-        let repo_root = self.repo.root.as_ref()?;
-        Repository::open(repo_root).ok()
+        self.repo.open().ok()
     }
 
-    pub fn get_ahead_behind(&self) -> Option<(usize, usize)> {
+    pub fn get_upstream_status(&self) -> Option<UpstreamStatus> {
         {
-            let data = self.ahead_behind.read().unwrap();
+            let data = self.upstream_status.read().unwrap();
             if let Some(result) = data.as_ref() {
                 return match result.as_ref() {
-                    Ok(ahead_behind) => Some(*ahead_behind),
+                    Ok(upstream_status) => Some(upstream_status.clone()),
                     Err(error) => {
-                        log::debug!("get_ahead_behind: {}", error);
+                        log::debug!("get_upstream_status: {}", error);
                         None
                     }
                 };
@@ -152,20 +468,35 @@ impl<'a> GitStatusInfo<'a> {
         }
 
         {
-            let mut data = self.ahead_behind.write().unwrap();
+            let mut data = self.upstream_status.write().unwrap();
             let repo = self.get_repository()?;
-            let branch_name = self.get_branch_name();
-            *data = Some(get_ahead_behind(&repo, &branch_name));
+            *data = Some(get_upstream_status(&repo));
             match data.as_ref().unwrap() {
-                Ok(ahead_behind) => Some(*ahead_behind),
+                Ok(upstream_status) => Some(upstream_status.clone()),
                 Err(error) => {
-                    log::debug!("get_ahead_behind: {}", error);
+                    log::debug!("get_upstream_status: {}", error);
                     None
                 }
             }
         }
     }
 
+    pub fn get_ahead_behind(&self) -> Option<(usize, usize)> {
+        self.get_upstream_status()
+            .map(|data| (data.ahead, data.behind))
+    }
+
+    pub fn get_upstream(&self) -> Option<String> {
+        self.get_upstream_status().and_then(|data| data.name)
+    }
+
+    pub fn is_upstream_gone(&self) -> bool {
+        match self.get_upstream_status() {
+            Some(data) => data.gone,
+            None => false,
+        }
+    }
+
     pub fn get_repo_status(&self) -> Option<RepoStatus> {
         {
             let data = self.repo_status.read().unwrap();
@@ -182,8 +513,7 @@ impl<'a> GitStatusInfo<'a> {
 
         {
             let mut data = self.repo_status.write().unwrap();
-            let mut repo = self.get_repository()?;
-            *data = Some(get_repo_status(&mut repo));
+            *data = Some(self.load_repo_status());
             match data.as_ref().unwrap() {
                 Ok(repo_status) => Some(*repo_status),
                 Err(error) => {
@@ -222,12 +552,62 @@ impl<'a> GitStatusInfo<'a> {
         }
     }
 
+    /// Whether the status took longer than the async timeout and no cached status
+    /// was available to show instead
+    pub fn is_loading(&self) -> bool {
+        self.get_repo_status();
+        self.loading.load(Ordering::Relaxed)
+    }
+
+    /// Computes the repo status, going through the on-disk cache if it's enabled
+    fn load_repo_status(&self) -> Result<RepoStatus, git2::Error> {
+        let cache = self.load_options.cache_dir.as_ref().and_then(|cache_dir| {
+            let key = StatusCacheKey::new(self.repo, self.options)?;
+            Some((cache_dir.join(key.file_name()), key))
+        });
+
+        if let (true, Some((cache_file, key))) = (self.load_options.reuse_cache, cache.as_ref()) {
+            if let Some(repo_status) = read_cached_status(cache_file, key) {
+                log::trace!("Using the cached git status from {:?}", cache_file);
+                return Ok(repo_status);
+            }
+        }
+
+        let receiver = spawn_repo_status(self.repo, self.options, cache.clone());
+        if let Ok(result) = receiver.recv_timeout(self.load_options.timeout) {
+            return result;
+        }
+
+        if self.load_options.is_async {
+            if let Some((cache_file, key)) = cache.as_ref() {
+                if let Some(repo_status) = read_cached_status(cache_file, key) {
+                    log::debug!("Computing the git status is slow, using the last cached one");
+                    return Ok(repo_status);
+                }
+            }
+            self.loading.store(true, Ordering::Relaxed);
+        } else {
+            log::warn!(
+                "Computing the git status timed out after {:?}. You can set command_timeout in your config to a higher value.",
+                self.load_options.timeout
+            );
+        }
+        Err(git2::Error::from_str("Timed out computing the repo status"))
+    }
+
+    pub fn get_submodules(&self) -> Option<(usize, usize)> {
+        self.get_repo_status()
+            .map(|data| (data.submodules_modified, data.submodules_untracked))
+    }
+
     pub fn get_conflicted(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.conflicted)
     }
 
-    pub fn get_deleted(&self) -> Option<usize> {
-        self.get_repo_status().map(|data| data.deleted)
+    /// Returns the number of staged deletions and of files deleted only in the working tree
+    pub fn get_deleted(&self) -> Option<(usize, usize)> {
+        self.get_repo_status()
+            .map(|data| (data.deleted, data.deleted_worktree))
     }
 
     pub fn get_renamed(&self) -> Option<usize> {
@@ -238,6 +618,10 @@ impl<'a> GitStatusInfo<'a> {
         self.get_repo_status().map(|data| data.modified)
     }
 
+    pub fn get_typechanged(&self) -> Option<usize> {
+        self.get_repo_status().map(|data| data.typechanged)
+    }
+
     pub fn get_staged(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.staged)
     }
@@ -245,23 +629,92 @@ impl<'a> GitStatusInfo<'a> {
     pub fn get_untracked(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.untracked)
     }
+
+    pub fn get_ignored(&self) -> Option<usize> {
+        self.get_repo_status().map(|data| data.ignored)
+    }
+
+    /// Whether the branch has an upstream and there are no local changes.
+    /// Being even with the upstream is checked by the caller.
+    pub fn is_up_to_date(&self) -> bool {
+        // Ignored files don't make the working tree dirty
+        let has_changes = match self.get_repo_status() {
+            Some(status) => {
+                RepoStatus {
+                    ignored: 0,
+                    ..status
+                } != RepoStatus::default()
+            }
+            None => true,
+        };
+        self.get_upstream().is_some() && !has_changes
+    }
+}
+
+/// Runs `get_repo_status` on a separate thread, caching the result if `cache` is set.
+/// git2 can't cancel a status scan, so one that the caller stops waiting for is left
+/// running in the background.
+fn spawn_repo_status(
+    repo: &Repo,
+    options: RepoStatusOptions,
+    cache: Option<(PathBuf, StatusCacheKey)>,
+) -> mpsc::Receiver<Result<RepoStatus, git2::Error>> {
+    let repo = repo.clone();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = repo
+            .open()
+            .and_then(|mut repository| get_repo_status(&mut repository, options));
+
+        if let (Some((cache_file, key)), Ok(repo_status)) = (cache, result.as_ref()) {
+            let entry = StatusCacheEntry {
+                key,
+                status: *repo_status,
+            };
+            if let Err(error) = write_cached_status(&cache_file, &entry) {
+                log::debug!("Unable to cache the git status: {}", error);
+            }
+        }
+
+        let _ = sender.send(result);
+    });
+
+    receiver
 }
 
 /// Gets the number of files in various git states (staged, modified, deleted, etc...)
-fn get_repo_status(repository: &mut Repository) -> Result<RepoStatus, git2::Error> {
+fn get_repo_status(
+    repository: &mut Repository,
+    options: RepoStatusOptions,
+) -> Result<RepoStatus, git2::Error> {
     log::debug!("New repo status created");
     let mut status_options = git2::StatusOptions::new();
 
     let mut repo_status = RepoStatus::default();
 
-    match repository.config()?.get_entry("status.showUntrackedFiles") {
-        Ok(entry) => status_options.include_untracked(entry.value() != Some("no")),
-        _ => status_options.include_untracked(true),
+    let untracked_files = match options.untracked_files {
+        Some(untracked_files) => untracked_files,
+        None => match repository.config()?.get_entry("status.showUntrackedFiles") {
+            Ok(entry) => entry
+                .value()
+                .and_then(UntrackedFiles::from_config)
+                .unwrap_or(UntrackedFiles::Normal),
+            _ => UntrackedFiles::Normal,
+        },
     };
+    status_options
+        .include_untracked(untracked_files != UntrackedFiles::No)
+        .recurse_untracked_dirs(untracked_files == UntrackedFiles::All)
+        // git2 can't pass a submodule ignore mode to the status scan, so submodules are only
+        // scanned with it when nothing is ignored and are inspected below otherwise
+        .exclude_submodules(options.ignore_submodules != IgnoreSubmodules::None)
+        .include_ignored(options.count_ignored);
+    // Like `git --no-optional-locks status`, the index is only written back when asked to
     status_options
         .renames_from_rewrites(true)
         .renames_head_to_index(true)
-        .include_unmodified(true);
+        .include_unmodified(true)
+        .update_index(options.update_index);
 
     let statuses = repository.statuses(Some(&mut status_options))?;
 
@@ -272,12 +725,49 @@ fn get_repo_status(repository: &mut Repository) -> Result<RepoStatus, git2::Erro
     statuses
         .iter()
         .map(|s| s.status())
-        .for_each(|status| repo_status.add(status));
+        .for_each(|status| match options.count_mode {
+            CountMode::Files => repo_status.add_file(status),
+            CountMode::Changes => repo_status.add(status),
+        });
+
+    let count_changes = match options.ignore_submodules {
+        IgnoreSubmodules::Untracked | IgnoreSubmodules::Dirty => true,
+        IgnoreSubmodules::None | IgnoreSubmodules::All => false,
+    };
+    let count_submodules =
+        options.count_submodules && options.ignore_submodules != IgnoreSubmodules::All;
+    if count_changes || count_submodules {
+        for submodule in repository.submodules()? {
+            let name = match submodule.name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let status = repository.submodule_status(name, options.ignore_submodules.to_git2())?;
+            let change = submodule_change(status);
+            if count_changes && !change.is_empty() {
+                match options.count_mode {
+                    CountMode::Files => repo_status.add_file(change),
+                    CountMode::Changes => repo_status.add(change),
+                }
+            }
+            if count_submodules {
+                repo_status.add_submodule(status);
+            }
+        }
+    }
 
     Ok(repo_status)
 }
 
+/// Counts the stash entries, which are stored as the reflog of `refs/stash`
+///
+/// Falls back to walking the stash if the reflog can't be read
 fn get_stashed_count(repository: &mut Repository) -> Result<usize, git2::Error> {
+    match repository.reflog("refs/stash") {
+        Ok(reflog) => return Ok(reflog.len()),
+        Err(error) => log::debug!("Unable to read the stash reflog: {}", error),
+    }
+
     let mut count = 0;
     repository.stash_foreach(|_, _, _| {
         count += 1;
@@ -288,28 +778,240 @@ fn get_stashed_count(repository: &mut Repository) -> Result<usize, git2::Error>
 
 /// Compares the current branch with the branch it is tracking to determine how
 /// far ahead or behind it is in relation
-fn get_ahead_behind(
-    repository: &Repository,
-    branch_name: &str,
-) -> Result<(usize, usize), git2::Error> {
-    let branch_object = repository.revparse_single(branch_name)?;
-    let tracking_branch_name = format!("{}@{{upstream}}", branch_name);
-    let tracking_object = repository.revparse_single(&tracking_branch_name)?;
+///
+/// A detached HEAD or a branch without an upstream is reported with no upstream
+/// name and zero commits ahead or behind. A branch whose configured upstream no
+/// longer exists is additionally marked as gone.
+fn get_upstream_status(repository: &Repository) -> Result<UpstreamStatus, git2::Error> {
+    let head = repository.head()?;
+    if !head.is_branch() {
+        return Ok(UpstreamStatus::default());
+    }
+
+    let is_configured = match head.name() {
+        Some(name) => repository.branch_upstream_name(name).is_ok(),
+        None => false,
+    };
+    let branch = Branch::wrap(head);
+    let upstream = match branch.upstream() {
+        Ok(upstream) => upstream,
+        Err(error) if error.code() == ErrorCode::NotFound => {
+            return Ok(UpstreamStatus {
+                gone: is_configured,
+                ..UpstreamStatus::default()
+            })
+        }
+        Err(error) => return Err(error),
+    };
+
+    let branch_oid = branch.get().peel_to_commit()?.id();
+    let upstream_oid = upstream.get().peel_to_commit()?.id();
+    let (ahead, behind) = repository.graph_ahead_behind(branch_oid, upstream_oid)?;
+
+    Ok(UpstreamStatus {
+        name: upstream.name()?.map(String::from),
+        ahead,
+        behind,
+        gone: false,
+    })
+}
+
+#[derive(Default, Debug, Clone)]
+struct UpstreamStatus {
+    name: Option<String>,
+    ahead: usize,
+    behind: usize,
+    /// The branch has an upstream configured, but it no longer exists
+    gone: bool,
+}
+
+/// The directory the repo status is cached in, `$STARSHIP_CACHE` or `~/.cache/starship`
+fn get_cache_dir(context: &Context) -> Option<PathBuf> {
+    context
+        .get_env_os("STARSHIP_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".cache/starship")))
+}
+
+/// Identifies the state of a repository a cached status was computed for.
+/// Changes to the working tree which aren't reflected in the index or HEAD
+/// don't change the key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct StatusCacheKey {
+    repo: PathBuf,
+    index_modified: Option<SystemTime>,
+    head: Option<String>,
+    options: String,
+}
+
+impl StatusCacheKey {
+    fn new(repo: &Repo, options: RepoStatusOptions) -> Option<Self> {
+        let git_dir = repo.path.as_ref()?;
+        let index_modified = fs::metadata(git_dir.join("index"))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let head = repo
+            .open()
+            .ok()?
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string());
+
+        Some(Self {
+            repo: repo.root.clone().unwrap_or_else(|| git_dir.clone()),
+            index_modified,
+            head,
+            options: format!("{:?}", options),
+        })
+    }
+
+    /// Each repository gets its own cache file
+    fn file_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.repo.hash(&mut hasher);
+        format!("git_status_{:016x}.json", hasher.finish())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatusCacheEntry {
+    key: StatusCacheKey,
+    status: RepoStatus,
+}
+
+fn read_cached_status(cache_file: &Path, key: &StatusCacheKey) -> Option<RepoStatus> {
+    let content = fs::read_to_string(cache_file).ok()?;
+    let entry: StatusCacheEntry = serde_json::from_str(&content).ok()?;
+    if &entry.key == key {
+        Some(entry.status)
+    } else {
+        None
+    }
+}
+
+/// Writes to a temporary file first, so concurrent prompts never read a partial entry
+fn write_cached_status(cache_file: &Path, entry: &StatusCacheEntry) -> std::io::Result<()> {
+    if let Some(cache_dir) = cache_file.parent() {
+        fs::create_dir_all(cache_dir)?;
+    }
+
+    let temp_file = cache_file.with_extension(format!("{}.tmp", process::id()));
+    fs::write(&temp_file, serde_json::to_string(entry)?)?;
+    fs::rename(&temp_file, cache_file)
+}
+
+/// Options affecting how the repo status is computed
+#[derive(Debug, Copy, Clone)]
+struct RepoStatusOptions {
+    update_index: bool,
+    /// Overrides git's `status.showUntrackedFiles` setting
+    untracked_files: Option<UntrackedFiles>,
+    count_mode: CountMode,
+    ignore_submodules: IgnoreSubmodules,
+    /// Inspect each submodule for new commits and changes, which is slower
+    count_submodules: bool,
+    /// Report ignored files, which is slow in repositories with large build directories
+    count_ignored: bool,
+}
+
+/// Mirrors the modes of `git status --untracked-files`
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum UntrackedFiles {
+    No,
+    Normal,
+    All,
+}
+
+impl UntrackedFiles {
+    fn from_config(mode: &str) -> Option<Self> {
+        match mode {
+            "no" | "false" => Some(UntrackedFiles::No),
+            "normal" | "true" => Some(UntrackedFiles::Normal),
+            "all" => Some(UntrackedFiles::All),
+            _ => None,
+        }
+    }
+}
+
+/// Mirrors the modes of `git status --ignore-submodules`
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum IgnoreSubmodules {
+    None,
+    Untracked,
+    Dirty,
+    All,
+}
+
+impl IgnoreSubmodules {
+    fn from_config(mode: &str) -> Option<Self> {
+        match mode {
+            "none" => Some(IgnoreSubmodules::None),
+            "untracked" => Some(IgnoreSubmodules::Untracked),
+            "dirty" => Some(IgnoreSubmodules::Dirty),
+            "all" => Some(IgnoreSubmodules::All),
+            _ => None,
+        }
+    }
+
+    fn to_git2(self) -> SubmoduleIgnore {
+        match self {
+            IgnoreSubmodules::None => SubmoduleIgnore::None,
+            IgnoreSubmodules::Untracked => SubmoduleIgnore::Untracked,
+            IgnoreSubmodules::Dirty => SubmoduleIgnore::Dirty,
+            IgnoreSubmodules::All => SubmoduleIgnore::All,
+        }
+    }
+}
+
+/// The change `git status` reports for a submodule's path in the superproject
+fn submodule_change(s: SubmoduleStatus) -> Status {
+    let worktree_changes = SubmoduleStatus::WD_MODIFIED
+        | SubmoduleStatus::WD_INDEX_MODIFIED
+        | SubmoduleStatus::WD_WD_MODIFIED
+        | SubmoduleStatus::WD_UNTRACKED;
+
+    let mut change = Status::empty();
+    change.set(Status::INDEX_NEW, s.is_index_added());
+    change.set(Status::INDEX_DELETED, s.is_index_deleted());
+    change.set(Status::INDEX_MODIFIED, s.is_index_modified());
+    change.set(Status::WT_DELETED, s.is_wd_deleted());
+    change.set(Status::WT_MODIFIED, s.intersects(worktree_changes));
+    change
+}
 
-    let branch_oid = branch_object.id();
-    let tracking_oid = tracking_object.id();
+/// How paths with changes both in the index and in the working tree are counted
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CountMode {
+    /// Each path is counted once, in its most significant state
+    Files,
+    /// Each path is counted once per state it is in
+    Changes,
+}
 
-    repository.graph_ahead_behind(branch_oid, tracking_oid)
+impl CountMode {
+    fn from_config(mode: &str) -> Option<Self> {
+        match mode {
+            "files" => Some(CountMode::Files),
+            "changes" => Some(CountMode::Changes),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 struct RepoStatus {
     conflicted: usize,
     deleted: usize,
+    deleted_worktree: usize,
     renamed: usize,
     modified: usize,
+    typechanged: usize,
     staged: usize,
     untracked: usize,
+    ignored: usize,
+    submodules_modified: usize,
+    submodules_untracked: usize,
 }
 
 impl RepoStatus {
@@ -318,7 +1020,11 @@ impl RepoStatus {
     }
 
     fn is_deleted(status: Status) -> bool {
-        status.is_wt_deleted() || status.is_index_deleted()
+        status.is_index_deleted()
+    }
+
+    fn is_deleted_worktree(status: Status) -> bool {
+        status.is_wt_deleted()
     }
 
     fn is_renamed(status: Status) -> bool {
@@ -329,6 +1035,10 @@ impl RepoStatus {
         status.is_wt_modified()
     }
 
+    fn is_typechanged(status: Status) -> bool {
+        status.is_wt_typechange() || status.is_index_typechange()
+    }
+
     fn is_staged(status: Status) -> bool {
         status.is_index_modified() || status.is_index_new()
     }
@@ -337,13 +1047,58 @@ impl RepoStatus {
         status.is_wt_new()
     }
 
+    fn is_ignored(status: Status) -> bool {
+        status.is_ignored()
+    }
+
+    fn add_submodule(&mut self, s: SubmoduleStatus) {
+        let modified = SubmoduleStatus::INDEX_MODIFIED
+            | SubmoduleStatus::WD_MODIFIED
+            | SubmoduleStatus::WD_INDEX_MODIFIED
+            | SubmoduleStatus::WD_WD_MODIFIED;
+
+        self.submodules_modified += s.intersects(modified) as usize;
+        self.submodules_untracked += s.is_wd_untracked() as usize;
+    }
+
     fn add(&mut self, s: Status) {
-        self.conflicted += RepoStatus::is_conflicted(s) as usize;
+        // Unmerged entries are only reported as conflicts, whatever else git2
+        // reports about their index and working tree state
+        if RepoStatus::is_conflicted(s) {
+            self.conflicted += 1;
+            return;
+        }
+
         self.deleted += RepoStatus::is_deleted(s) as usize;
+        self.deleted_worktree += RepoStatus::is_deleted_worktree(s) as usize;
         self.renamed += RepoStatus::is_renamed(s) as usize;
         self.modified += RepoStatus::is_modified(s) as usize;
+        self.typechanged += RepoStatus::is_typechanged(s) as usize;
         self.staged += RepoStatus::is_staged(s) as usize;
         self.untracked += RepoStatus::is_untracked(s) as usize;
+        self.ignored += RepoStatus::is_ignored(s) as usize;
+    }
+
+    /// Counts a path only once, preferring conflicts over staged changes,
+    /// staged changes over worktree changes and those over untracked files
+    fn add_file(&mut self, s: Status) {
+        let index_changes = Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE;
+        let worktree_changes =
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE;
+
+        if s.is_conflicted() {
+            self.add(Status::CONFLICTED);
+        } else if s.intersects(index_changes) {
+            self.add(s & index_changes);
+        } else if s.intersects(worktree_changes) {
+            self.add(s & worktree_changes);
+        } else {
+            self.add(s);
+        }
     }
 }
 
@@ -351,14 +1106,35 @@ fn format_text<F>(format_str: &str, config_path: &str, mapper: F) -> Option<Vec<
 where
     F: Fn(&str) -> Option<String> + Send + Sync,
 {
-    if let Ok(formatter) = StringFormatter::new(format_str) {
-        formatter
+    match StringFormatter::new(format_str) {
+        Ok(formatter) => formatter
             .map(|variable| mapper(variable).map(Ok))
             .parse(None)
-            .ok()
-    } else {
-        log::warn!("Error parsing format string `{}`", &config_path);
+            .ok(),
+        Err(error) => {
+            log::warn!("Error parsing format string `{}`:\n{}", &config_path, error);
+            None
+        }
+    }
+}
+
+/// Concatenates the segments of each status, with `separator` between them
+fn join_segments<I>(statuses: I, separator: &str) -> Option<Vec<Segment>>
+where
+    I: Iterator<Item = Vec<Segment>>,
+{
+    let mut joined: Vec<Segment> = Vec::new();
+    for segments in statuses.filter(|segments| !segments.is_empty()) {
+        if !joined.is_empty() && !separator.is_empty() {
+            joined.push(Segment::new(None, separator));
+        }
+        joined.extend(segments);
+    }
+
+    if joined.is_empty() {
         None
+    } else {
+        Some(joined)
     }
 }
 
@@ -380,8 +1156,15 @@ mod tests {
     use std::io;
     use std::path::Path;
     use std::process::Command;
+    use std::time::Duration;
 
+    use super::{
+        acquire_refresh_lock, spawn_status_refresh, CountMode, GitStatusInfo, IgnoreSubmodules,
+        RefreshLock, RepoStatus, RepoStatusOptions, StatusLoadOptions, STATUS_REFRESH_TIME_LIMIT,
+    };
+    use crate::context::Context;
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use git2::Status;
 
     /// Right after the calls to git the filesystem state may not have finished
     /// updating yet causing some of the tests to fail. These barriers are placed
@@ -402,6 +1185,204 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn repo_status_counts_each_status() {
+        let cases = [
+            (Status::CURRENT, RepoStatus::default()),
+            (
+                Status::IGNORED,
+                RepoStatus {
+                    ignored: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_NEW,
+                RepoStatus {
+                    staged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_MODIFIED,
+                RepoStatus {
+                    staged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_DELETED,
+                RepoStatus {
+                    deleted: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_RENAMED,
+                RepoStatus {
+                    renamed: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_NEW,
+                RepoStatus {
+                    untracked: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_MODIFIED,
+                RepoStatus {
+                    modified: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_DELETED,
+                RepoStatus {
+                    deleted_worktree: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_TYPECHANGE,
+                RepoStatus {
+                    typechanged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_TYPECHANGE,
+                RepoStatus {
+                    typechanged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_MODIFIED | Status::WT_MODIFIED,
+                RepoStatus {
+                    staged: 1,
+                    modified: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::CONFLICTED,
+                RepoStatus {
+                    conflicted: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::CONFLICTED | Status::INDEX_NEW | Status::WT_MODIFIED,
+                RepoStatus {
+                    conflicted: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+        ];
+
+        for (status, expected) in cases.iter() {
+            let mut actual = RepoStatus::default();
+            actual.add(*status);
+            assert_eq!(*expected, actual, "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn repo_status_counts_each_file_once() {
+        let cases = [
+            // MM
+            (
+                Status::INDEX_MODIFIED | Status::WT_MODIFIED,
+                RepoStatus {
+                    staged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            // AM
+            (
+                Status::INDEX_NEW | Status::WT_MODIFIED,
+                RepoStatus {
+                    staged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            // RM
+            (
+                Status::INDEX_RENAMED | Status::WT_MODIFIED,
+                RepoStatus {
+                    renamed: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            // AD
+            (
+                Status::INDEX_NEW | Status::WT_DELETED,
+                RepoStatus {
+                    staged: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_MODIFIED,
+                RepoStatus {
+                    modified: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::WT_NEW,
+                RepoStatus {
+                    untracked: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::CONFLICTED | Status::INDEX_NEW | Status::WT_MODIFIED,
+                RepoStatus {
+                    conflicted: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+        ];
+
+        for (status, expected) in cases.iter() {
+            let mut actual = RepoStatus::default();
+            actual.add_file(*status);
+            assert_eq!(*expected, actual, "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn repo_status_counts_each_change() {
+        let cases = [
+            (
+                Status::INDEX_NEW | Status::WT_MODIFIED,
+                RepoStatus {
+                    staged: 1,
+                    modified: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+            (
+                Status::INDEX_RENAMED | Status::WT_MODIFIED,
+                RepoStatus {
+                    renamed: 1,
+                    modified: 1,
+                    ..RepoStatus::default()
+                },
+            ),
+        ];
+
+        for (status, expected) in cases.iter() {
+            let mut actual = RepoStatus::default();
+            actual.add(*status);
+            assert_eq!(*expected, actual, "{:?}", status);
+        }
+    }
+
     #[test]
     fn show_nothing_on_empty_dir() -> io::Result<()> {
         let repo_dir = tempfile::tempdir()?;
@@ -422,367 +1403,1781 @@ mod tests {
         behind(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
-            .path(repo_dir.path())
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇣");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_behind_with_packed_refs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        behind(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["pack-refs", "--all", "--prune"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇣");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_ahead_behind_in_shallow_clone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        behind(&repo_dir.path())?;
+        make_shallow(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_ahead_behind_in_shallow_clone_when_enabled() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        behind(&repo_dir.path())?;
+        make_shallow(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                show_ahead_behind_when_shallow = true
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇣");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_shallow_symbol() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        behind(&repo_dir.path())?;
+        make_shallow(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                shallow_symbol = "≋"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("≋");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_shallow_symbol_in_full_clone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                shallow_symbol = "≋"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_behind_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        behind(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                behind = "⇣$count"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇣1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_ahead() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        File::create(repo_dir.path().join("readme.md"))?.sync_all()?;
+        ahead(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_ahead_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        File::create(repo_dir.path().join("readme.md"))?.sync_all()?;
+        ahead(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ahead="⇡$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇡1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_diverged() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        diverge(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇕");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_diverged_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        diverge(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                diverged=r"⇕⇡$ahead_count⇣$behind_count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇕⇡1⇣1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_diverged_with_distinct_counts() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        diverge(&repo_dir.path())?;
+        fs::write(repo_dir.path().join("Cargo.toml"), "[package]")?;
+        Command::new("git")
+            .args(&["commit", "-am", "Update Cargo.toml again", "--no-gpg-sign"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$ahead_behind"
+                diverged = r"⇡$ahead_count⇣$behind_count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(String::from("⇡2⇣1"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_up_to_date() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                up_to_date = "✓"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("✓");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_ignored_files() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_ignored(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ignored = "◌$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("?◌1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_ignored_files_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_ignored(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("?");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_up_to_date_with_ignored_files() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        fs::write(repo_dir.path().join(".git/info/exclude"), "build.log\n")?;
+        File::create(repo_dir.path().join("build.log"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                up_to_date = "✓"
+                ignored = "◌"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("◌✓");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_up_to_date_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_up_to_date_with_changes() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                up_to_date = "✓"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_ahead_behind_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", "no-upstream"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        ahead(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_upstream_gone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["update-ref", "-d", "refs/remotes/origin/master"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("✗");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_upstream_gone_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["branch", "--unset-upstream"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$upstream"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(String::from("origin/master"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_upstream_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "-b", "no-upstream"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$upstream"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_ahead_behind_on_detached_head() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        Command::new("git")
+            .args(&["checkout", "HEAD^"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_conflicted() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_conflict(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("=");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_conflicted_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_conflict(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                conflicted = "=$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("=1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_file() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_untracked(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("?");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_file_with_extended_colors() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_untracked(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                style = "bold fg:#ff8800 bg:17"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::RGB(0xFF, 0x88, 0x00)
+                .bold()
+                .on(Color::Fixed(17))
+                .paint("[?]")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_file_with_invalid_hex_color() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_untracked(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                style = "bold #ff88zz"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(String::from("[?] "));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_file_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_untracked(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("?1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_untracked_file_if_disabled() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_untracked(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&["config", "status.showUntrackedFiles", "no"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_write_index_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let index_path = repo_dir.path().join(".git").join("index");
+
+        touch_readme(&repo_dir.path())?;
+        let index_before = fs::read(&index_path)?;
+
+        ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+
+        assert_eq!(index_before, fs::read(&index_path)?);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn writes_index_with_update_index() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let index_path = repo_dir.path().join(".git").join("index");
+
+        touch_readme(&repo_dir.path())?;
+        let index_before = fs::read(&index_path)?;
+
+        ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                update_index = true
+            })
+            .path(&repo_dir.path())
+            .collect();
+
+        assert_ne!(index_before, fs::read(&index_path)?);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_directory_once_by_default() -> io::Result<()> {
+        test_untracked_files_mode(None, format_output("?1"))
+    }
+
+    #[test]
+    fn doesnt_show_untracked_files_with_mode_no() -> io::Result<()> {
+        test_untracked_files_mode(Some("no"), None)
+    }
+
+    #[test]
+    fn shows_untracked_directory_once_with_mode_normal() -> io::Result<()> {
+        test_untracked_files_mode(Some("normal"), format_output("?1"))
+    }
+
+    #[test]
+    fn shows_every_untracked_file_with_mode_all() -> io::Result<()> {
+        test_untracked_files_mode(Some("all"), format_output("?3"))
+    }
+
+    fn test_untracked_files_mode(mode: Option<&str>, expected: Option<String>) -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let untracked_dir = repo_dir.path().join("build");
+        fs::create_dir(&untracked_dir)?;
+        for file in &["a", "b", "c"] {
+            File::create(untracked_dir.join(file))?.sync_all()?;
+        }
+
+        let mut config = toml::toml! {
+            [git_status]
+            untracked = "?$count"
+        };
+        if let Some(mode) = mode {
+            config["git_status"]
+                .as_table_mut()
+                .unwrap()
+                .insert(String::from("untracked_files"), toml::Value::from(mode));
+        }
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(config)
+            .path(&repo_dir.path())
+            .collect();
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified_submodule() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_dirty_submodule_when_ignoring_dirty() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ignore_submodules = "dirty"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_submodule_with_new_commits_when_ignoring_dirty() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&[
+                "-c",
+                "user.name=starship",
+                "-c",
+                "user.email=starship@example.com",
+            ])
+            .args(&["commit", "-am", "Advance submodule", "--no-gpg-sign"])
+            .current_dir(repo_dir.path().join("submodule"))
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ignore_submodules = "dirty"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_submodule_with_new_commits_when_ignoring_all() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&[
+                "-c",
+                "user.name=starship",
+                "-c",
+                "user.email=starship@example.com",
+            ])
+            .args(&["commit", "-am", "Advance submodule", "--no-gpg-sign"])
+            .current_dir(repo_dir.path().join("submodule"))
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ignore_submodules = "all"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_status_with_large_index() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                max_index_size = 1
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_large_repo_symbol_with_large_index() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                max_index_size = 1
+                large_repo_symbol = "[\\[…\\]]($style) "
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("…");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_status_with_small_index() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                max_index_size = 1_000_000
+                large_repo_symbol = "…"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn writes_cache_when_enabled() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        create_untracked(&repo_dir.path())?;
+
+        let actual = render_with_cache(repo_dir.path(), cache_dir.path());
+        let expected = Some(String::from("?1"));
+
+        assert_eq!(expected, actual);
+        assert_eq!(fs::read_dir(cache_dir.path())?.count(), 1);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_write_cache_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        create_untracked(&repo_dir.path())?;
+
+        ModuleRenderer::new("git_status")
+            .env("STARSHIP_CACHE", cache_dir.path().to_string_lossy())
+            .path(&repo_dir.path())
+            .collect();
+
+        assert_eq!(fs::read_dir(cache_dir.path())?.count(), 0);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn uses_cached_status_on_cache_hit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        create_untracked(&repo_dir.path())?;
+        render_with_cache(repo_dir.path(), cache_dir.path());
+        set_cached_untracked_count(cache_dir.path(), 5)?;
+
+        // The cached count is shown, so the status wasn't computed again
+        let actual = render_with_cache(repo_dir.path(), cache_dir.path());
+        let expected = Some(String::from("?5"));
+
+        assert_eq!(expected, actual);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn ignores_cached_status_after_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        create_untracked(&repo_dir.path())?;
+        render_with_cache(repo_dir.path(), cache_dir.path());
+        set_cached_untracked_count(cache_dir.path(), 5)?;
+
+        Command::new("git")
+            .args(&[
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Move HEAD",
+                "--no-gpg-sign",
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = render_with_cache(repo_dir.path(), cache_dir.path());
+        let expected = Some(String::from("?1"));
+
+        assert_eq!(expected, actual);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    fn render_with_cache(repo_dir: &Path, cache_dir: &Path) -> Option<String> {
+        ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$untracked"
+                untracked = "?$count"
+                use_cache = true
+            })
+            .env("STARSHIP_CACHE", cache_dir.to_string_lossy())
+            .path(repo_dir)
+            .collect()
+    }
+
+    #[test]
+    fn is_loading_when_status_is_slow() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+        create_untracked(&repo_dir.path())?;
+
+        let context = Context::new_with_dir(Default::default(), repo_dir.path());
+        let repo = context.get_repo()?;
+        let info = GitStatusInfo::load(repo, status_options(), async_options(cache_dir.path()));
+
+        assert!(info.is_loading());
+        assert_eq!(info.get_untracked(), None);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn uses_cached_status_when_status_is_slow() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+        create_untracked(&repo_dir.path())?;
+
+        let context = Context::new_with_dir(Default::default(), repo_dir.path());
+        let repo = context.get_repo()?;
+        // The status keeps being computed after the first prompt gives up on it
+        let info = GitStatusInfo::load(repo, status_options(), async_options(cache_dir.path()));
+        assert!(info.is_loading());
+        wait_for_cache_entry(cache_dir.path())?;
+
+        let info = GitStatusInfo::load(repo, status_options(), async_options(cache_dir.path()));
+
+        assert!(!info.is_loading());
+        assert_eq!(info.get_untracked(), Some(1));
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn only_one_refresh_holds_the_lock() -> io::Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let root = Path::new("/path/to/repo");
+        let time_limit = Duration::from_secs(60);
+
+        let lock_file = acquire_refresh_lock(root, cache_dir.path(), time_limit).unwrap();
+        assert!(lock_file.exists());
+        assert_eq!(
+            acquire_refresh_lock(root, cache_dir.path(), time_limit),
+            None
+        );
+        // Other repositories are refreshed independently
+        let other_lock = acquire_refresh_lock(Path::new("/other"), cache_dir.path(), time_limit);
+        assert!(other_lock.is_some());
+
+        // The refresh removes its lock when it's done
+        drop(RefreshLock(lock_file.clone()));
+        assert!(!lock_file.exists());
+        assert_eq!(
+            acquire_refresh_lock(root, cache_dir.path(), time_limit),
+            Some(lock_file)
+        );
+        cache_dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn refresh_runs_starship_with_the_prompt_config() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let cache_dir = tempfile::tempdir()?;
+        let bin_dir = tempfile::tempdir()?;
+        let root = Path::new("/path/to/repo");
+        // Records the arguments and the lock file it was started with
+        let output = bin_dir.path().join("output");
+        let starship = bin_dir.path().join("starship");
+        fs::write(
+            &starship,
+            format!(
+                "#!/bin/sh\nprintf '%s\\n' \"$@\" \"$STARSHIP_GIT_STATUS_REFRESH\" > {0}.tmp\nmv {0}.tmp {0}\n",
+                output.display()
+            ),
+        )?;
+        fs::set_permissions(&starship, fs::Permissions::from_mode(0o755))?;
+
+        let config_path = Path::new("/path/to/starship.toml");
+        spawn_status_refresh(
+            &starship,
+            root,
+            cache_dir.path(),
+            Some(config_path.as_ref()),
+        );
+        for _ in 0..500 {
+            if output.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let output = fs::read_to_string(&output)?;
+        let mut lines = output.lines();
+        let args: Vec<_> = lines.by_ref().take(6).collect();
+        let expected = vec![
+            "module",
+            "git_status",
+            "--path",
+            "/path/to/repo",
+            "--config",
+            "/path/to/starship.toml",
+        ];
+        assert_eq!(expected, args);
+        // The refresh holds the lock until it exits
+        let lock_file = Path::new(lines.next().unwrap());
+        assert!(lock_file.starts_with(cache_dir.path()));
+        assert!(lock_file.exists());
+        cache_dir.close()?;
+        bin_dir.close()
+    }
+
+    #[test]
+    fn refresh_respects_the_lock() -> io::Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let root = Path::new("/path/to/repo");
+
+        // Another refresh is running, so none is started
+        let lock_file = acquire_refresh_lock(root, cache_dir.path(), STATUS_REFRESH_TIME_LIMIT);
+        spawn_status_refresh(Path::new("/nonexistent"), root, cache_dir.path(), None);
+        assert!(lock_file.as_ref().unwrap().exists());
+        drop(RefreshLock(lock_file.unwrap()));
+
+        // A refresh that fails to start releases its lock
+        spawn_status_refresh(Path::new("/nonexistent"), root, cache_dir.path(), None);
+        assert!(acquire_refresh_lock(root, cache_dir.path(), STATUS_REFRESH_TIME_LIMIT).is_some());
+        cache_dir.close()
+    }
+
+    #[test]
+    fn stale_refresh_lock_is_replaced() -> io::Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let root = Path::new("/path/to/repo");
+
+        let lock_file = acquire_refresh_lock(root, cache_dir.path(), Duration::from_secs(0));
+        assert!(lock_file.is_some());
+        let lock_file = acquire_refresh_lock(root, cache_dir.path(), Duration::from_secs(0));
+        assert!(lock_file.is_some());
+        cache_dir.close()
+    }
+
+    #[test]
+    fn shows_status_computed_within_async_timeout() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let cache_dir = tempfile::tempdir()?;
+        create_untracked(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                async_timeout_ms = 10000
+            })
+            .env("STARSHIP_CACHE", cache_dir.path().to_string_lossy())
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("?");
+
+        assert_eq!(expected, actual);
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    fn status_options() -> RepoStatusOptions {
+        RepoStatusOptions {
+            update_index: false,
+            untracked_files: None,
+            count_mode: CountMode::Changes,
+            ignore_submodules: IgnoreSubmodules::None,
+            count_submodules: false,
+            count_ignored: false,
+        }
+    }
+
+    /// Gives up on the status right away, like a prompt in a very large repository
+    fn async_options(cache_dir: &Path) -> StatusLoadOptions {
+        StatusLoadOptions {
+            timeout: Duration::from_secs(0),
+            cache_dir: Some(cache_dir.to_path_buf()),
+            reuse_cache: false,
+            is_async: true,
+        }
+    }
+
+    fn wait_for_cache_entry(cache_dir: &Path) -> io::Result<()> {
+        for _ in 0..500 {
+            for entry in fs::read_dir(cache_dir)? {
+                if entry?.path().extension() == Some("json".as_ref()) {
+                    return Ok(());
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "The git status was never cached",
+        ))
+    }
+
+    /// Rewrites the only cache entry in `cache_dir` to claim a different untracked count
+    fn set_cached_untracked_count(cache_dir: &Path, count: usize) -> io::Result<()> {
+        let cache_file = fs::read_dir(cache_dir)?.next().unwrap()?.path();
+        let mut entry: serde_json::Value = serde_json::from_str(&fs::read_to_string(&cache_file)?)?;
+        entry["status"]["untracked"] = count.into();
+        fs::write(&cache_file, entry.to_string())
+    }
+
+    #[test]
+    fn shows_submodule_with_new_commits() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&[
+                "-c",
+                "user.name=starship",
+                "-c",
+                "user.email=starship@example.com",
+            ])
+            .args(&["commit", "-am", "Advance submodule", "--no-gpg-sign"])
+            .current_dir(repo_dir.path().join("submodule"))
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$submodule"
+                submodule = "S$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(String::from("S1"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_submodule_with_untracked_files() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+        create_untracked(&repo_dir.path().join("submodule"))?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$submodule"
+                submodule = "S$count?$untracked_count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = Some(String::from("S1?1"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn doesnt_show_submodule_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_dirty_submodule(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$submodule"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_stashed() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        barrier();
+
+        create_stash(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&["reset", "--hard", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("$");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_stashed_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        barrier();
+
+        create_stash(&repo_dir.path())?;
+        barrier();
+
+        Command::new("git")
+            .args(&["reset", "--hard", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                stashed = r"\$$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("$1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_multiple_stashes_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        barrier();
+
+        create_stash(&repo_dir.path())?;
+
+        fs::write(repo_dir.path().join("readme.md"), "# second stash")?;
+        Command::new("git")
+            .args(&["stash"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                stashed = r"\$$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("$2");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified_with_count() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                modified = "!$count"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("!1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified_from_nested_subdirectory() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let nested_dir = repo_dir.path().join("sub").join("dir");
+        fs::create_dir_all(&nested_dir)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(&nested_dir)
             .collect();
-        let expected = format_output("⇣");
+        let expected = format_output("!");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_behind_with_count() -> io::Result<()> {
+    fn shows_modified_in_worktree() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree_path = worktree_dir.path().join("worktree");
 
-        behind(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["worktree", "add", "--detach"])
+            .arg(&worktree_path)
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        create_modified(&worktree_path)?;
 
         let actual = ModuleRenderer::new("git_status")
-            .config(toml::toml! {
-                [git_status]
-                behind = "⇣$count"
-            })
-            .path(repo_dir.path())
+            .path(&worktree_path)
             .collect();
-        let expected = format_output("⇣1");
+        let expected = format_output("!");
 
         assert_eq!(expected, actual);
+        worktree_dir.close()?;
         repo_dir.close()
     }
 
     #[test]
-    fn shows_ahead() -> io::Result<()> {
+    fn shows_stashed_in_worktree() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree_path = worktree_dir.path().join("worktree");
 
-        File::create(repo_dir.path().join("readme.md"))?.sync_all()?;
-        ahead(&repo_dir.path())?;
+        create_stash(&repo_dir.path())?;
+
+        Command::new("git")
+            .args(&["worktree", "add", "--detach"])
+            .arg(&worktree_path)
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
 
         let actual = ModuleRenderer::new("git_status")
-            .path(&repo_dir.path())
+            .path(&worktree_path)
             .collect();
-        let expected = format_output("⇡");
+        let expected = format_output("$");
 
         assert_eq!(expected, actual);
+        worktree_dir.close()?;
         repo_dir.close()
     }
 
     #[test]
-    fn shows_ahead_with_count() -> io::Result<()> {
+    fn shows_modified_with_git_dir_and_work_tree_env_variables() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let bare_dir = tempfile::tempdir()?;
+        let work_tree = tempfile::tempdir()?;
 
-        File::create(repo_dir.path().join("readme.md"))?.sync_all()?;
-        ahead(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["clone", "--bare"])
+            .arg(repo_dir.path())
+            .arg(bare_dir.path())
+            .output()?;
+        barrier();
+
+        Command::new("git")
+            .arg("--git-dir")
+            .arg(bare_dir.path())
+            .arg("--work-tree")
+            .arg(work_tree.path())
+            .args(&["reset", "--hard", "HEAD"])
+            .output()?;
+        barrier();
+
+        create_modified(&work_tree.path())?;
 
         let actual = ModuleRenderer::new("git_status")
-            .config(toml::toml! {
-                [git_status]
-                ahead="⇡$count"
-            })
-            .path(&repo_dir.path())
+            .env("GIT_DIR", bare_dir.path().to_string_lossy())
+            .env("GIT_WORK_TREE", work_tree.path().to_string_lossy())
+            .path(work_tree.path())
             .collect();
-        let expected = format_output("⇡1");
+        let expected = format_output("!");
 
         assert_eq!(expected, actual);
+        work_tree.close()?;
+        bare_dir.close()?;
         repo_dir.close()
     }
 
     #[test]
-    fn shows_diverged() -> io::Result<()> {
+    fn doesnt_show_in_bare_repo() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let bare_dir = tempfile::tempdir()?;
 
-        diverge(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["clone", "--bare"])
+            .arg(repo_dir.path())
+            .arg(bare_dir.path())
+            .output()?;
+        barrier();
 
         let actual = ModuleRenderer::new("git_status")
-            .path(&repo_dir.path())
+            .path(bare_dir.path())
             .collect();
-        let expected = format_output("⇕");
+        let expected = None;
 
         assert_eq!(expected, actual);
+        bare_dir.close()?;
         repo_dir.close()
     }
 
     #[test]
-    fn shows_diverged_with_count() -> io::Result<()> {
+    #[cfg(unix)]
+    fn shows_modified_in_non_utf8_path() -> io::Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let parent_dir = tempfile::tempdir()?;
+        let clone_dir = parent_dir.path().join(OsStr::from_bytes(b"non_utf8_\xff"));
 
-        diverge(&repo_dir.path())?;
+        Command::new("git")
+            .arg("clone")
+            .arg(repo_dir.path())
+            .arg(&clone_dir)
+            .output()?;
+        barrier();
 
-        let actual = ModuleRenderer::new("git_status")
-            .config(toml::toml! {
-                [git_status]
-                diverged=r"⇕⇡$ahead_count⇣$behind_count"
-            })
-            .path(&repo_dir.path())
-            .collect();
-        let expected = format_output("⇕⇡1⇣1");
+        create_modified(&clone_dir)?;
+
+        let actual = ModuleRenderer::new("git_status").path(&clone_dir).collect();
+        let expected = format_output("!");
 
         assert_eq!(expected, actual);
+        parent_dir.close()?;
         repo_dir.close()
     }
 
     #[test]
-    fn shows_conflicted() -> io::Result<()> {
+    #[cfg(unix)]
+    fn shows_typechanged() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_conflict(&repo_dir.path())?;
+        create_typechanged(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("=");
+        let expected = format_output("⇢");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_conflicted_with_count() -> io::Result<()> {
+    #[cfg(unix)]
+    fn shows_typechanged_with_count() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_conflict(&repo_dir.path())?;
+        create_typechanged(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                conflicted = "=$count"
+                typechanged = "⇢$count"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("=1");
+        let expected = format_output("⇢1");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_untracked_file() -> io::Result<()> {
+    fn shows_staged_file() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_untracked(&repo_dir.path())?;
+        create_staged(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("?");
+        let expected = format_output("+");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_untracked_file_with_count() -> io::Result<()> {
+    fn shows_staged_file_with_count() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_untracked(&repo_dir.path())?;
+        create_staged(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                untracked = "?$count"
+                staged = "+[$count](green)"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("?1");
+        let expected = Some(format!(
+            "{} ",
+            ANSIStrings(&[
+                Color::Red.bold().paint("[+"),
+                Color::Green.bold().paint("1"),
+                Color::Red.bold().paint("]"),
+            ])
+        ));
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn doesnt_show_untracked_file_if_disabled() -> io::Result<()> {
+    fn shows_staged_and_modified_file() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_untracked(&repo_dir.path())?;
-
+        fs::write(repo_dir.path().join("readme.md"), "# staged")?;
         Command::new("git")
-            .args(&["config", "status.showUntrackedFiles", "no"])
+            .args(&["add", "readme.md"])
             .current_dir(repo_dir.path())
             .output()?;
         barrier();
 
+        fs::write(repo_dir.path().join("readme.md"), "# staged and modified")?;
+
         let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
-        let expected = None;
+        let expected = format_output("!+");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_stashed() -> io::Result<()> {
+    fn shows_statuses_without_separator_by_default() -> io::Result<()> {
+        test_separator(None, format_output("!1+1?1"))
+    }
+
+    #[test]
+    fn shows_statuses_with_separator() -> io::Result<()> {
+        test_separator(Some(" "), format_output("!1 +1 ?1"))
+    }
+
+    #[test]
+    fn shows_single_status_without_separator() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
-        barrier();
+        create_untracked(&repo_dir.path())?;
 
-        create_stash(&repo_dir.path())?;
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                separator = "|"
+            })
+            .path(&repo_dir.path())
+            .collect();
+        let expected = format_output("?");
 
-        Command::new("git")
-            .args(&["reset", "--hard", "HEAD"])
-            .current_dir(repo_dir.path())
-            .output()?;
-        barrier();
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn test_separator(separator: Option<&str>, expected: Option<String>) -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        create_staged(&repo_dir.path())?;
+        create_modified(&repo_dir.path())?;
+        File::create(repo_dir.path().join("notes"))?.sync_all()?;
+
+        let mut config = toml::toml! {
+            [git_status]
+            modified = "!$count"
+            staged = "+$count"
+            untracked = "?$count"
+        };
+        if let Some(separator) = separator {
+            config["git_status"]
+                .as_table_mut()
+                .unwrap()
+                .insert(String::from("separator"), toml::Value::from(separator));
+        }
 
         let actual = ModuleRenderer::new("git_status")
+            .config(config)
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("$");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_stashed_with_count() -> io::Result<()> {
+    fn shows_staged_and_modified_file_once_in_files_mode() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
-        barrier();
-
-        create_stash(&repo_dir.path())?;
-        barrier();
 
+        fs::write(repo_dir.path().join("readme.md"), "# staged")?;
         Command::new("git")
-            .args(&["reset", "--hard", "HEAD"])
+            .args(&["add", "readme.md"])
             .current_dir(repo_dir.path())
             .output()?;
         barrier();
 
+        fs::write(repo_dir.path().join("readme.md"), "# staged and modified")?;
+
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                stashed = r"\$$count"
+                count_mode = "files"
+                modified = "!$count"
+                staged = "+$count"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("$1");
+        let expected = format_output("+1");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_modified() -> io::Result<()> {
+    fn shows_renamed_file() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_modified(&repo_dir.path())?;
+        create_renamed(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("!");
+        let expected = format_output("»");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_modified_with_count() -> io::Result<()> {
+    fn shows_renamed_file_with_count() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_modified(&repo_dir.path())?;
+        create_renamed(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                modified = "!$count"
+                renamed = "»$count"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("!1");
+        let expected = format_output("»1");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_staged_file() -> io::Result<()> {
+    fn shows_renamed_file_with_spaces() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_staged(&repo_dir.path())?;
+        fs::write(repo_dir.path().join("a b.txt"), "renamed")?;
+        Command::new("git")
+            .args(&["add", "a b.txt"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(&["commit", "-m", "Add file with spaces", "--no-gpg-sign"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(&["mv", "a b.txt", "c d.txt"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
 
         let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$all_status"
+                renamed = "»$count"
+            })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("+");
+        let expected = Some(String::from("»1"));
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_staged_file_with_count() -> io::Result<()> {
+    fn shows_deleted_file() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_staged(&repo_dir.path())?;
+        create_deleted(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
-            .config(toml::toml! {
-                [git_status]
-                staged = "+[$count](green)"
-            })
             .path(&repo_dir.path())
             .collect();
-        let expected = Some(format!(
-            "{} ",
-            ANSIStrings(&[
-                Color::Red.bold().paint("[+"),
-                Color::Green.paint("1"),
-                Color::Red.bold().paint("]"),
-            ])
-        ));
+        let expected = format_output("✘");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_renamed_file() -> io::Result<()> {
+    fn shows_deleted_file_with_count() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_renamed(&repo_dir.path())?;
+        create_deleted(&repo_dir.path())?;
 
         let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                deleted = "✘$count"
+            })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("»");
+        let expected = format_output("✘1");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_renamed_file_with_count() -> io::Result<()> {
+    fn shows_staged_and_worktree_deletions_separately() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
-        create_renamed(&repo_dir.path())?;
+        create_deleted(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["rm", "--quiet", "Cargo.toml"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
 
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                renamed = "»$count"
+                deleted = "D$count"
+                worktree_deleted = "W$count"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("»1");
+        let expected = format_output("D1W1");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_deleted_file() -> io::Result<()> {
+    fn shows_staged_and_worktree_deletions_together_by_default() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
         create_deleted(&repo_dir.path())?;
+        Command::new("git")
+            .args(&["rm", "--quiet", "Cargo.toml"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
 
         let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                deleted = "D$count"
+            })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("✘");
+        let expected = format_output("D2");
 
         assert_eq!(expected, actual);
         repo_dir.close()
     }
 
     #[test]
-    fn shows_deleted_file_with_count() -> io::Result<()> {
+    fn doesnt_show_worktree_deletion_as_staged() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::GIT)?;
 
         create_deleted(&repo_dir.path())?;
@@ -790,11 +3185,12 @@ mod tests {
         let actual = ModuleRenderer::new("git_status")
             .config(toml::toml! {
                 [git_status]
-                deleted = "✘$count"
+                deleted = "D"
+                worktree_deleted = "W"
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("✘1");
+        let expected = format_output("W");
 
         assert_eq!(expected, actual);
         repo_dir.close()
@@ -861,6 +3257,18 @@ mod tests {
         Ok(())
     }
 
+    /// Marks the root commit as the shallow boundary, like `git clone --depth`
+    fn make_shallow(repo_dir: &Path) -> io::Result<()> {
+        let root_commit = Command::new("git")
+            .args(&["rev-list", "--max-parents=0", "HEAD"])
+            .current_dir(repo_dir)
+            .output()?
+            .stdout;
+        fs::write(repo_dir.join(".git").join("shallow"), root_commit)?;
+
+        Ok(())
+    }
+
     fn diverge(repo_dir: &Path) -> io::Result<()> {
         Command::new("git")
             .args(&["reset", "--hard", "HEAD^"])
@@ -922,12 +3330,59 @@ mod tests {
         Ok(())
     }
 
+    /// Rewrites the readme with its current contents so that only its stat data changes
+    fn touch_readme(repo_dir: &Path) -> io::Result<()> {
+        let readme = repo_dir.join("readme.md");
+        let contents = fs::read(&readme)?;
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        fs::write(&readme, contents)?;
+        barrier();
+
+        Ok(())
+    }
+
+    /// Adds a copy of the fixture as a committed submodule and modifies a file inside it
+    fn create_dirty_submodule(repo_dir: &Path) -> io::Result<()> {
+        Command::new("git")
+            .args(&["-c", "protocol.file.allow=always", "submodule", "add"])
+            .arg(repo_dir)
+            .arg("submodule")
+            .current_dir(repo_dir)
+            .output()?;
+        barrier();
+
+        Command::new("git")
+            .args(&["commit", "-m", "Add submodule", "--no-gpg-sign"])
+            .current_dir(repo_dir)
+            .output()?;
+        barrier();
+
+        create_modified(&repo_dir.join("submodule"))
+    }
+
+    /// Replaces a tracked file with a symlink
+    #[cfg(unix)]
+    fn create_typechanged(repo_dir: &Path) -> io::Result<()> {
+        fs::remove_file(repo_dir.join("readme.md"))?;
+        std::os::unix::fs::symlink("Cargo.toml", repo_dir.join("readme.md"))?;
+
+        Ok(())
+    }
+
     fn create_untracked(repo_dir: &Path) -> io::Result<()> {
         File::create(repo_dir.join("license"))?.sync_all()?;
 
         Ok(())
     }
 
+    /// Ignores a new file with a `.gitignore`, which is itself untracked
+    fn create_ignored(repo_dir: &Path) -> io::Result<()> {
+        fs::write(repo_dir.join(".gitignore"), "build.log\n")?;
+        File::create(repo_dir.join("build.log"))?.sync_all()?;
+
+        Ok(())
+    }
+
     fn create_modified(repo_dir: &Path) -> io::Result<()> {
         File::create(repo_dir.join("readme.md"))?.sync_all()?;
 
@@ -1,55 +1,81 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::go::GoConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
+use std::cmp::Ordering;
+use std::path::Path;
+
 /// Creates a module with the current Go version
 ///
 /// Will display the Go version if any of the following criteria are met:
 ///     - Current directory contains a `go.mod` file
 ///     - Current directory contains a `go.sum` file
+///     - Current directory contains a `go.work` file
 ///     - Current directory contains a `glide.yaml` file
 ///     - Current directory contains a `Gopkg.yml` file
 ///     - Current directory contains a `Gopkg.lock` file
 ///     - Current directory contains a `.go-version` file
 ///     - Current directory contains a `Godeps` directory
 ///     - Current directory contains a file with the `.go` extension
+///
+/// The version is styled with `not_capable_style` when it is older than the `go` directive in
+/// `go.mod`.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("golang");
+    let config = GoConfig::try_load(module.config);
+
     let is_go_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "go.mod",
-            "go.sum",
-            "glide.yaml",
-            "Gopkg.yml",
-            "Gopkg.lock",
-            ".go-version",
-        ])
-        .set_extensions(&["go"])
-        .set_folders(&["Godeps"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_go_project {
         return None;
     }
 
-    let mut module = context.new_module("golang");
-    let config = GoConfig::try_load(module.config);
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let go_version = context
+        .exec_cmd("go", &["version"])
+        .and_then(|output| utils::parse_version(&output.stdout).map(str::to_owned));
+    let mod_version = get_go_mod_version(&context.current_dir);
+    let is_capable = match (&go_version, &mod_version) {
+        (Some(go_version), Some(mod_version)) => {
+            compare_go_versions(go_version, mod_version) != Ordering::Less
+        }
+        _ => true,
+    };
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if is_capable {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => {
-                    format_go_version(&utils::exec_cmd("go", &["version"])?.stdout.as_str()).map(Ok)
-                }
+                "version" => go_version
+                    .as_deref()
+                    .map(|version| {
+                        VersionFormatter::format_module_version(
+                            module.get_name(),
+                            version,
+                            config.version_format,
+                        )
+                    })
+                    .map(Ok),
+                "mod_version" => mod_version.clone().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -66,27 +92,52 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_go_version(go_stdout: &str) -> Option<String> {
-    // go version output looks like this:
-    // go version go1.13.3 linux/amd64
-
-    let version = go_stdout
-        // split into ["", "1.12.4 linux/amd64"]
-        .splitn(2, "go version go")
-        // return "1.12.4 linux/amd64"
-        .nth(1)?
-        // split into ["1.12.4", "linux/amd64"]
-        .split_whitespace()
-        // return "1.12.4"
-        .next()?;
-
-    Some(format!("v{}", version))
+fn get_go_mod_version(base_dir: &Path) -> Option<String> {
+    let go_mod = utils::read_file(base_dir.join("go.mod")).ok()?;
+    parse_go_mod_version(&go_mod)
+}
+
+/// Reads the minimum Go version from the `go` directive of a `go.mod` file, e.g. `go 1.21`
+fn parse_go_mod_version(go_mod: &str) -> Option<String> {
+    go_mod.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("go"), Some(version)) => Some(version.to_owned()),
+            _ => None,
+        }
+    })
+}
+
+/// Compares Go versions like `1.21`, `1.21.3` or `1.21rc1` by their numeric components, missing
+/// components count as zero.
+fn compare_go_versions(lhs: &str, rhs: &str) -> Ordering {
+    fn components(version: &str) -> Vec<u64> {
+        version
+            .split('.')
+            .map(|component| {
+                let digits = component
+                    .chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    }
+
+    let (lhs, rhs) = (components(lhs), components(rhs));
+    let len = lhs.len().max(rhs.len());
+    let component = |version: &[u64], i| version.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| component(&lhs, i).cmp(&component(&rhs, i)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use ansi_term::Color;
     use std::fs::{self, File};
     use std::io;
@@ -196,8 +247,119 @@ mod tests {
     }
 
     #[test]
-    fn test_format_go_version() {
+    fn folder_with_go_file_and_version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.go"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                [golang]
+                version_format = "v${major}.${minor}"
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("🐹 v1.12")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_go_version() {
         let input = "go version go1.12 darwin/amd64";
-        assert_eq!(format_go_version(input), Some("v1.12".to_string()));
+        assert_eq!(utils::parse_version(input), Some("1.12"));
+    }
+
+    #[test]
+    fn folder_with_go_work() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("go.work"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("golang").path(dir.path()).collect();
+
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("🐹 v1.12.1")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_go_mod_version() {
+        let go_mod = "\
+module github.com/starship/example
+
+// go 1.10 in a comment is ignored
+go 1.21
+
+require (
+\tgolang.org/x/text v0.3.7
+)
+";
+        assert_eq!(parse_go_mod_version(go_mod), Some("1.21".to_string()));
+        assert_eq!(
+            parse_go_mod_version("module example\n\ngo 1.19.2 // toolchain\n"),
+            Some("1.19.2".to_string())
+        );
+        assert_eq!(parse_go_mod_version("module example\n"), None);
+    }
+
+    #[test]
+    fn test_compare_go_versions() {
+        assert_eq!(compare_go_versions("1.12.1", "1.12"), Ordering::Greater);
+        assert_eq!(compare_go_versions("1.12", "1.12.0"), Ordering::Equal);
+        assert_eq!(compare_go_versions("1.9", "1.12"), Ordering::Less);
+        assert_eq!(compare_go_versions("1.21rc1", "1.21"), Ordering::Equal);
+        assert_eq!(compare_go_versions("2.0", "1.21.3"), Ordering::Greater);
+    }
+
+    #[test]
+    fn go_mod_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/starship/example\n\ngo 1.12\n",
+        )?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                [golang]
+                format = "via [$symbol$version \\(go $mod_version\\)]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Cyan.bold().paint("🐹 v1.12.1 (go 1.12)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn go_mod_version_not_capable() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/starship/example\n\ngo 1.21\n",
+        )?;
+
+        let actual = ModuleRenderer::new("golang").path(dir.path()).collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🐹 v1.12.1")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .cmd(
+                "go version",
+                Some(CommandOutput {
+                    stdout: String::from("go version go1.21.3 linux/amd64\n"),
+                    stderr: String::default(),
+                    status: Some(0),
+                }),
+            )
+            .collect();
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("🐹 v1.21.3")));
+        assert_eq!(expected, actual);
+        dir.close()
     }
 }
@@ -1,19 +1,20 @@
+use std::path::Path;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::hg_branch::HgBranchConfig;
+use crate::context::Vcs;
 use crate::formatter::StringFormatter;
 
 /// Creates a module with the Hg bookmark or branch in the current directory
 ///
 /// Will display the bookmark or branch name if the current directory is an hg repo
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_hg_repo = context.try_begin_scan()?.set_folders(&[".hg"]).is_match();
-
-    if !is_hg_repo {
-        return None;
-    }
+    let repo_root = match context.get_vcs_root() {
+        Some(vcs_root) if vcs_root.is(Vcs::Mercurial) => &vcs_root.path,
+        _ => return None,
+    };
 
     let mut module = context.new_module("hg_branch");
     let config: HgBranchConfig = HgBranchConfig::try_load(module.config);
@@ -35,7 +36,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let branch_name =
-        get_hg_current_bookmark(context).unwrap_or_else(|| get_hg_branch_name(context));
+        get_hg_current_bookmark(repo_root).unwrap_or_else(|| get_hg_branch_name(repo_root));
 
     let truncated_graphemes = get_graphemes(&branch_name, len);
     // The truncation symbol should only be added if we truncated
@@ -46,7 +47,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         truncated_graphemes
     };
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "symbol" => Some(config.symbol),
@@ -74,14 +75,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_hg_branch_name(ctx: &Context) -> String {
-    std::fs::read_to_string(ctx.current_dir.join(".hg").join("branch"))
+fn get_hg_branch_name(repo_root: &Path) -> String {
+    std::fs::read_to_string(repo_root.join(".hg").join("branch"))
         .map(|s| s.trim().into())
         .unwrap_or_else(|_| "default".to_string())
 }
 
-fn get_hg_current_bookmark(ctx: &Context) -> Option<String> {
-    std::fs::read_to_string(ctx.current_dir.join(".hg").join("bookmarks.current"))
+fn get_hg_current_bookmark(repo_root: &Path) -> Option<String> {
+    std::fs::read_to_string(repo_root.join(".hg").join("bookmarks.current"))
         .map(|s| s.trim().into())
         .ok()
 }
@@ -129,6 +130,70 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn test_branch_in_subdirectory() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+        fs::write(repo_dir.path().join(".hg").join("branch"), "stable\n")?;
+        let sub_dir = repo_dir.path().join("src").join("module");
+        fs::create_dir_all(&sub_dir)?;
+
+        expect_hg_branch_with_config(
+            &sub_dir,
+            None,
+            &[Expect::BranchName(&"stable"), Expect::NoTruncation],
+        )?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_hg_repo_inside_git_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let hg_dir = repo_dir.path().join("vendor");
+        fs::create_dir_all(hg_dir.join(".hg"))?;
+        fs::write(hg_dir.join(".hg").join("branch"), "stable\n")?;
+
+        expect_hg_branch_with_config(
+            &hg_dir,
+            None,
+            &[Expect::BranchName(&"stable"), Expect::NoTruncation],
+        )?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_default_branch_without_branch_file() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+
+        expect_hg_branch_with_config(
+            repo_dir.path(),
+            None,
+            &[Expect::BranchName(&"default"), Expect::NoTruncation],
+        )?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_bookmark_in_subdirectory() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+        fs::write(repo_dir.path().join(".hg").join("branch"), "stable\n")?;
+        fs::write(
+            repo_dir.path().join(".hg").join("bookmarks.current"),
+            "feature",
+        )?;
+        let sub_dir = repo_dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        expect_hg_branch_with_config(
+            &sub_dir,
+            None,
+            &[Expect::BranchName(&"feature"), Expect::NoTruncation],
+        )?;
+        repo_dir.close()
+    }
+
     #[test]
     #[ignore]
     fn test_hg_disabled_per_default() -> io::Result<()> {
@@ -1,5 +1,4 @@
 use super::{Context, Module};
-use std::ffi::OsString;
 
 use crate::config::RootModuleConfig;
 use crate::configs::hostname::HostnameConfig;
@@ -14,35 +13,24 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("hostname");
     let config: HostnameConfig = HostnameConfig::try_load(module.config);
 
-    let ssh_connection = context.get_env("SSH_CONNECTION");
-    if config.ssh_only && ssh_connection.is_none() {
+    let ssh_session = context.is_ssh_session();
+    if config.ssh_only && !ssh_session {
         return None;
     }
 
-    let os_hostname: OsString = gethostname::gethostname();
+    let host = context.hostname.as_deref()?;
 
-    let host = match os_hostname.into_string() {
-        Ok(host) => host,
-        Err(bad) => {
-            log::warn!("hostname is not valid UTF!\n{:?}", bad);
-            return None;
-        }
+    let host = match config.trim_at {
+        "" => host,
+        trim_at => host.split(trim_at).next().unwrap_or(host),
     };
 
-    //rustc doesn't let you do an "if" and an "if let" in the same if statement
-    // if this changes in the future this can become a lot cleaner
-    let host = if !config.trim_at.is_empty() {
-        if let Some(index) = host.find(config.trim_at) {
-            host.split_at(index).0
-        } else {
-            host.as_ref()
-        }
-    } else {
-        host.as_ref()
-    };
-
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
+            .map_meta(|variable, _| match variable {
+                "ssh_symbol" if ssh_session => Some(config.ssh_symbol),
+                _ => None,
+            })
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
                 _ => None,
@@ -71,31 +59,16 @@ mod tests {
     use ansi_term::{Color, Style};
     use std::io;
 
-    macro_rules! get_hostname {
-        () => {
-            if let Some(hostname) = gethostname::gethostname().into_string().ok() {
-                hostname
-            } else {
-                println!(
-                    "hostname was not tested because gethostname failed! \
-                     This could be caused by your hostname containing invalid UTF."
-                );
-                return Ok(());
-            }
-        };
-    }
-
     #[test]
     fn ssh_only_false() -> io::Result<()> {
-        let hostname = get_hostname!();
         let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship")
             .config(toml::toml! {
                 [hostname]
                 ssh_only = false
-                trim_at = ""
             })
             .collect();
-        let expected = Some(format!("{} in ", style().paint(hostname)));
+        let expected = Some(format!("{} in ", style().paint("spaceship")));
 
         assert_eq!(expected, actual);
         Ok(())
@@ -104,9 +77,63 @@ mod tests {
     #[test]
     fn no_ssh() -> io::Result<()> {
         let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship")
+            .env("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")
+            .collect();
+        let expected = Some(format!("{} in ", style().paint("🌐 spaceship")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_tty() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship")
+            .env("SSH_TTY", "/dev/pts/0")
             .config(toml::toml! {
                 [hostname]
-                ssh_only = true
+                ssh_symbol = "ssh:"
+            })
+            .collect();
+        let expected = Some(format!("{} in ", style().paint("ssh:spaceship")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_symbol_is_only_shown_over_ssh() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+            })
+            .collect();
+        let expected = Some(format!("{} in ", style().paint("spaceship")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_hostname() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
             })
             .collect();
         let expected = None;
@@ -116,17 +143,15 @@ mod tests {
     }
 
     #[test]
-    fn ssh() -> io::Result<()> {
-        let hostname = get_hostname!();
+    fn trim_at_first_dot_by_default() -> io::Result<()> {
         let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship.example.com")
             .config(toml::toml! {
                 [hostname]
-                ssh_only = true
-                trim_at = ""
+                ssh_only = false
             })
-            .env("SSH_CONNECTION", "something")
             .collect();
-        let expected = Some(format!("{} in ", style().paint(hostname)));
+        let expected = Some(format!("{} in ", style().paint("spaceship")));
 
         assert_eq!(expected, actual);
         Ok(())
@@ -134,15 +159,15 @@ mod tests {
 
     #[test]
     fn no_trim_at() -> io::Result<()> {
-        let hostname = get_hostname!();
         let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship.example.com")
             .config(toml::toml! {
                 [hostname]
                 ssh_only = false
                 trim_at = ""
             })
             .collect();
-        let expected = Some(format!("{} in ", style().paint(hostname)));
+        let expected = Some(format!("{} in ", style().paint("spaceship.example.com")));
 
         assert_eq!(expected, actual);
         Ok(())
@@ -150,16 +175,15 @@ mod tests {
 
     #[test]
     fn trim_at() -> io::Result<()> {
-        let hostname = get_hostname!();
-        let (remainder, trim_at) = hostname.split_at(1);
         let actual = ModuleRenderer::new("hostname")
+            .hostname("spaceship.example.com")
             .config(toml::toml! {
                 [hostname]
                 ssh_only = false
-                trim_at = trim_at
+                trim_at = ".example.com"
             })
             .collect();
-        let expected = Some(format!("{} in ", style().paint(remainder)));
+        let expected = Some(format!("{} in ", style().paint("spaceship")));
 
         assert_eq!(expected, actual);
         Ok(())
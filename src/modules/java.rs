@@ -1,12 +1,14 @@
 use crate::configs::java::JavaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
+use crate::utils;
 
 use super::{Context, Module, RootModuleConfig};
 
-use crate::utils;
-
 use regex::Regex;
+use std::path::Path;
+
 const JAVA_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+)[^\\s]*\\s(?:built|from)";
+const JAVA_DASH_VERSION_PATTERN: &str = "version \"(?P<version>[\\d\\.]+)";
 
 /// Creates a module with the current Java version
 ///
@@ -14,18 +16,14 @@ const JAVA_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+)[^\\s]*\\s(?:built|fro
 ///     - Current directory contains a file with a `.java`, `.class`, `.jar`, `.gradle`, `.clj`, or `.cljc` extension
 ///     - Current directory contains a `pom.xml`, `build.gradle.kts`, `build.sbt`, `.java-version`, `deps.edn`, `project.clj`, or `build.boot` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("java");
+    let config: JavaConfig = JavaConfig::try_load(module.config);
+
     let is_java_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "pom.xml",
-            "build.gradle.kts",
-            "build.sbt",
-            ".java-version",
-            "deps.edn",
-            "project.clj",
-            "build.boot",
-        ])
-        .set_extensions(&["java", "class", "jar", "gradle", "clj", "cljc"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_java_project {
@@ -33,11 +31,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let java_version = get_java_version(context)?;
+    let java_version = VersionFormatter::format_module_version(
+        module.get_name(),
+        &java_version,
+        config.version_format,
+    );
 
-    let mut module = context.new_module("java");
-    let config: JavaConfig = JavaConfig::try_load(module.config);
-
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -65,20 +65,63 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Starting the JVM is slow, so the `release` file of `$JAVA_HOME` is read first
 fn get_java_version(context: &Context) -> Option<String> {
-    let java_command = match context.get_env("JAVA_HOME") {
+    let java_home = context.get_env("JAVA_HOME");
+    if let Some(version) = java_home
+        .as_ref()
+        .and_then(|java_home| get_release_file_version(Path::new(java_home)))
+    {
+        return Some(version);
+    }
+
+    let java_command = match java_home {
         Some(java_home) => format!("{}/bin/java", java_home),
         None => String::from("java"),
     };
 
-    let output = utils::exec_cmd(&java_command.as_str(), &["-Xinternalversion"])?;
-    let java_version = if output.stdout.is_empty() {
+    if let Some(version) = context
+        .exec_cmd(&java_command, &["-Xinternalversion"])
+        .and_then(|output| parse_java_version(&get_command_output(output)))
+    {
+        return Some(version);
+    }
+
+    // Some JVMs don't know `-Xinternalversion`, `-version` prints to stderr
+    let output = context.exec_cmd(&java_command, &["-version"])?;
+    parse_java_dash_version(&get_command_output(output))
+}
+
+fn get_command_output(output: utils::CommandOutput) -> String {
+    if output.stdout.is_empty() {
         output.stderr
     } else {
         output.stdout
-    };
+    }
+}
 
-    parse_java_version(&java_version)
+fn get_release_file_version(java_home: &Path) -> Option<String> {
+    let release = utils::read_file(java_home.join("release")).ok()?;
+    parse_release_file(&release)
+}
+
+/// Reads the version from the `JAVA_VERSION="11.0.4"` line of a JDK's `release` file
+fn parse_release_file(release: &str) -> Option<String> {
+    let version = release.lines().find_map(|line| {
+        let mut parts = line.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("JAVA_VERSION"), Some(value)) => Some(value.trim().trim_matches('"')),
+            _ => None,
+        }
+    })?;
+
+    // Java 8 versions look like `1.8.0_222`
+    let version = version.split('_').next()?;
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_string())
+    }
 }
 
 fn parse_java_version(java_version: &str) -> Option<String> {
@@ -86,73 +129,82 @@ fn parse_java_version(java_version: &str) -> Option<String> {
     let captures = re.captures(java_version)?;
     let version = &captures["version"];
 
-    Some(format!("v{}", &version))
+    Some(version.to_string())
+}
+
+fn parse_java_dash_version(java_version: &str) -> Option<String> {
+    let re = Regex::new(JAVA_DASH_VERSION_PATTERN).ok()?;
+    let captures = re.captures(java_version)?;
+    let version = &captures["version"];
+
+    Some(version.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
     fn test_parse_java_version_openjdk() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 10:18:43 by \"openjdk\" with gcc 4.4.7 20120313 (Red Hat 4.4.7-23)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-post-Ubuntu-1ubuntu219.04) for linux-amd64 JRE (11.0.4+11-post-Ubuntu-1ubuntu219.04), built on Jul 18 2019 18:21:46 by \"build\" with gcc 8.3.0";
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_oracle() {
         let java_8 = "Java HotSpot(TM) Client VM (25.65-b01) for linux-arm-vfp-hflt JRE (1.8.0_65-b17), built on Oct  6 2015 16:19:04 by \"java_re\" with gcc 4.7.2 20120910 (prerelease)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_redhat() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 20:48:53 by \"root\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_12 = "OpenJDK 64-Bit Server VM (12.0.2+10) for linux-amd64 JRE (12.0.2+10), built on Jul 18 2019 14:41:47 by \"jenkins\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_12), Some("v12.0.2".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_12), Some("12.0.2".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_zulu() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (Zulu 8.40.0.25-CA-linux64) (1.8.0_222-b10), built on Jul 11 2019 11:36:39 by \"zulu_re\" with gcc 4.4.7 20120313 (Red Hat 4.4.7-3)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS) for linux-amd64 JRE (Zulu11.33+15-CA) (11.0.4+11-LTS), built on Jul 11 2019 21:37:17 by \"zulu_re\" with gcc 4.9.2 20150212 (Red Hat 4.9.2-6)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_eclipse_openj9() {
         let java_8 = "Eclipse OpenJ9 OpenJDK 64-bit Server VM (1.8.0_222-b10) from linux-amd64 JRE with Extensions for OpenJDK for Eclipse OpenJ9 8.0.222.0, built on Jul 17 2019 21:29:18 by jenkins with g++ (GCC) 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_11 = "Eclipse OpenJ9 OpenJDK 64-bit Server VM (11.0.4+11) from linux-amd64 JRE with Extensions for OpenJDK for Eclipse OpenJ9 11.0.4.0, built on Jul 17 2019 21:51:37 by jenkins with g++ (GCC) 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_graalvm() {
         let java_8 = "OpenJDK 64-Bit GraalVM CE 19.2.0.1 (25.222-b08-jvmci-19.2-b02) for linux-amd64 JRE (8u222), built on Jul 19 2019 17:37:13 by \"buildslave\" with gcc 7.3.0";
-        assert_eq!(parse_java_version(java_8), Some("v8".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("8".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_amazon_corretto() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 20:48:53 by \"root\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS) for linux-amd64 JRE (11.0.4+11-LTS), built on Jul 11 2019 20:06:11 by \"\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_sapmachine() {
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS-sapmachine) for linux-amd64 JRE (11.0.4+11-LTS-sapmachine), built on Jul 17 2019 08:58:43 by \"\" with gcc 7.3.0";
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
@@ -249,4 +301,111 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn test_parse_release_file() {
+        let java_17 = "\
+IMPLEMENTOR=\"Eclipse Adoptium\"
+IMPLEMENTOR_VERSION=\"Temurin-17.0.2+8\"
+JAVA_VERSION=\"17.0.2\"
+JAVA_VERSION_DATE=\"2022-01-18\"
+";
+        assert_eq!(parse_release_file(java_17), Some("17.0.2".to_string()));
+
+        let java_8 = "JAVA_VERSION=\"1.8.0_222\"\nOS_NAME=\"Linux\"\n";
+        assert_eq!(parse_release_file(java_8), Some("1.8.0".to_string()));
+
+        assert_eq!(parse_release_file("OS_NAME=\"Linux\"\n"), None);
+        assert_eq!(parse_release_file("JAVA_VERSION=\"\"\n"), None);
+    }
+
+    #[test]
+    fn test_parse_java_dash_version() {
+        let openjdk_17 = "openjdk version \"17.0.2\" 2022-01-18\nOpenJDK Runtime Environment Temurin-17.0.2+8 (build 17.0.2+8)\nOpenJDK 64-Bit Server VM Temurin-17.0.2+8 (build 17.0.2+8, mixed mode, sharing)\n";
+        let oracle_8 = "java version \"1.8.0_301\"\nJava(TM) SE Runtime Environment (build 1.8.0_301-b09)\nJava HotSpot(TM) 64-Bit Server VM (build 25.301-b09, mixed mode)\n";
+        assert_eq!(
+            parse_java_dash_version(openjdk_17),
+            Some("17.0.2".to_string())
+        );
+        assert_eq!(parse_java_dash_version(oracle_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_dash_version("Unrecognized option"), None);
+    }
+
+    #[test]
+    fn java_home_release_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Main.java"))?.sync_all()?;
+        let java_home = tempfile::tempdir()?;
+        fs::write(
+            java_home.path().join("release"),
+            "JAVA_VERSION=\"17.0.2\"\n",
+        )?;
+        let java_home = java_home.path().to_str().unwrap();
+        let java_command = format!("{}/bin/java -Xinternalversion", java_home);
+
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .env("JAVA_HOME", java_home)
+            .cmd(&java_command, None)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.dimmed().paint("☕ v17.0.2")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn java_home_without_release_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Main.java"))?.sync_all()?;
+        let java_home = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .env("JAVA_HOME", java_home.path().to_str().unwrap())
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.dimmed().paint("☕ v13.0.2")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn java_dash_version_on_stderr() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("pom.xml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .cmd("java -Xinternalversion", None)
+            .cmd(
+                "java -version",
+                Some(CommandOutput {
+                    stdout: String::default(),
+                    stderr: String::from(
+                        "openjdk version \"11.0.12\" 2021-07-20\nOpenJDK Runtime Environment (build 11.0.12+7)\n",
+                    ),
+                    status: Some(0),
+                }),
+            )
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.dimmed().paint("☕ v11.0.12")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Main.java"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .config(toml::toml! {
+                [java]
+                version_format = "${major}"
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.dimmed().paint("☕ 13")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
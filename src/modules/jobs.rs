@@ -15,20 +15,33 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .trim()
         .parse::<i64>()
         .ok()?;
-    if num_of_jobs == 0 {
-        return None;
-    }
 
-    let module_number = if num_of_jobs > config.threshold {
+    // `threshold` predates the separate thresholds of the symbol and the number. When it's
+    // set, the symbol is shown for any job and the number once there are more than `threshold`.
+    let (symbol_threshold, number_threshold) = if config.threshold != 1 {
+        (1, config.threshold + 1)
+    } else {
+        (config.symbol_threshold, config.number_threshold)
+    };
+
+    let module_symbol = if num_of_jobs >= symbol_threshold {
+        config.symbol
+    } else {
+        ""
+    };
+    let module_number = if num_of_jobs >= number_threshold {
         num_of_jobs.to_string()
     } else {
         "".to_string()
     };
+    if module_symbol.is_empty() && module_number.is_empty() {
+        return None;
+    }
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(module_symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -115,4 +128,61 @@ mod test {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn below_symbol_threshold_renders_nothing() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                symbol_threshold = 3
+                number_threshold = 3
+            })
+            .jobs(2)
+            .collect();
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn below_number_threshold_renders_symbol_only() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                number_threshold = 4
+            })
+            .jobs(3)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("✦")));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn number_threshold_of_one_shows_single_job_count() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                number_threshold = 1
+            })
+            .jobs(1)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("✦1")));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn count_without_symbol() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                symbol_threshold = 5
+                number_threshold = 2
+            })
+            .jobs(3)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("3")));
+        assert_eq!(expected, actual);
+    }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::julia::JuliaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Julia version
@@ -11,19 +11,21 @@ use crate::utils;
 ///     - Current directory contains a `Manifest.toml` file
 ///     - Current directory contains a file with the `.jl` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("julia");
+    let config = JuliaConfig::try_load(module.config);
+
     let is_julia_project = context
         .try_begin_scan()?
-        .set_files(&["Project.toml", "Manifest.toml"])
-        .set_extensions(&["jl"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_julia_project {
         return None;
     }
 
-    let mut module = context.new_module("julia");
-    let config = JuliaConfig::try_load(module.config);
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -35,7 +37,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    format_julia_version(&utils::exec_cmd("julia", &["--version"])?.stdout.as_str())
+                    let julia_version = context.exec_cmd("julia", &["--version"])?.stdout;
+                    utils::parse_version(&julia_version)
+                        .map(|version| {
+                            VersionFormatter::format_module_version(
+                                module.get_name(),
+                                version,
+                                config.version_format,
+                            )
+                        })
                         .map(Ok)
                 }
                 _ => None,
@@ -54,21 +64,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_julia_version(julia_stdout: &str) -> Option<String> {
-    // julia version output looks like this:
-    // julia version 1.4.0
-
-    let version = julia_stdout
-        // split into ["", "1.4.0"]
-        .splitn(2, "julia version")
-        // return "1.4.0"
-        .nth(1)?
-        .split_whitespace()
-        .next()?;
-
-    Some(format!("v{}", version))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,8 +120,8 @@ mod tests {
     }
 
     #[test]
-    fn test_format_julia_version() {
+    fn test_parse_julia_version() {
         let input = "julia version 1.4.0";
-        assert_eq!(format_julia_version(input), Some("v1.4.0".to_string()));
+        assert_eq!(utils::parse_version(input), Some("1.4.0"));
     }
 }
@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::localip::LocalipConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the IPv4 address of the local machine
+///
+/// The address is the one of the interface that traffic to `probe_address` would leave through.
+/// Will display the address if all of the following criteria are met:
+///     - localip.disabled is false
+///     - localip.ssh_only is false OR the user is currently connected as an SSH session
+///     - the machine has a route to `probe_address`
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("localip");
+    let config: LocalipConfig = LocalipConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    if config.ssh_only && !context.is_ssh_session() {
+        return None;
+    }
+
+    let probe = match config.probe_address.parse::<SocketAddr>() {
+        Ok(probe) => probe,
+        Err(error) => {
+            log::warn!(
+                "Invalid localip.probe_address {:?}, expected an ip and a port: {}",
+                config.probe_address,
+                error
+            );
+            return None;
+        }
+    };
+    let localipv4 = context.get_local_ipv4(probe)?.to_string();
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "localipv4" => Some(Ok(&localipv4)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `localip`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::io;
+    use std::net::Ipv4Addr;
+
+    const LOCAL_IP: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 42);
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .local_ipv4(LOCAL_IP)
+            .env("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_only_false() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .local_ipv4(LOCAL_IP)
+            .config(toml::toml! {
+                [localip]
+                ssh_only = false
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Yellow.bold().paint("192.168.1.42")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn no_ssh() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .local_ipv4(LOCAL_IP)
+            .config(toml::toml! {
+                [localip]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .local_ipv4(LOCAL_IP)
+            .env("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")
+            .config(toml::toml! {
+                [localip]
+                format = "@[$localipv4]($style) "
+                style = "green"
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("@{} ", Color::Green.paint("192.168.1.42")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn no_address() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .config(toml::toml! {
+                [localip]
+                ssh_only = false
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_probe_address() -> io::Result<()> {
+        let actual = ModuleRenderer::new("localip")
+            .local_ipv4(LOCAL_IP)
+            .config(toml::toml! {
+                [localip]
+                ssh_only = false
+                probe_address = "example.com"
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+}
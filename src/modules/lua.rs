@@ -1,12 +1,9 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::lua::LuaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
-use regex::Regex;
-const LUA_VERSION_PATERN: &str = "(?P<version>[\\d\\.]+[a-z\\-]*[1-9]*)[^\\s]*";
-
 /// Creates a module with the current Lua version
 ///
 /// Will display the Lua version if any of the following criteria are met:
@@ -14,21 +11,27 @@ const LUA_VERSION_PATERN: &str = "(?P<version>[\\d\\.]+[a-z\\-]*[1-9]*)[^\\s]*";
 ///     - Current directory contains a `lua` directory
 ///     - Current directory contains a file with the `.lua` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("lua");
+    let config = LuaConfig::try_load(module.config);
+
     let is_lua_project = context
         .try_begin_scan()?
-        .set_files(&[".lua-version"])
-        .set_folders(&["lua"])
-        .set_extensions(&["lua"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_lua_project {
         return None;
     }
 
-    let mut module = context.new_module("lua");
-    let config = LuaConfig::try_load(module.config);
-    let lua_version = format_lua_version(&get_lua_version(&config.lua_binary)?)?;
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let lua_version = get_lua_version(context, config.lua_binary)?;
+    let lua_version = VersionFormatter::format_module_version(
+        module.get_name(),
+        utils::parse_version(&lua_version)?,
+        config.version_format,
+    );
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -56,8 +59,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_lua_version(lua_binary: &str) -> Option<String> {
-    match utils::exec_cmd(lua_binary, &["-v"]) {
+fn get_lua_version(context: &Context, lua_binary: &str) -> Option<String> {
+    match context.exec_cmd(lua_binary, &["-v"]) {
         Some(output) => {
             if output.stdout.is_empty() {
                 Some(output.stderr)
@@ -69,18 +72,6 @@ fn get_lua_version(lua_binary: &str) -> Option<String> {
     }
 }
 
-fn format_lua_version(lua_stdout: &str) -> Option<String> {
-    // lua -v output looks like this:
-    // Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio
-
-    // luajit -v output looks like this:
-    // LuaJIT 2.0.5 -- Copyright (C) 2005-2017 Mike Pall. http://luajit.org/
-    let re = Regex::new(LUA_VERSION_PATERN).ok()?;
-    let captures = re.captures(lua_stdout)?;
-    let version = &captures["version"];
-    Some(format!("v{}", version))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,15 +143,12 @@ mod tests {
     }
 
     #[test]
-    fn test_format_lua_version() {
+    fn test_parse_lua_version() {
         let lua_input = "Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio";
-        assert_eq!(format_lua_version(lua_input), Some("v5.4.0".to_string()));
+        assert_eq!(utils::parse_version(lua_input), Some("5.4.0"));
 
         let luajit_input =
             "LuaJIT 2.1.0-beta3 -- Copyright (C) 2005-2017 Mike Pall. http://luajit.org/";
-        assert_eq!(
-            format_lua_version(luajit_input),
-            Some("v2.1.0-beta3".to_string())
-        );
+        assert_eq!(utils::parse_version(luajit_input), Some("2.1.0-beta3"));
     }
 }
@@ -14,9 +14,11 @@ mod elixir;
 mod elm;
 mod env_var;
 mod erlang;
+mod fill;
 mod gcloud;
 mod git_branch;
 mod git_commit;
+mod git_metrics;
 mod git_state;
 mod git_status;
 mod golang;
@@ -28,6 +30,7 @@ mod jobs;
 mod julia;
 mod kubernetes;
 mod line_break;
+mod localip;
 mod lua;
 mod memory_usage;
 mod nim;
@@ -35,6 +38,7 @@ mod nix_shell;
 mod nodejs;
 mod ocaml;
 mod openstack;
+mod os;
 mod package;
 mod perl;
 mod php;
@@ -42,28 +46,33 @@ mod purescript;
 mod python;
 mod ruby;
 mod rust;
+mod shell;
 mod shlvl;
 mod singularity;
 mod status;
+mod sudo;
 mod swift;
 mod terraform;
 mod time;
 mod username;
-mod utils;
+pub(crate) mod utils;
 mod zig;
 
 #[cfg(feature = "battery")]
 mod battery;
 
+#[cfg(feature = "battery")]
+pub use self::battery::{BatteryInfoProvider, BatteryInfoProviderImpl};
+
 use crate::config::RootModuleConfig;
 use crate::context::{Context, Shell};
 use crate::module::Module;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
     let start: Instant = Instant::now();
 
-    let mut m: Option<Module> = {
+    let m: Option<Module> = {
         match module {
             // Keep these ordered alphabetically.
             // Default ordering is handled in configs/starship_root.rs
@@ -81,10 +90,12 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "elixir" => elixir::module(context),
             "elm" => elm::module(context),
             "erlang" => erlang::module(context),
-            "env_var" => env_var::module(context),
+            "fill" => fill::module(context),
+            "env_var" => env_var::module(None, context),
             "gcloud" => gcloud::module(context),
             "git_branch" => git_branch::module(context),
             "git_commit" => git_commit::module(context),
+            "git_metrics" => git_metrics::module(context),
             "git_state" => git_state::module(context),
             "git_status" => git_status::module(context),
             "golang" => golang::module(context),
@@ -96,6 +107,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "julia" => julia::module(context),
             "kubernetes" => kubernetes::module(context),
             "line_break" => line_break::module(context),
+            "localip" => localip::module(context),
             "lua" => lua::module(context),
             "memory_usage" => memory_usage::module(context),
             "nim" => nim::module(context),
@@ -103,6 +115,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "nodejs" => nodejs::module(context),
             "ocaml" => ocaml::module(context),
             "openstack" => openstack::module(context),
+            "os" => os::module(context),
             "package" => package::module(context),
             "perl" => perl::module(context),
             "php" => php::module(context),
@@ -110,10 +123,12 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "python" => python::module(context),
             "ruby" => ruby::module(context),
             "rust" => rust::module(context),
+            "shell" => shell::module(context),
             "shlvl" => shlvl::module(context),
             "singularity" => singularity::module(context),
             "swift" => swift::module(context),
             "status" => status::module(context),
+            "sudo" => sudo::module(context),
             "terraform" => terraform::module(context),
             "time" => time::module(context),
             "crystal" => crystal::module(context),
@@ -126,18 +141,49 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
-    let elapsed = start.elapsed();
-    log::trace!("Took {:?} to compute module {:?}", elapsed, module);
-    if elapsed.as_millis() < 1 {
-        // If we take less than 1ms to compute a None, then we will not return a module at all
-        // if we have a module: default duration is 0 so no need to change it
-        m
-    } else {
-        // if we took more than 1ms we want to report that and so--in case we have None currently--
-        // need to create an empty module just to hold the duration for that case
-        m.get_or_insert_with(|| context.new_module(module)).duration = elapsed;
-        m
+    record_duration(m, start.elapsed(), module, || context.new_module(module))
+}
+
+/// Computes the custom module `name`, recording how long it took like `handle` does
+pub fn handle_custom<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    let start: Instant = Instant::now();
+    let m = custom::module(name, context);
+    record_duration(m, start.elapsed(), name, || {
+        Module::new(name, "<custom module>", None)
+    })
+}
+
+/// Computes the `env_var` module configured under `[env_var.<name>]`, recording how long it
+/// took like `handle` does
+pub fn handle_env_var<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    let start: Instant = Instant::now();
+    let m = env_var::module(Some(name), context);
+    record_duration(m, start.elapsed(), name, || {
+        Module::new(&format!("env_var.{}", name), description("env_var"), None)
+    })
+}
+
+fn record_duration<'a, F>(
+    mut m: Option<Module<'a>>,
+    elapsed: Duration,
+    name: &str,
+    new_module: F,
+) -> Option<Module<'a>>
+where
+    F: FnOnce() -> Module<'a>,
+{
+    log::trace!("Took {:?} to compute module {:?}", elapsed, name);
+    if let Some(m) = &mut m {
+        m.duration = elapsed;
+    } else if elapsed.as_millis() >= 1 {
+        // If we take less than 1ms to compute a None, then we will not return a module at all.
+        // If we took more than 1ms we want to report that, so we create an empty module just to
+        // hold the duration.
+        let mut empty = new_module();
+        empty.duration = elapsed;
+        m = Some(empty);
     }
+    m
 }
 
 pub fn description(module: &str) -> &'static str {
@@ -157,9 +203,11 @@ pub fn description(module: &str) -> &'static str {
         "dotnet" => "The relevant version of the .NET Core SDK for the current directory",
         "env_var" => "Displays the current value of a selected environment variable",
         "erlang" => "Current OTP version",
+        "fill" => "Fills the remaining space on the line with a pad string",
         "gcloud" => "The current GCP client configuration",
         "git_branch" => "The active branch of the repo in your current directory",
         "git_commit" => "The active commit (and tag if any) of the repo in your current directory",
+        "git_metrics" => "The number of lines added and deleted in the working tree",
         "git_state" => "The current git operation, and it's progress",
         "git_status" => "Symbol representing the state of the repo",
         "golang" => "The currently installed version of Golang",
@@ -171,6 +219,7 @@ pub fn description(module: &str) -> &'static str {
         "julia" => "The currently installed version of Julia",
         "kubernetes" => "The current Kubernetes context name and, if set, the namespace",
         "line_break" => "Separates the prompt into two lines",
+        "localip" => "The currently assigned ipv4 address",
         "lua" => "The currently installed version of Lua",
         "memory_usage" => "Current system memory and swap usage",
         "nim" => "The currently installed version of Nim",
@@ -178,6 +227,7 @@ pub fn description(module: &str) -> &'static str {
         "nodejs" => "The currently installed version of NodeJS",
         "ocaml" => "The currently installed version of OCaml",
         "openstack" => "The current OpenStack cloud and project",
+        "os" => "The current operating system",
         "package" => "The package version of the current directory's project",
         "perl" => "The currently installed version of Perl",
         "php" => "The currently installed version of PHP",
@@ -186,8 +236,10 @@ pub fn description(module: &str) -> &'static str {
         "ruby" => "The currently installed version of Ruby",
         "rust" => "The currently installed version of Rust",
         "swift" => "The currently installed version of Swift",
+        "shell" => "The currently used shell indicator",
         "shlvl" => "The current value of SHLVL",
         "status" => "The status of the last command",
+        "sudo" => "The sudo credentials are currently cached",
         "terraform" => "The currently selected terraform workspace and version",
         "time" => "The current local time",
         "username" => "The active user's username",
@@ -1,28 +1,33 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::nodejs::NodejsConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use regex::Regex;
 use semver::Version;
 use semver::VersionReq;
 use serde_json as json;
-use std::path::PathBuf;
+use std::path::Path;
 
 /// Creates a module with the current Node.js version
 ///
 /// Will display the Node.js version if any of the following criteria are met:
-///     - Current directory contains a `.js`, `.mjs` or `.cjs` file
-///     - Current directory contains a `.ts` file
-///     - Current directory contains a `package.json` or `.node-version` file
+///     - Current directory contains a `.js`, `.mjs`, `.cjs` or `.ts` file
+///     - Current directory contains a `package.json`, `.node-version` or `.nvmrc` file
 ///     - Current directory contains a `node_modules` directory
+///
+/// The version is styled with `not_capable_style` when it doesn't satisfy `engines.node` in
+/// `package.json`.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("nodejs");
+    let config = NodejsConfig::try_load(module.config);
+
     let is_js_project = context
         .try_begin_scan()?
-        .set_files(&["package.json", ".node-version"])
-        .set_extensions(&["js", "mjs", "cjs", "ts"])
-        .set_folders(&["node_modules"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     let is_esy_project = context
@@ -34,12 +39,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let mut module = context.new_module("nodejs");
-    let config = NodejsConfig::try_load(module.config);
-    let nodejs_version = utils::exec_cmd("node", &["--version"])?.stdout;
+    let nodejs_version = context.exec_cmd("node", &["--version"])?.stdout;
+    let nodejs_version = nodejs_version.trim();
     let engines_version = get_engines_version(&context.current_dir);
-    let in_engines_range = check_engines_version(&nodejs_version, engines_version);
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let in_engines_range = check_engines_version(nodejs_version, engines_version);
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -56,7 +60,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(nodejs_version.trim())),
+                "version" => Some(Ok(VersionFormatter::format_module_version(
+                    module.get_name(),
+                    nodejs_version.trim_start_matches('v'),
+                    config.version_format,
+                ))),
                 _ => None,
             })
             .parse(None)
@@ -73,7 +81,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_engines_version(base_dir: &PathBuf) -> Option<String> {
+fn get_engines_version(base_dir: &Path) -> Option<String> {
     let json_str = utils::read_file(base_dir.join("package.json")).ok()?;
     let package_json: json::Value = json::from_str(&json_str).ok()?;
     let raw_version = package_json.get("engines")?.get("node")?.as_str()?;
@@ -81,20 +89,19 @@ fn get_engines_version(base_dir: &PathBuf) -> Option<String> {
 }
 
 fn check_engines_version(nodejs_version: &str, engines_version: Option<String>) -> bool {
-    if engines_version.is_none() {
-        return true;
-    }
-    let r = match VersionReq::parse(&engines_version.unwrap()) {
+    let engines_version = match engines_version {
+        Some(engines_version) => engines_version,
+        None => return true,
+    };
+    let r = match VersionReq::parse(&engines_version) {
         Ok(r) => r,
         Err(_e) => return true,
     };
     let re = Regex::new(r"\d+\.\d+\.\d+").unwrap();
-    let version = re
-        .captures(nodejs_version)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .as_str();
+    let version = match re.find(nodejs_version) {
+        Some(version) => version.as_str(),
+        None => return true,
+    };
     let v = match Version::parse(version) {
         Ok(v) => v,
         Err(_e) => return true,
@@ -105,6 +112,7 @@ fn check_engines_version(nodejs_version: &str, engines_version: Option<String>)
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use ansi_term::Color;
     use std::fs::{self, File};
     use std::io;
@@ -154,6 +162,17 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_nvmrc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".nvmrc"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs").path(dir.path()).collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_js_file() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -247,4 +266,117 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn engines_node_version_with_mocked_node() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{ "name": "fixture", "engines": { "node": ">=18.0.0" } }"#,
+        )?;
+        let node_version = |version: &str| {
+            Some(CommandOutput {
+                stdout: format!("{}\n", version),
+                stderr: String::default(),
+                status: Some(0),
+            })
+        };
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .cmd("node --version", node_version("v18.17.0"))
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v18.17.0")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .cmd("node --version", node_version("v16.20.1"))
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("⬢ v16.20.1")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn unparsable_node_version_is_capable() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{ "engines": { "node": ">=18.0.0" } }"#,
+        )?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .cmd(
+                "node --version",
+                Some(CommandOutput {
+                    stdout: String::from("nightly\n"),
+                    stderr: String::default(),
+                    status: Some(0),
+                }),
+            )
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ vnightly")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn node_not_installed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .cmd("node --version", None)
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                version_format = "v${major}.${minor}"
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn custom_detect_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+        File::create(dir.path().join("deno.json"))?.sync_all()?;
+        let config = toml::toml! {
+            [nodejs]
+            detect_extensions = []
+            detect_files = ["deno.json"]
+        };
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+
+        fs::remove_file(dir.path().join("deno.json"))?;
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
 }
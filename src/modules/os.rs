@@ -0,0 +1,216 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::os::OsConfig;
+use crate::formatter::StringFormatter;
+use crate::utils::OsInfo;
+
+/// Creates a module with the current operating system
+///
+/// On Linux the distribution is read from `/etc/os-release`, on other platforms the operating
+/// system starship was built for is shown.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("os");
+    let config: OsConfig = OsConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let os_info = context.get_os_info();
+    let symbol = get_symbol(&config, os_info);
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "type" => Some(Ok(&os_info.os_type)),
+                "name" => Some(Ok(&os_info.name)),
+                "version" => os_info.version.as_ref().map(Ok),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `os`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Looks up the symbol of the operating system. Symbols set in `[os.symbols]` take precedence
+/// over the default ones, a `wsl` symbol is used inside WSL and distributions without a symbol
+/// fall back to the one of `linux`.
+fn get_symbol<'a>(config: &OsConfig<'a>, os_info: &OsInfo) -> &'a str {
+    let default_symbols = OsConfig::new().symbols;
+    let fallback = if cfg!(target_os = "linux") {
+        Some("linux")
+    } else {
+        None
+    };
+
+    let candidates = std::iter::once(if os_info.wsl { Some("wsl") } else { None })
+        .chain(std::iter::once(Some(os_info.os_type.as_str())))
+        .chain(std::iter::once(fallback))
+        .flatten();
+    for candidate in candidates {
+        if let Some(symbol) = config.symbols.get(candidate) {
+            return symbol;
+        }
+        if let Some(symbol) = default_symbols.get(candidate) {
+            return symbol;
+        }
+    }
+
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use crate::utils::OsInfo;
+    use ansi_term::Color;
+    use std::io;
+
+    fn os_info(os_type: &str, name: &str, version: Option<&str>, wsl: bool) -> OsInfo {
+        OsInfo {
+            os_type: os_type.to_owned(),
+            name: name.to_owned(),
+            version: version.map(ToOwned::to_owned),
+            wsl,
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("macos", "macOS", None, false))
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn default_symbol() -> io::Result<()> {
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("macos", "macOS", None, false))
+            .config(toml::toml! {
+                [os]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("🍎 ")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_symbol_keeps_other_defaults() -> io::Result<()> {
+        let config = toml::toml! {
+            [os]
+            disabled = false
+            [os.symbols]
+            arch = "A "
+        };
+
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("arch", "Arch Linux", None, false))
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("A ")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("ubuntu", "Ubuntu", Some("20.04"), false))
+            .config(config)
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("🎯 ")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn unknown_distribution_uses_linux_symbol() -> io::Result<()> {
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("exotic", "Exotic Linux", None, false))
+            .config(toml::toml! {
+                [os]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("🐧 ")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn wsl_symbol() -> io::Result<()> {
+        let config = toml::toml! {
+            [os]
+            disabled = false
+            [os.symbols]
+            wsl = "W "
+        };
+
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("ubuntu", "Ubuntu", None, true))
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("W ")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("ubuntu", "Ubuntu", None, false))
+            .config(config)
+            .collect();
+        let expected = Some(format!("{}", Color::White.bold().paint("🎯 ")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn name_and_version() -> io::Result<()> {
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("fedora", "Fedora", Some("33"), false))
+            .config(toml::toml! {
+                [os]
+                disabled = false
+                format = "[$name( $version) \\($type\\)]($style) "
+                style = "blue"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Blue.paint("Fedora 33 (fedora)")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("os")
+            .os_info(os_info("arch", "Arch Linux", None, false))
+            .config(toml::toml! {
+                [os]
+                disabled = false
+                format = "[$name( $version)]($style) "
+                style = "blue"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Blue.paint("Arch Linux")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::perl::PerlConfig;
-use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current perl version
 ///
@@ -11,30 +10,25 @@ use crate::utils;
 ///     - Current directory contains a "Makefile.PL", "Build.PL",  "cpanfile", "cpanfile.snapshot",
 ///       "META.json", "META.yml", or ".perl-version" file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("perl");
+    let config: PerlConfig = PerlConfig::try_load(module.config);
+
     let is_perl_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "Makefile.PL",
-            "Build.PL",
-            "cpanfile",
-            "cpanfile.snapshot",
-            "META.json",
-            "META.yml",
-            ".perl-version",
-        ])
-        .set_extensions(&["pl", "pm", "pod"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_perl_project {
         return None;
     }
 
-    let perl_version = utils::exec_cmd("perl", &["-e", "printf q#%vd#,$^V;"])?.stdout;
-
-    let mut module = context.new_module("perl");
-    let config: PerlConfig = PerlConfig::try_load(module.config);
+    let perl_version = context
+        .exec_cmd("perl", &["-e", "printf q#%vd#,$^V;"])?
+        .stdout;
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -45,7 +39,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(format!("v{}", &perl_version))),
+                "version" => Some(Ok(VersionFormatter::format_module_version(
+                    module.get_name(),
+                    &perl_version,
+                    config.version_format,
+                ))),
                 _ => None,
             })
             .parse(None)
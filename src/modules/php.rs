@@ -1,8 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::php::PhpConfig;
-use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current PHP version
 ///
@@ -10,17 +9,21 @@ use crate::utils;
 ///     - Current directory contains a `.php` file
 ///     - Current directory contains a `composer.json` or `.php-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("php");
+    let config: PhpConfig = PhpConfig::try_load(module.config);
+
     let is_php_project = context
         .try_begin_scan()?
-        .set_files(&["composer.json", ".php-version"])
-        .set_extensions(&["php"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_php_project {
         return None;
     }
 
-    match utils::exec_cmd(
+    match context.exec_cmd(
         "php",
         &[
             "-nr",
@@ -28,25 +31,27 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         ],
     ) {
         Some(php_cmd_output) => {
-            let mut module = context.new_module("php");
-            let config: PhpConfig = PhpConfig::try_load(module.config);
-
-            let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-                formatter
-                    .map_meta(|variable, _| match variable {
-                        "symbol" => Some(config.symbol),
-                        _ => None,
-                    })
-                    .map_style(|variable| match variable {
-                        "style" => Some(Ok(config.style)),
-                        _ => None,
-                    })
-                    .map(|variable| match variable {
-                        "version" => format_php_version(&php_cmd_output.stdout).map(Ok),
-                        _ => None,
-                    })
-                    .parse(None)
-            });
+            let parsed =
+                StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+                    formatter
+                        .map_meta(|variable, _| match variable {
+                            "symbol" => Some(config.symbol),
+                            _ => None,
+                        })
+                        .map_style(|variable| match variable {
+                            "style" => Some(Ok(config.style)),
+                            _ => None,
+                        })
+                        .map(|variable| match variable {
+                            "version" => Some(Ok(VersionFormatter::format_module_version(
+                                module.get_name(),
+                                &php_cmd_output.stdout,
+                                config.version_format,
+                            ))),
+                            _ => None,
+                        })
+                        .parse(None)
+                });
 
             module.set_segments(match parsed {
                 Ok(segments) => segments,
@@ -62,27 +67,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn format_php_version(php_version: &str) -> Option<String> {
-    let mut formatted_version = String::with_capacity(php_version.len() + 1);
-    formatted_version.push('v');
-    formatted_version.push_str(php_version);
-    Some(formatted_version)
-}
-
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::fs::File;
     use std::io;
 
-    #[test]
-    fn test_format_php_version() {
-        let input = "7.3.8";
-        assert_eq!(format_php_version(input), Some("v7.3.8".to_string()));
-    }
-
     #[test]
     fn folder_without_php_files() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -138,4 +129,22 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn version_formatting() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("any.php"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("php")
+            .path(dir.path())
+            .config(toml::toml! {
+                [php]
+                version_format = "${major}.${minor}"
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Fixed(147).bold().paint("🐘 7.3")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
@@ -3,8 +3,7 @@ use std::path::Path;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::python::PythonConfig;
-use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current Python version
 ///
@@ -20,17 +19,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: PythonConfig = PythonConfig::try_load(module.config);
 
     let is_py_project = {
-        let base = context.try_begin_scan()?.set_files(&[
-            "requirements.txt",
-            ".python-version",
-            "pyproject.toml",
-            "Pipfile",
-            "tox.ini",
-            "setup.py",
-            "__init__.py",
-        ]);
+        let base = context
+            .try_begin_scan()?
+            .set_files(&config.detect_files)
+            .set_folders(&config.detect_folders);
         if config.scan_for_pyfiles {
-            base.set_extensions(&["py"]).is_match()
+            base.set_extensions(&config.detect_extensions).is_match()
         } else {
             base.is_match()
         }
@@ -43,14 +37,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let python_version = if config.pyenv_version_name {
-        utils::exec_cmd("pyenv", &["version-name"])?.stdout
+        context.exec_cmd("pyenv", &["version-name"])?.stdout
     } else {
         let version = config
             .python_binary
             .0
             .iter()
-            .find_map(|binary| get_python_version(binary))?;
-        format_python_version(&version)
+            .find_map(|binary| get_python_version(context, binary))?;
+        VersionFormatter::format_module_version(
+            module.get_name(),
+            parse_python_version(&version),
+            config.version_format,
+        )
     };
     let virtual_env = get_python_virtual_env(context);
     let pyenv_prefix = if config.pyenv_version_name {
@@ -59,7 +57,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         ""
     };
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -89,27 +87,28 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_python_version(python_binary: &str) -> Option<String> {
-    match utils::exec_cmd(python_binary, &["--version"]) {
-        Some(output) => {
-            if output.stdout.is_empty() {
-                Some(output.stderr)
-            } else {
-                Some(output.stdout)
-            }
-        }
-        None => None,
+/// Python 2 prints its version to stderr, Python 3 to stdout
+fn get_python_version(context: &Context, python_binary: &str) -> Option<String> {
+    let output = context.exec_cmd(python_binary, &["--version"])?;
+    let version = if output.stdout.trim().is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+
+    if version.trim().is_empty() {
+        None
+    } else {
+        Some(version)
     }
 }
 
-fn format_python_version(python_stdout: &str) -> String {
-    format!(
-        "v{}",
-        python_stdout
-            .trim_start_matches("Python ")
-            .trim_end_matches(":: Anaconda, Inc.")
-            .trim()
-    )
+fn parse_python_version(python_stdout: &str) -> &str {
+    python_stdout
+        .trim()
+        .trim_start_matches("Python ")
+        .trim_end_matches(":: Anaconda, Inc.")
+        .trim()
 }
 
 fn get_python_virtual_env(context: &Context) -> Option<String> {
@@ -133,6 +132,7 @@ fn get_prompt_from_venv(venv_path: &Path) -> Option<String> {
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use ansi_term::Color;
     use std::fs::{create_dir_all, File};
     use std::io;
@@ -141,13 +141,122 @@ mod tests {
     #[test]
     fn test_format_python_version() {
         let input = "Python 3.7.2";
-        assert_eq!(format_python_version(input), "v3.7.2");
+        assert_eq!(parse_python_version(input), "3.7.2");
     }
 
     #[test]
     fn test_format_python_version_anaconda() {
         let input = "Python 3.6.10 :: Anaconda, Inc.";
-        assert_eq!(format_python_version(input), "v3.6.10");
+        assert_eq!(parse_python_version(input), "3.6.10");
+    }
+
+    #[test]
+    fn test_get_python_virtual_env_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let venv = dir.path().join("my_venv");
+        create_dir_all(&venv)?;
+        let mut context = Context::new_with_dir(Default::default(), dir.path());
+        context
+            .env
+            .insert("VIRTUAL_ENV", venv.to_str().unwrap().to_owned());
+        assert_eq!(
+            get_python_virtual_env(&context),
+            Some(String::from("my_venv"))
+        );
+
+        std::fs::write(
+            venv.join("pyvenv.cfg"),
+            "home = /usr/bin\nprompt = \"custom\"\n",
+        )?;
+        assert_eq!(
+            get_python_virtual_env(&context),
+            Some(String::from("custom"))
+        );
+
+        context.env.clear();
+        assert_eq!(get_python_virtual_env(&context), None);
+        dir.close()
+    }
+
+    #[test]
+    fn binaries_are_tried_in_order() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("requirements.txt"))?.sync_all()?;
+        let render = |python_binary: Vec<&str>| {
+            ModuleRenderer::new("python")
+                .path(dir.path())
+                .config(toml::toml! {
+                    [python]
+                    python_binary = python_binary
+                })
+                .cmd(
+                    "python3 --version",
+                    Some(CommandOutput {
+                        stdout: String::from("Python 3.11.4\n"),
+                        stderr: String::default(),
+                        status: Some(0),
+                    }),
+                )
+                .cmd(
+                    "python --version",
+                    Some(CommandOutput {
+                        stdout: String::default(),
+                        stderr: String::default(),
+                        status: Some(0),
+                    }),
+                )
+                .collect()
+        };
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v3.11.4")));
+        assert_eq!(render(vec!["python3", "python2"]), expected);
+        // `python` prints no version, so `python3` is used
+        assert_eq!(render(vec!["python", "python3"]), expected);
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v2.7.17")));
+        assert_eq!(render(vec!["python2", "python3"]), expected);
+        assert_eq!(render(vec!["python", "python2", "python3"]), expected);
+
+        assert_eq!(render(vec!["python"]), None);
+        dir.close()
+    }
+
+    #[test]
+    fn version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("pyproject.toml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                python_binary = "python3"
+                version_format = "${major}.${minor}"
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 3.8")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn custom_detect_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("environment.yml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                python_binary = "python3"
+                detect_files = ["environment.yml"]
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.0")));
+        assert_eq!(expected, actual);
+        dir.close()
     }
 
     #[test]
@@ -349,6 +458,28 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn without_venv_uses_fallback() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("requirements.txt"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                format = "via [$version \\(${virtualenv:-system}\\)]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("v3.8.0 (system)")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
     #[test]
     fn with_active_venv_and_prompt() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -376,6 +507,30 @@ prompt = 'foo'
         dir.close()
     }
 
+    #[test]
+    fn with_active_venv_and_empty_prompt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_dir_all(dir.path().join("my_venv"))?;
+        let mut venv_cfg = File::create(dir.path().join("my_venv").join("pyvenv.cfg"))?;
+        venv_cfg.write_all(
+            br#"
+home = something
+prompt =
+        "#,
+        )?;
+        venv_cfg.sync_all()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env("VIRTUAL_ENV", dir.path().join("my_venv").to_str().unwrap())
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.0")));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
     fn check_python2_renders(dir: &tempfile::TempDir, starship_config: Option<toml::Value>) {
         let config = starship_config.unwrap_or(toml::toml! {
             [python]
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::ruby::RubyConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Ruby version
@@ -10,19 +10,21 @@ use crate::utils;
 ///     - Current directory contains a `.rb` file
 ///     - Current directory contains a `Gemfile` or `.ruby-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("ruby");
+    let config = RubyConfig::try_load(module.config);
+
     let is_rb_project = context
         .try_begin_scan()?
-        .set_files(&["Gemfile", ".ruby-version"])
-        .set_extensions(&["rb"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_rb_project {
         return None;
     }
 
-    let mut module = context.new_module("ruby");
-    let config = RubyConfig::try_load(module.config);
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -34,7 +36,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    format_ruby_version(&utils::exec_cmd("ruby", &["-v"])?.stdout.as_str()).map(Ok)
+                    let ruby_version = context.exec_cmd("ruby", &["-v"])?.stdout;
+                    utils::parse_version(&ruby_version)
+                        .map(|version| {
+                            VersionFormatter::format_module_version(
+                                module.get_name(),
+                                version,
+                                config.version_format,
+                            )
+                        })
+                        .map(Ok)
                 }
                 _ => None,
             })
@@ -52,29 +63,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_ruby_version(ruby_version: &str) -> Option<String> {
-    let version = ruby_version
-        // split into ["ruby", "2.6.0p0", "linux/amd64"]
-        .split_whitespace()
-        // return "2.6.0p0"
-        .nth(1)?
-        // split into ["2.6.0", "0"]
-        .split('p')
-        // return "2.6.0"
-        .next()?;
-
-    let mut formatted_version = String::with_capacity(version.len() + 1);
-    formatted_version.push('v');
-    formatted_version.push_str(version);
-    Some(formatted_version)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
@@ -125,20 +119,64 @@ mod tests {
     }
 
     #[test]
-    fn test_format_ruby_version() -> io::Result<()> {
+    fn folder_with_custom_detect_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Gemfile"))?.sync_all()?;
+        File::create(dir.path().join("Rakefile"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .config(toml::toml! {
+                [ruby]
+                detect_files = ["Rakefile"]
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💎 v2.5.1")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .config(toml::toml! {
+                [ruby]
+                detect_files = []
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_detect_folders() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("gems"))?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .config(toml::toml! {
+                [ruby]
+                detect_folders = ["gems"]
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💎 v2.5.1")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_ruby_version() -> io::Result<()> {
         assert_eq!(
-            format_ruby_version("ruby 2.1.10p492 (2016-04-01 revision 54464) [x86_64-darwin19.0]"),
-            Some("v2.1.10".to_string())
+            utils::parse_version("ruby 2.1.10p492 (2016-04-01 revision 54464) [x86_64-darwin19.0]"),
+            Some("2.1.10")
         );
         assert_eq!(
-            format_ruby_version("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]"),
-            Some("v2.5.1".to_string())
+            utils::parse_version("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]"),
+            Some("2.5.1")
         );
         assert_eq!(
-            format_ruby_version(
+            utils::parse_version(
                 "ruby 2.7.0p0 (2019-12-25 revision 647ee6f091) [x86_64-linux-musl]"
             ),
-            Some("v2.7.0".to_string())
+            Some("2.7.0")
         );
 
         Ok(())
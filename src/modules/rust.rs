@@ -1,13 +1,16 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use std::process::{Command, Output};
+use std::path::{Path, PathBuf};
 
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
 use serde::Deserialize;
 
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::rust::RustConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
+use crate::utils::{self, CommandOutput};
 
 /// Creates a module with the current Rust version
 ///
@@ -15,19 +18,40 @@ use crate::formatter::StringFormatter;
 ///     - Current directory contains a file with a `.rs` extension
 ///     - Current directory contains a `Cargo.toml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("rust");
+    let config = RustConfig::try_load(module.config);
+
     let is_rs_project = context
         .try_begin_scan()?
-        .set_files(&["Cargo.toml"])
-        .set_extensions(&["rs"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_rs_project {
         return None;
     }
 
-    let mut module = context.new_module("rust");
-    let config = RustConfig::try_load(module.config);
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    // The toolchain and the version are only looked up if the format uses them
+    let toolchain = OnceCell::new();
+    let get_toolchain = || {
+        toolchain
+            .get_or_init(|| get_rust_toolchain(context))
+            .as_ref()
+    };
+    let version = OnceCell::new();
+    let get_version = || {
+        version
+            .get_or_init(|| {
+                let toolchain_override = get_toolchain()
+                    .filter(|toolchain| toolchain.is_override)
+                    .map(|toolchain| toolchain.name.as_str());
+                get_module_version(context, toolchain_override)
+            })
+            .as_deref()
+    };
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -38,9 +62,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                // This may result in multiple calls to `get_module_version` when a user have
-                // multiple `$version` variables defined in `format`.
-                "version" => get_module_version(context).map(Ok),
+                "version" => get_version()
+                    .map(|version| {
+                        format_version(module.get_name(), version, config.version_format)
+                    })
+                    .map(Ok),
+                "numver" => get_version()
+                    .and_then(extract_numeric_version)
+                    .map(|numver| Ok(numver.to_owned())),
+                "toolchain" => get_toolchain().map(|toolchain| Ok(toolchain.name.clone())),
                 _ => None,
             })
             .parse(None)
@@ -57,39 +87,78 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_module_version(context: &Context) -> Option<String> {
-    // `$CARGO_HOME/bin/rustc(.exe) --version` may attempt installing a rustup toolchain.
-    // https://github.com/starship/starship/issues/417
-    //
-    // To display appropriate versions preventing `rustc` from downloading toolchains, we have to
-    // check
-    // 1. `$RUSTUP_TOOLCHAIN`
-    // 2. `rustup override list`
-    // 3. `rust-toolchain` in `.` or parent directories
-    // as `rustup` does.
-    // https://github.com/rust-lang/rustup.rs/tree/eb694fcada7becc5d9d160bf7c623abe84f8971d#override-precedence
-    //
-    // Probably we have no other way to know whether any toolchain override is specified for the
-    // current directory. The following commands also cause toolchain installations.
-    // - `rustup show`
-    // - `rustup show active-toolchain`
-    // - `rustup which`
-    let module_version = if let Some(toolchain) = env_rustup_toolchain(context)
-        .or_else(|| execute_rustup_override_list(&context.current_dir))
-        .or_else(|| find_rust_toolchain_file(&context))
-    {
-        match execute_rustup_run_rustc_version(&toolchain) {
-            RustupRunRustcVersionOutcome::RustcVersion(stdout) => format_rustc_version(stdout),
-            RustupRunRustcVersionOutcome::ToolchainName(toolchain) => toolchain,
-            RustupRunRustcVersionOutcome::RustupNotWorking => {
-                // If `rustup` is not in `$PATH` or cannot be executed for other reasons, we can
-                // safely execute `rustc --version`.
-                format_rustc_version(execute_rustc_version()?)
-            }
-            RustupRunRustcVersionOutcome::Err => return None,
+/// Finds the toolchain rustup would use in the current directory, without executing rustup.
+///
+/// `$CARGO_HOME/bin/rustc(.exe) --version` may attempt installing a rustup toolchain.
+/// https://github.com/starship/starship/issues/417
+///
+/// To display appropriate versions preventing `rustc` from downloading toolchains, we have to
+/// check
+/// 1. `$RUSTUP_TOOLCHAIN`
+/// 2. the directory overrides in rustup's `settings.toml`
+/// 3. `rust-toolchain` or `rust-toolchain.toml` in `.` or parent directories
+/// 4. the default toolchain in rustup's `settings.toml`
+///
+/// as `rustup` does.
+/// https://github.com/rust-lang/rustup.rs/tree/eb694fcada7becc5d9d160bf7c623abe84f8971d#override-precedence
+///
+/// Probably we have no other way to know whether any toolchain override is specified for the
+/// current directory. The following commands also cause toolchain installations.
+/// - `rustup show`
+/// - `rustup show active-toolchain`
+/// - `rustup which`
+fn get_rust_toolchain(context: &Context) -> Option<RustToolchain> {
+    let settings = read_rustup_settings(context);
+
+    let toolchain_override = env_rustup_toolchain(context)
+        .or_else(|| {
+            settings
+                .as_ref()?
+                .override_for(&context.current_dir)
+                .map(ToOwned::to_owned)
+        })
+        .or_else(|| find_rust_toolchain_file(context));
+
+    match toolchain_override {
+        Some(name) => Some(RustToolchain {
+            name,
+            is_override: true,
+        }),
+        None => settings?.default_toolchain.map(|name| RustToolchain {
+            name,
+            is_override: false,
+        }),
+    }
+}
+
+/// The toolchain rustup selects for the current directory
+struct RustToolchain {
+    name: String,
+    /// Whether the toolchain replaces rustup's default toolchain for the current directory
+    is_override: bool,
+}
+
+/// Gets the version of `toolchain_override`, or of the default toolchain if there's none
+fn get_module_version(context: &Context, toolchain_override: Option<&str>) -> Option<String> {
+    let toolchain = match toolchain_override {
+        Some(toolchain) => toolchain,
+        // rustc runs the default toolchain, which is installed
+        None => return Some(format_rustc_version(execute_rustc_version(context)?)),
+    };
+
+    // Toolchains pinned to a release are named after their version, so rustc is not needed
+    if let Some(version) = extract_toolchain_version(toolchain) {
+        return Some(version.to_owned());
+    }
+
+    let module_version = match execute_rustup_run_rustc_version(context, toolchain) {
+        RustupRunRustcVersionOutcome::RustcVersion(stdout) => format_rustc_version(stdout),
+        RustupRunRustcVersionOutcome::ToolchainName(toolchain) => toolchain,
+        RustupRunRustcVersionOutcome::RustupNotWorking => {
+            // If `rustup` is not in `$PATH`, cannot be executed or fails for other reasons, we
+            // can safely execute `rustc --version`.
+            format_rustc_version(execute_rustc_version(context)?)
         }
-    } else {
-        format_rustc_version(execute_rustc_version()?)
     };
 
     Some(module_version)
@@ -100,35 +169,67 @@ fn env_rustup_toolchain(context: &Context) -> Option<String> {
     Some(val.trim().to_owned())
 }
 
-fn execute_rustup_override_list(cwd: &Path) -> Option<String> {
-    let Output { stdout, .. } = Command::new("rustup")
-        .args(&["override", "list"])
-        .output()
-        .ok()?;
-    let stdout = String::from_utf8(stdout).ok()?;
-    extract_toolchain_from_rustup_override_list(&stdout, cwd)
+/// The parts of rustup's `settings.toml` that select a toolchain
+#[derive(Debug, Default, Deserialize, PartialEq)]
+struct RustupSettings {
+    default_toolchain: Option<String>,
+    #[serde(default)]
+    overrides: HashMap<String, String>,
 }
 
-fn extract_toolchain_from_rustup_override_list(stdout: &str, cwd: &Path) -> Option<String> {
-    if stdout == "no overrides\n" {
-        return None;
+impl RustupSettings {
+    /// The toolchain of the most specific directory override containing `cwd`
+    fn override_for(&self, cwd: &Path) -> Option<&str> {
+        self.overrides
+            .iter()
+            .filter(|(dir, _)| cwd.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.len())
+            .map(|(_, toolchain)| toolchain.as_str())
+    }
+}
+
+fn read_rustup_settings(context: &Context) -> Option<RustupSettings> {
+    let rustup_home = context
+        .get_env("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".rustup")))?;
+    let contents = utils::read_file(rustup_home.join("settings.toml")).ok()?;
+    parse_rustup_settings(&contents)
+}
+
+fn parse_rustup_settings(contents: &str) -> Option<RustupSettings> {
+    match toml::from_str(contents) {
+        Ok(settings) => Some(settings),
+        Err(error) => {
+            log::debug!("Unable to parse rustup settings: {}", error);
+            None
+        }
     }
-    stdout
-        .lines()
-        .flat_map(|line| {
-            let mut words = line.split_whitespace();
-            let dir = words.next()?;
-            let toolchain = words.next()?;
-            Some((dir, toolchain))
-        })
-        .find(|(dir, _)| cwd.starts_with(dir))
-        .map(|(_, toolchain)| toolchain.to_owned())
 }
 
 fn find_rust_toolchain_file(context: &Context) -> Option<String> {
-    // Look for 'rust-toolchain' as rustup does.
+    // Look for 'rust-toolchain' or 'rust-toolchain.toml' as rustup does.
     // https://github.com/rust-lang/rustup/blob/89912c4cf51645b9c152ab7380fd07574fec43a3/src/config.rs#L546-L616
 
+    let mut dir = &*context.current_dir;
+    loop {
+        if let Some(toolchain) = read_channel(&dir.join("rust-toolchain"), false)
+            .or_else(|| read_channel(&dir.join("rust-toolchain.toml"), true))
+        {
+            return Some(toolchain);
+        }
+        dir = dir.parent()?;
+    }
+}
+
+/// Reads the channel of a toolchain file. `rust-toolchain` may also contain just the name of
+/// the toolchain, while `rust-toolchain.toml` is always TOML.
+fn read_channel(path: &Path, only_toml: bool) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    parse_toolchain_file(&contents, only_toml)
+}
+
+fn parse_toolchain_file(contents: &str, only_toml: bool) -> Option<String> {
     #[derive(Deserialize)]
     struct OverrideFile {
         toolchain: ToolchainSection,
@@ -139,77 +240,88 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
         channel: Option<String>,
     }
 
-    fn read_channel(path: &Path) -> Option<String> {
-        let contents = fs::read_to_string(path).ok()?;
-
-        match contents.lines().count() {
-            0 => None,
-            1 => Some(contents),
-            _ => {
-                toml::from_str::<OverrideFile>(&contents)
-                    .ok()?
-                    .toolchain
-                    .channel
-            }
+    match contents.lines().count() {
+        0 => None,
+        1 if !only_toml => Some(contents.to_owned()),
+        _ => {
+            toml::from_str::<OverrideFile>(contents)
+                .ok()?
+                .toolchain
+                .channel
         }
-        .filter(|c| !c.trim().is_empty())
-        .map(|c| c.trim().to_owned())
     }
+    .filter(|c| !c.trim().is_empty())
+    .map(|c| c.trim().to_owned())
+}
 
-    if let Ok(true) = context
-        .dir_contents()
-        .map(|dir| dir.has_file("rust-toolchain"))
-    {
-        if let Some(toolchain) = read_channel(Path::new("rust-toolchain")) {
-            return Some(toolchain);
-        }
-    }
+/// Extracts the version of toolchains like `1.51.0` or `1.51.0-x86_64-unknown-linux-gnu`.
+/// Channels like `stable` or `1.51` don't pin a version.
+fn extract_toolchain_version(toolchain: &str) -> Option<&str> {
+    static TOOLCHAIN_VERSION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(\d+\.\d+\.\d+)(-|$)").unwrap());
+    TOOLCHAIN_VERSION
+        .captures(toolchain)
+        .and_then(|captures| captures.get(1))
+        .map(|version| version.as_str())
+}
 
-    let mut dir = &*context.current_dir;
-    loop {
-        if let Some(toolchain) = read_channel(&dir.join("rust-toolchain")) {
-            return Some(toolchain);
-        }
-        dir = dir.parent()?;
-    }
+/// Extracts the `major.minor.patch` part of a version like `1.52.0-nightly`
+fn extract_numeric_version(version: &str) -> Option<&str> {
+    static NUMERIC_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+\.\d+\.\d+").unwrap());
+    NUMERIC_VERSION
+        .find(version)
+        .map(|version| version.as_str())
 }
 
-fn execute_rustup_run_rustc_version(toolchain: &str) -> RustupRunRustcVersionOutcome {
-    Command::new("rustup")
-        .args(&["run", toolchain, "rustc", "--version"])
-        .output()
-        .map(extract_toolchain_from_rustup_run_rustc_version)
-        .unwrap_or(RustupRunRustcVersionOutcome::RustupNotWorking)
+fn execute_rustup_run_rustc_version(
+    context: &Context,
+    toolchain: &str,
+) -> RustupRunRustcVersionOutcome {
+    context
+        .exec_cmd_with_status("rustup", &["run", toolchain, "rustc", "--version"])
+        .map_or(
+            RustupRunRustcVersionOutcome::RustupNotWorking,
+            extract_toolchain_from_rustup_run_rustc_version,
+        )
 }
 
-fn extract_toolchain_from_rustup_run_rustc_version(output: Output) -> RustupRunRustcVersionOutcome {
-    if output.status.success() {
-        if let Ok(output) = String::from_utf8(output.stdout) {
-            return RustupRunRustcVersionOutcome::RustcVersion(output);
-        }
-    } else if let Ok(stderr) = String::from_utf8(output.stderr) {
-        if stderr.starts_with("error: toolchain '") && stderr.ends_with("' is not installed\n") {
-            let stderr = stderr
-                ["error: toolchain '".len()..stderr.len() - "' is not installed\n".len()]
-                .to_owned();
-            return RustupRunRustcVersionOutcome::ToolchainName(stderr);
-        }
+fn extract_toolchain_from_rustup_run_rustc_version(
+    output: CommandOutput,
+) -> RustupRunRustcVersionOutcome {
+    if output.success() {
+        return RustupRunRustcVersionOutcome::RustcVersion(output.stdout);
     }
-    RustupRunRustcVersionOutcome::Err
+    output
+        .stderr
+        .trim_end()
+        .strip_prefix("error: toolchain '")
+        .and_then(|stderr| stderr.strip_suffix("' is not installed"))
+        .map_or(
+            RustupRunRustcVersionOutcome::RustupNotWorking,
+            |toolchain| RustupRunRustcVersionOutcome::ToolchainName(toolchain.to_owned()),
+        )
 }
 
-fn execute_rustc_version() -> Option<String> {
-    match Command::new("rustc").arg("--version").output() {
-        Ok(output) => Some(String::from_utf8(output.stdout).unwrap()),
-        Err(_) => None,
-    }
+fn execute_rustc_version(context: &Context) -> Option<String> {
+    context
+        .exec_cmd("rustc", &["--version"])
+        .map(|output| output.stdout)
 }
 
 fn format_rustc_version(mut rustc_stdout: String) -> String {
     let offset = &rustc_stdout.find('(').unwrap_or_else(|| rustc_stdout.len());
     let formatted_version: String = rustc_stdout.drain(..offset).collect();
 
-    format!("v{}", formatted_version.replace("rustc", "").trim())
+    formatted_version.replace("rustc", "").trim().to_owned()
+}
+
+/// Applies `version_format` to a rustc version. A toolchain name shown in place of the version
+/// (because the toolchain is not installed) is left as it is.
+fn format_version(module_name: &str, version: &str, version_format: &str) -> String {
+    match extract_numeric_version(version) {
+        Some(_) => VersionFormatter::format_module_version(module_name, version, version_format),
+        None => version.to_owned(),
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -217,121 +329,147 @@ enum RustupRunRustcVersionOutcome {
     RustcVersion(String),
     ToolchainName(String),
     RustupNotWorking,
-    Err,
 }
 
 #[cfg(test)]
 mod tests {
-    use once_cell::sync::Lazy;
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use ansi_term::Color;
+    use std::fs::File;
     use std::io;
-    use std::process::{ExitStatus, Output};
 
     use super::*;
 
     #[test]
-    fn test_extract_toolchain_from_rustup_override_list() {
-        static NO_OVERRIDES_INPUT: &str = "no overrides\n";
-        static NO_OVERRIDES_CWD: &str = "";
+    fn test_rustup_settings_overrides() {
+        static SETTINGS: &str = r#"
+default_host_triple = "x86_64-unknown-linux-gnu"
+default_toolchain = "stable-x86_64-unknown-linux-gnu"
+profile = "default"
+version = "12"
+
+[overrides]
+"/home/user/src/a" = "beta-x86_64-unknown-linux-gnu"
+"/home/user/src/a/b" = "nightly-x86_64-unknown-linux-gnu"
+"#;
+        let settings = parse_rustup_settings(SETTINGS).unwrap();
         assert_eq!(
-            extract_toolchain_from_rustup_override_list(
-                NO_OVERRIDES_INPUT,
-                NO_OVERRIDES_CWD.as_ref(),
-            ),
-            None,
+            settings.default_toolchain.as_deref(),
+            Some("stable-x86_64-unknown-linux-gnu")
         );
+        assert_eq!(
+            settings.override_for("/home/user/src/a/src".as_ref()),
+            Some("beta-x86_64-unknown-linux-gnu"),
+        );
+        assert_eq!(
+            settings.override_for("/home/user/src/a/b/tests".as_ref()),
+            Some("nightly-x86_64-unknown-linux-gnu"),
+        );
+        assert_eq!(settings.override_for("/home/user/src/c".as_ref()), None);
+
+        let settings = parse_rustup_settings("version = \"12\"").unwrap();
+        assert_eq!(settings, RustupSettings::default());
 
-        static OVERRIDES_INPUT: &str =
-            "/home/user/src/a                                beta-x86_64-unknown-linux-gnu\n\
-             /home/user/src/b                                nightly-x86_64-unknown-linux-gnu\n";
-        static OVERRIDES_CWD_A: &str = "/home/user/src/a/src";
-        static OVERRIDES_CWD_B: &str = "/home/user/src/b/tests";
-        static OVERRIDES_CWD_C: &str = "/home/user/src/c/examples";
+        assert_eq!(parse_rustup_settings("[overrides"), None);
+    }
+
+    #[test]
+    fn test_parse_toolchain_file() {
         assert_eq!(
-            extract_toolchain_from_rustup_override_list(OVERRIDES_INPUT, OVERRIDES_CWD_A.as_ref()),
-            Some("beta-x86_64-unknown-linux-gnu".to_owned()),
+            parse_toolchain_file("nightly-2021-03-01\n", false),
+            Some("nightly-2021-03-01".to_owned())
         );
         assert_eq!(
-            extract_toolchain_from_rustup_override_list(OVERRIDES_INPUT, OVERRIDES_CWD_B.as_ref()),
-            Some("nightly-x86_64-unknown-linux-gnu".to_owned()),
+            parse_toolchain_file("[toolchain]\nchannel = \"1.34.0\"\n", true),
+            Some("1.34.0".to_owned())
         );
         assert_eq!(
-            extract_toolchain_from_rustup_override_list(OVERRIDES_INPUT, OVERRIDES_CWD_C.as_ref()),
-            None,
+            parse_toolchain_file(
+                "[toolchain]\nchannel = \"nightly\"\ncomponents = [\"rustfmt\"]\n",
+                false
+            ),
+            Some("nightly".to_owned())
         );
+        // `rust-toolchain.toml` can't hold a bare toolchain name
+        assert_eq!(parse_toolchain_file("stable", true), None);
+        assert_eq!(
+            parse_toolchain_file("[toolchain]\ncomponents = [\"rustfmt\"]\n", true),
+            None
+        );
+        assert_eq!(
+            parse_toolchain_file("[toolchain]\nchannel = \"\"\n", true),
+            None
+        );
+        assert_eq!(parse_toolchain_file("", false), None);
     }
 
-    #[cfg(any(unix, windows))]
     #[test]
-    fn test_extract_toolchain_from_rustup_run_rustc_version() {
-        #[cfg(unix)]
-        use std::os::unix::process::ExitStatusExt as _;
-        #[cfg(windows)]
-        use std::os::windows::process::ExitStatusExt as _;
-
-        static RUSTC_VERSION: Lazy<Output> = Lazy::new(|| Output {
-            status: ExitStatus::from_raw(0),
-            stdout: b"rustc 1.34.0\n"[..].to_owned(),
-            stderr: vec![],
-        });
+    fn test_extract_toolchain_version() {
+        assert_eq!(extract_toolchain_version("1.51.0"), Some("1.51.0"));
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(RUSTC_VERSION.clone()),
-            RustupRunRustcVersionOutcome::RustcVersion("rustc 1.34.0\n".to_owned()),
+            extract_toolchain_version("1.51.0-x86_64-unknown-linux-gnu"),
+            Some("1.51.0")
         );
+        assert_eq!(extract_toolchain_version("1.51"), None);
+        assert_eq!(extract_toolchain_version("stable"), None);
+        assert_eq!(extract_toolchain_version("nightly-2021-03-01"), None);
+    }
 
-        static TOOLCHAIN_NAME: Lazy<Output> = Lazy::new(|| Output {
-            status: ExitStatus::from_raw(1),
-            stdout: vec![],
-            stderr: b"error: toolchain 'channel-triple' is not installed\n"[..].to_owned(),
-        });
-        assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(TOOLCHAIN_NAME.clone()),
-            RustupRunRustcVersionOutcome::ToolchainName("channel-triple".to_owned()),
-        );
+    #[test]
+    fn test_extract_numeric_version() {
+        assert_eq!(extract_numeric_version("1.52.0-nightly"), Some("1.52.0"));
+        assert_eq!(extract_numeric_version("1.51.0"), Some("1.51.0"));
+        assert_eq!(extract_numeric_version("nightly"), None);
+    }
 
-        static INVALID_STDOUT: Lazy<Output> = Lazy::new(|| Output {
-            status: ExitStatus::from_raw(0),
-            stdout: b"\xc3\x28"[..].to_owned(),
-            stderr: vec![],
-        });
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_extract_toolchain_from_rustup_run_rustc_version() {
+        let rustc_version = CommandOutput {
+            stdout: "rustc 1.34.0".to_owned(),
+            stderr: String::default(),
+            status: Some(0),
+        };
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDOUT.clone()),
-            RustupRunRustcVersionOutcome::Err,
+            extract_toolchain_from_rustup_run_rustc_version(rustc_version),
+            RustupRunRustcVersionOutcome::RustcVersion("rustc 1.34.0".to_owned()),
         );
 
-        static INVALID_STDERR: Lazy<Output> = Lazy::new(|| Output {
-            status: ExitStatus::from_raw(1),
-            stdout: vec![],
-            stderr: b"\xc3\x28"[..].to_owned(),
-        });
+        let toolchain_name = CommandOutput {
+            stdout: String::default(),
+            stderr: "error: toolchain 'channel-triple' is not installed".to_owned(),
+            status: Some(1),
+        };
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(INVALID_STDERR.clone()),
-            RustupRunRustcVersionOutcome::Err,
+            extract_toolchain_from_rustup_run_rustc_version(toolchain_name),
+            RustupRunRustcVersionOutcome::ToolchainName("channel-triple".to_owned()),
         );
 
-        static UNEXPECTED_FORMAT_OF_ERROR: Lazy<Output> = Lazy::new(|| Output {
-            status: ExitStatus::from_raw(1),
-            stdout: vec![],
-            stderr: b"error:"[..].to_owned(),
-        });
+        let unexpected_format_of_error = CommandOutput {
+            stdout: String::default(),
+            stderr: "error:".to_owned(),
+            status: Some(1),
+        };
         assert_eq!(
-            extract_toolchain_from_rustup_run_rustc_version(UNEXPECTED_FORMAT_OF_ERROR.clone()),
-            RustupRunRustcVersionOutcome::Err,
+            extract_toolchain_from_rustup_run_rustc_version(unexpected_format_of_error),
+            RustupRunRustcVersionOutcome::RustupNotWorking,
         );
     }
 
     #[test]
     fn test_format_rustc_version() {
         let nightly_input = String::from("rustc 1.34.0-nightly (b139669f3 2019-04-10)");
-        assert_eq!(format_rustc_version(nightly_input), "v1.34.0-nightly");
+        assert_eq!(format_rustc_version(nightly_input), "1.34.0-nightly");
 
         let beta_input = String::from("rustc 1.34.0-beta.1 (2bc1d406d 2019-04-10)");
-        assert_eq!(format_rustc_version(beta_input), "v1.34.0-beta.1");
+        assert_eq!(format_rustc_version(beta_input), "1.34.0-beta.1");
 
         let stable_input = String::from("rustc 1.34.0 (91856ed52 2019-04-10)");
-        assert_eq!(format_rustc_version(stable_input), "v1.34.0");
+        assert_eq!(format_rustc_version(stable_input), "1.34.0");
 
         let version_without_hash = String::from("rustc 1.34.0");
-        assert_eq!(format_rustc_version(version_without_hash), "v1.34.0");
+        assert_eq!(format_rustc_version(version_without_hash), "1.34.0");
     }
 
     #[test]
@@ -373,6 +511,268 @@ mod tests {
             find_rust_toolchain_file(&context),
             Some("1.34.0".to_owned())
         );
+        dir.close()?;
+
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"nightly\"",
+        )?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        let context = Context::new_with_dir(Default::default(), &sub_dir);
+
+        assert_eq!(
+            find_rust_toolchain_file(&context),
+            Some("nightly".to_owned())
+        );
+        dir.close()
+    }
+
+    fn rustc_version(version: &str) -> Option<CommandOutput> {
+        Some(CommandOutput {
+            stdout: format!("{}\n", version),
+            stderr: String::default(),
+            status: Some(0),
+        })
+    }
+
+    #[test]
+    fn rustc_version_without_rustup() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.rs"))?.sync_all()?;
+        let rustup_home = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd(
+                "rustc --version",
+                rustc_version("rustc 1.52.0-nightly (152f66092 2021-02-17)"),
+            )
+            .config(toml::toml! {
+                [rust]
+                format = "[$version $numver( $toolchain)]($style)"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Red.bold().paint("v1.52.0-nightly 1.52.0")
+        ));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn rustup_toolchain_env_skips_rustc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_TOOLCHAIN", "1.51.0-x86_64-unknown-linux-gnu")
+            .cmd("rustc --version", None)
+            .config(toml::toml! {
+                [rust]
+                format = "[$version \\($toolchain\\)]($style)"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Red
+                .bold()
+                .paint("v1.51.0 (1.51.0-x86_64-unknown-linux-gnu)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn toolchain_file_skips_rustc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.49.0\"\n",
+        )?;
+        let rustup_home = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd("rustc --version", None)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🦀 v1.49.0")));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn rustup_settings_override() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        fs::write(
+            dir.path().join("rust-toolchain"),
+            "[toolchain]\nchannel = \"1.49.0\"\n",
+        )?;
+        let rustup_home = tempfile::tempdir()?;
+        let mut settings = toml::value::Table::new();
+        settings.insert("default_toolchain".to_owned(), "stable".into());
+        let mut overrides = toml::value::Table::new();
+        overrides.insert(
+            dir.path().to_str().unwrap().to_owned(),
+            "1.50.0-x86_64-unknown-linux-gnu".into(),
+        );
+        settings.insert("overrides".to_owned(), overrides.into());
+        fs::write(
+            rustup_home.path().join("settings.toml"),
+            toml::to_string(&settings).unwrap(),
+        )?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd("rustc --version", None)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🦀 v1.50.0")));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    /// Writes rustup's `settings.toml` with `default_toolchain` to a new `RUSTUP_HOME`
+    fn rustup_home_with_default(default_toolchain: &str) -> io::Result<tempfile::TempDir> {
+        let rustup_home = tempfile::tempdir()?;
+        fs::write(
+            rustup_home.path().join("settings.toml"),
+            format!("default_toolchain = \"{}\"\n", default_toolchain),
+        )?;
+        Ok(rustup_home)
+    }
+
+    #[test]
+    fn default_toolchain_runs_rustc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        let rustup_home = rustup_home_with_default("stable-x86_64-unknown-linux-gnu")?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd(
+                "rustc --version",
+                rustc_version("rustc 1.52.0 (88f19c6da 2021-05-03)"),
+            )
+            .cmd(
+                "rustup run stable-x86_64-unknown-linux-gnu rustc --version",
+                rustc_version("rustc 1.40.0 (73528e339 2019-12-16)"),
+            )
+            .config(toml::toml! {
+                [rust]
+                format = "[$version \\($toolchain\\)]($style)"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Red
+                .bold()
+                .paint("v1.52.0 (stable-x86_64-unknown-linux-gnu)")
+        ));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn toolchain_file_channel_runs_rustup() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        fs::write(dir.path().join("rust-toolchain"), "nightly\n")?;
+        let rustup_home = rustup_home_with_default("stable")?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd("rustc --version", None)
+            .cmd(
+                "rustup run nightly rustc --version",
+                rustc_version("rustc 1.53.0-nightly (07e0e2ec2 2021-03-24)"),
+            )
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🦀 v1.53.0-nightly")
+        ));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn toolchain_not_installed_shows_its_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        fs::write(dir.path().join("rust-toolchain"), "nightly\n")?;
+        let rustup_home = rustup_home_with_default("stable")?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd("rustc --version", None)
+            .cmd(
+                "rustup run nightly rustc --version",
+                Some(CommandOutput {
+                    stdout: String::default(),
+                    stderr: "error: toolchain 'nightly' is not installed".to_owned(),
+                    status: Some(1),
+                }),
+            )
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🦀 nightly")));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn failing_rustup_falls_back_to_rustc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Cargo.toml"))?.sync_all()?;
+        fs::write(dir.path().join("rust-toolchain"), "nightly\n")?;
+        let rustup_home = rustup_home_with_default("stable")?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_str().unwrap())
+            .cmd(
+                "rustc --version",
+                rustc_version("rustc 1.53.0-nightly (07e0e2ec2 2021-03-24)"),
+            )
+            .cmd(
+                "rustup run nightly rustc --version",
+                Some(CommandOutput {
+                    stdout: String::default(),
+                    stderr: "error: could not read settings".to_owned(),
+                    status: Some(1),
+                }),
+            )
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🦀 v1.53.0-nightly")
+        ));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
         dir.close()
     }
 }
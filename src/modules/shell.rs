@@ -0,0 +1,141 @@
+use super::{Context, Module, RootModuleConfig, Shell};
+
+use crate::configs::shell::ShellConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module showing which shell the prompt is rendered for
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("shell");
+    let config: ShellConfig = ShellConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let indicator = match context.shell {
+        Shell::Bash => config.bash_indicator,
+        Shell::Fish => config.fish_indicator,
+        Shell::Ion => config.ion_indicator,
+        Shell::Nu => config.nu_indicator,
+        Shell::PowerShell => config.powershell_indicator,
+        Shell::Tcsh => config.tcsh_indicator,
+        Shell::Zsh => config.zsh_indicator,
+        Shell::Unknown => config.unknown_indicator,
+    };
+    if indicator.is_empty() {
+        return None;
+    }
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "indicator" => Some(indicator),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `shell`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Shell;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::io;
+
+    fn render(shell: Shell) -> Option<String> {
+        ModuleRenderer::new("shell")
+            .shell(shell)
+            .config(toml::toml! {
+                [shell]
+                disabled = false
+            })
+            .collect()
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("shell").shell(Shell::Bash).collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn default_indicators() -> io::Result<()> {
+        let shells = [
+            (Shell::Bash, "bsh"),
+            (Shell::Fish, "fsh"),
+            (Shell::Ion, "ion"),
+            (Shell::Nu, "nu"),
+            (Shell::PowerShell, "psh"),
+            (Shell::Tcsh, "tsh"),
+            (Shell::Zsh, "zsh"),
+        ];
+        for (shell, indicator) in shells.iter() {
+            let expected = Some(format!("{} ", Color::White.bold().paint(*indicator)));
+            assert_eq!(expected, render(*shell));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_shell_has_no_indicator() -> io::Result<()> {
+        let actual = render(Shell::Unknown);
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_indicators_and_style() -> io::Result<()> {
+        let config = toml::toml! {
+            [shell]
+            disabled = false
+            bash_indicator = "[BASH](red)"
+            nu_indicator = "nushell"
+            unknown_indicator = "?"
+            style = "cyan"
+        };
+
+        let actual = ModuleRenderer::new("shell")
+            .shell(Shell::Bash)
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.paint("BASH")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("shell")
+            .shell(Shell::Nu)
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.paint("nushell")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("shell")
+            .shell(Shell::Unknown)
+            .config(config)
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.paint("?")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+}
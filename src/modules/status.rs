@@ -5,50 +5,98 @@ use crate::formatter::StringFormatter;
 
 /// Creates a module with the status of the last command
 ///
-/// Will display the status only if it is not 0
+/// Will display the status only if it is not 0, unless `success_symbol` is set
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let exit_code = context
         .properties
         .get("status_code")
         .map_or("0", String::as_str);
 
-    if exit_code == "0" {
-        None
+    let mut module = context.new_module("status");
+    let config = StatusConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    // The status codes of the pipeline, if it had more than one command
+    let pipestatus = context
+        .properties
+        .get("pipestatus")
+        .map(|pipestatus| pipestatus.split_whitespace().collect::<Vec<&str>>())
+        .filter(|pipestatus| config.pipestatus && pipestatus.len() > 1);
+    let is_success = match &pipestatus {
+        Some(pipestatus) => pipestatus.iter().all(|code| *code == "0"),
+        None => exit_code == "0",
+    };
+
+    let symbol = if !is_success {
+        config.symbol
+    } else if !config.success_symbol.is_empty() {
+        config.success_symbol
     } else {
-        let mut module = context.new_module("status");
-        let config = StatusConfig::try_load(module.config);
+        return None;
+    };
+    let common_meaning = exit_code.parse::<i32>().ok().and_then(common_meaning);
+    let (format, format_name) = match pipestatus {
+        Some(_) => (config.pipestatus_format, "pipestatus_format"),
+        None => (config.format, "format"),
+    };
 
-        // As we default to disabled=true, we have to check here after loading our config module,
-        // before it was only checking against whatever is in the config starship.toml
-        if config.disabled {
+    let parsed = StringFormatter::new_with_context(format, context).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "status" => Some(Ok(exit_code.to_owned())),
+                "common_meaning" => common_meaning.map(|meaning| Ok(meaning.to_owned())),
+                "pipestatus" => pipestatus
+                    .as_ref()
+                    .map(|pipestatus| Ok(pipestatus.join(config.pipestatus_separator))),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!(
+                "Error parsing format string in `status.{}`:\n{}",
+                format_name,
+                error
+            );
             return None;
-        };
+        }
+    });
+    Some(module)
+}
 
-        let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-            formatter
-                .map_meta(|var, _| match var {
-                    "symbol" => Some(config.symbol),
-                    _ => None,
-                })
-                .map_style(|variable| match variable {
-                    "style" => Some(Ok(config.style)),
-                    _ => None,
-                })
-                .map(|variable| match variable {
-                    "status" => Some(Ok(exit_code)),
-                    _ => None,
-                })
-                .parse(None)
-        });
-
-        module.set_segments(match parsed {
-            Ok(segments) => segments,
-            Err(_error) => {
-                log::warn!("Error parsing format string in `status.format`");
-                return None;
-            }
-        });
-        Some(module)
+/// The name of exit codes that shells and common tools give a meaning to. Codes above 128
+/// are used by shells when a command was terminated by the signal `code - 128`.
+fn common_meaning(code: i32) -> Option<&'static str> {
+    match code {
+        1 => Some("ERROR"),
+        2 => Some("USAGE"),
+        126 => Some("NOT EXECUTABLE"),
+        127 => Some("NOT FOUND"),
+        129 => Some("SIGHUP"),
+        130 => Some("SIGINT"),
+        131 => Some("SIGQUIT"),
+        134 => Some("SIGABRT"),
+        137 => Some("SIGKILL"),
+        139 => Some("SIGSEGV"),
+        141 => Some("SIGPIPE"),
+        143 => Some("SIGTERM"),
+        _ => None,
     }
 }
 
@@ -101,7 +149,7 @@ mod tests {
         for status in exit_values.iter() {
             let expected = Some(format!(
                 "{} ",
-                Color::Red.bold().paint(format!("✖{}", status))
+                Color::Red.bold().paint(format!("✖ {}", status))
             ));
             let actual = ModuleRenderer::new("status")
                 .config(toml::toml! {
@@ -115,4 +163,130 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn success_symbol_shows_zero_status() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("✔ 0")));
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                success_symbol = "✔"
+                disabled = false
+            })
+            .status(0)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn common_meaning_of_status() {
+        let statuses = [
+            (1, "ERROR"),
+            (126, "NOT EXECUTABLE"),
+            (127, "NOT FOUND"),
+            (130, "SIGINT"),
+            (137, "SIGKILL"),
+        ];
+
+        for (status, meaning) in statuses.iter() {
+            let expected = Some(format!("{} {}", status, meaning));
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "$status( $common_meaning)"
+                    disabled = false
+                })
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn unknown_status_has_no_common_meaning() {
+        let expected = Some("42".to_owned());
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$status( $common_meaning)"
+                disabled = false
+            })
+            .status(42)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn pipestatus_shows_every_status_code() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("✖ [0|1|0]")));
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[0, 1, 0])
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn pipestatus_format_has_overall_status() {
+        let expected = Some("0 1 127 => 127 NOT FOUND".to_owned());
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                pipestatus_separator = " "
+                pipestatus_format = "$pipestatus => $status $common_meaning"
+                disabled = false
+            })
+            .status(127)
+            .pipestatus(&[0, 1, 127])
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn pipestatus_of_successful_pipeline_is_hidden() {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[0, 0])
+            .collect();
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn single_pipestatus_uses_plain_format() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("✖ 1")));
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                disabled = false
+            })
+            .status(1)
+            .pipestatus(&[1])
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn pipestatus_is_ignored_unless_enabled() {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[0, 1, 0])
+            .collect();
+        assert_eq!(None, actual);
+    }
 }
@@ -0,0 +1,143 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::sudo::SudoConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with sudo credential cache status
+///
+/// Will display if sudo has cached credentials, so running `sudo` won't prompt for a password
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("sudo");
+    let config: SudoConfig = SudoConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    if cfg!(windows) && !config.allow_windows {
+        return None;
+    }
+
+    if !context.is_sudo_cached() {
+        return None;
+    }
+
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `sudo`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use ansi_term::Color;
+    use std::io;
+
+    fn cached() -> Option<CommandOutput> {
+        Some(CommandOutput {
+            stdout: String::default(),
+            stderr: String::default(),
+            status: Some(0),
+        })
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("sudo")
+            .cmd("sudo -n true", cached())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn credentials_cached() -> io::Result<()> {
+        let actual = ModuleRenderer::new("sudo")
+            .cmd("sudo -n true", cached())
+            .config(toml::toml! {
+                [sudo]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Blue.bold().paint("as 🧙 ")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn credentials_not_cached() -> io::Result<()> {
+        let actual = ModuleRenderer::new("sudo")
+            .cmd("sudo -n true", None)
+            .config(toml::toml! {
+                [sudo]
+                disabled = false
+                allow_windows = true
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn custom_symbol_and_style() -> io::Result<()> {
+        let actual = ModuleRenderer::new("sudo")
+            .cmd("sudo -n true", cached())
+            .config(toml::toml! {
+                [sudo]
+                disabled = false
+                format = "[$symbol]($style) "
+                symbol = "#"
+                style = "red"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.paint("#")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn hidden_on_windows_unless_allowed() -> io::Result<()> {
+        let actual = ModuleRenderer::new("sudo")
+            .cmd("sudo -n true", cached())
+            .config(toml::toml! {
+                [sudo]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::swift::SwiftConfig;
-use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current Swift version
 ///
@@ -10,21 +9,23 @@ use crate::utils;
 ///     - The current directory contains a `Package.swift` file
 ///     - The current directory contains a file with extension `.swift`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("swift");
+    let config: SwiftConfig = SwiftConfig::try_load(module.config);
+
     let is_swift_project = context
         .try_begin_scan()?
-        .set_extensions(&["swift"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_swift_project {
         return None;
     }
 
-    let swift_version = utils::exec_cmd("swift", &["--version"])?.stdout;
+    let swift_version = context.exec_cmd("swift", &["--version"])?.stdout;
 
-    let mut module = context.new_module("swift");
-    let config: SwiftConfig = SwiftConfig::try_load(module.config);
-
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
@@ -35,7 +36,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => parse_swift_version(&swift_version).map(Ok),
+                "version" => parse_swift_version(&swift_version)
+                    .map(|version| {
+                        VersionFormatter::format_module_version(
+                            module.get_name(),
+                            version,
+                            config.version_format,
+                        )
+                    })
+                    .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -52,15 +61,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn parse_swift_version(swift_version: &str) -> Option<String> {
+fn parse_swift_version(swift_version: &str) -> Option<&str> {
     // split into ["Apple", "Swift", "version", "5.2.2", ...] or
     //            ["Swift", "version", "5.3-dev", ...]
     let mut splited = swift_version.split_whitespace();
     let _ = splited.position(|t| t == "version")?;
     // return "5.2.2" or "5.3-dev"
-    let version = splited.next()?;
-
-    Some(format!("v{}", version))
+    splited.next()
 }
 
 #[cfg(test)]
@@ -74,13 +81,13 @@ mod tests {
     #[test]
     fn test_parse_swift_version() {
         let input = "Apple Swift version 5.2.2";
-        assert_eq!(parse_swift_version(input), Some(String::from("v5.2.2")));
+        assert_eq!(parse_swift_version(input), Some("5.2.2"));
     }
 
     #[test]
     fn test_parse_swift_version_without_org_name() {
         let input = "Swift version 5.3-dev (LLVM ..., Swift ...)";
-        assert_eq!(parse_swift_version(input), Some(String::from("v5.3-dev")));
+        assert_eq!(parse_swift_version(input), Some("5.3-dev"));
     }
 
     #[test]
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::terraform::TerraformConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use std::io;
@@ -13,20 +13,21 @@ use std::path::PathBuf;
 ///     - Current directory contains a `.terraform` directory
 ///     - Current directory contains a file with the `.tf` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("terraform");
+    let config: TerraformConfig = TerraformConfig::try_load(module.config);
+
     let is_terraform_project = context
         .try_begin_scan()?
-        .set_folders(&[".terraform"])
-        .set_extensions(&["tf", "hcl"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_terraform_project {
         return None;
     }
 
-    let mut module = context.new_module("terraform");
-    let config: TerraformConfig = TerraformConfig::try_load(module.config);
-
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "symbol" => Some(config.symbol),
@@ -37,10 +38,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => format_terraform_version(
-                    &utils::exec_cmd("terraform", &["version"])?.stdout.as_str(),
-                )
-                .map(Ok),
+                "version" => {
+                    let terraform_version = context.exec_cmd("terraform", &["version"])?.stdout;
+                    // The version is followed by a space to separate it from the workspace
+                    Some(Ok(format!(
+                        "{} ",
+                        VersionFormatter::format_module_version(
+                            module.get_name(),
+                            parse_terraform_version(&terraform_version)?,
+                            config.version_format,
+                        )
+                    )))
+                }
                 "workspace" => get_terraform_workspace(context).map(Ok),
                 _ => None,
             })
@@ -78,7 +87,7 @@ fn get_terraform_workspace(context: &Context) -> Option<String> {
     }
 }
 
-fn format_terraform_version(version: &str) -> Option<String> {
+fn parse_terraform_version(version: &str) -> Option<&str> {
     // `terraform version` output looks like this
     // Terraform v0.12.14
     // With potential extra output if it detects you are not running the latest version
@@ -88,8 +97,7 @@ fn format_terraform_version(version: &str) -> Option<String> {
             .next()?
             .trim_start_matches("Terraform ")
             .trim()
-            .to_owned()
-            + " ",
+            .trim_start_matches('v'),
     )
 }
 
@@ -102,44 +110,35 @@ mod tests {
     use std::io::{self, Write};
 
     #[test]
-    fn test_format_terraform_version_release() {
+    fn test_parse_terraform_version_release() {
         let input = "Terraform v0.12.14";
-        assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14 ".to_string())
-        );
+        assert_eq!(parse_terraform_version(input), Some("0.12.14"));
     }
 
     #[test]
-    fn test_format_terraform_version_prerelease() {
+    fn test_parse_terraform_version_prerelease() {
         let input = "Terraform v0.12.14-rc1";
-        assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14-rc1 ".to_string())
-        );
+        assert_eq!(parse_terraform_version(input), Some("0.12.14-rc1"));
     }
 
     #[test]
-    fn test_format_terraform_version_development() {
+    fn test_parse_terraform_version_development() {
         let input = "Terraform v0.12.14-dev (cca89f74)";
         assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14-dev (cca89f74) ".to_string())
+            parse_terraform_version(input),
+            Some("0.12.14-dev (cca89f74)")
         );
     }
 
     #[test]
-    fn test_format_terraform_version_multiline() {
+    fn test_parse_terraform_version_multiline() {
         let input = "Terraform v0.12.13
 
 Your version of Terraform is out of date! The latest version
 is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
 
 ";
-        assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.13 ".to_string())
-        );
+        assert_eq!(parse_terraform_version(input), Some("0.12.13"));
     }
 
     #[test]
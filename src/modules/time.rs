@@ -15,13 +15,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
-    // Hide prompt if current time is not inside time_range
-    let (display_start, display_end) = parse_time_range(config.time_range);
-    let time_now = Local::now().time();
-    if !is_inside_time_range(time_now, display_start, display_end) {
-        return None;
-    }
-
     let default_format = if config.use_12hr { "%r" } else { "%T" };
     let time_format = config.time_format.unwrap_or(default_format);
 
@@ -30,21 +23,34 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         time_format
     );
 
-    let formatted_time_string = if config.utc_time_offset != "local" {
-        match create_offset_time_string(Utc::now(), &config.utc_time_offset, &time_format) {
-            Ok(formatted_string) => formatted_string,
+    let local_time = context.now.with_timezone(&Local);
+    let offset_time = if config.utc_time_offset != "local" {
+        match create_offset_time(context.now, config.utc_time_offset) {
+            Ok(offset_time) => Some(offset_time),
             Err(_) => {
                 log::warn!(
                     "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
                 );
-                format_time(&time_format, Local::now())
+                None
             }
         }
     } else {
-        format_time(&time_format, Local::now())
+        None
+    };
+
+    // Hide prompt if current time is not inside time_range
+    let (display_start, display_end) = parse_time_range(config.time_range);
+    let time_now = offset_time.map_or_else(|| local_time.time(), |time| time.time());
+    if !is_inside_time_range(time_now, display_start, display_end) {
+        return None;
+    }
+
+    let formatted_time_string = match offset_time {
+        Some(offset_time) => format_time_fixed_offset(time_format, offset_time),
+        None => format_time(time_format, local_time),
     };
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
@@ -68,11 +74,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn create_offset_time_string(
+fn create_offset_time(
     utc_time: DateTime<Utc>,
     utc_time_offset_str: &str,
-    time_format: &str,
-) -> Result<String, &'static str> {
+) -> Result<DateTime<FixedOffset>, &'static str> {
     // Using floats to allow 30/45 minute offsets: https://www.timeanddate.com/time/time-zones-interesting.html
     let utc_time_offset_in_hours = utc_time_offset_str.parse::<f32>().unwrap_or(
         // Passing out of range value to force falling back to "local"
@@ -86,7 +91,7 @@ fn create_offset_time_string(
         let target_time = utc_time.with_timezone(&timezone_offset);
         log::trace!("Time in target timezone now is {}", target_time);
 
-        Ok(format_time_fixed_offset(&time_format, target_time))
+        Ok(target_time)
     } else {
         Err("Invalid timezone offset.")
     }
@@ -154,6 +159,7 @@ tests become extra important */
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
+    use ansi_term::Color;
     use chrono::offset::TimeZone;
     use std::io;
 
@@ -291,7 +297,10 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-3";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual = format_time_fixed_offset(
+            FMT_12,
+            create_offset_time(utc_time, utc_time_offset_str).unwrap(),
+        );
         assert_eq!(actual, "12:36:47 PM");
     }
 
@@ -300,7 +309,10 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+5";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual = format_time_fixed_offset(
+            FMT_12,
+            create_offset_time(utc_time, utc_time_offset_str).unwrap(),
+        );
         assert_eq!(actual, "08:36:47 PM");
     }
 
@@ -309,7 +321,10 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+9.5";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual = format_time_fixed_offset(
+            FMT_12,
+            create_offset_time(utc_time, utc_time_offset_str).unwrap(),
+        );
         assert_eq!(actual, "01:06:47 AM");
     }
 
@@ -318,7 +333,10 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+5.75";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual = format_time_fixed_offset(
+            FMT_12,
+            create_offset_time(utc_time, utc_time_offset_str).unwrap(),
+        );
         assert_eq!(actual, "09:21:47 PM");
     }
 
@@ -327,7 +345,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+24";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time(utc_time, utc_time_offset_str)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -337,7 +355,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-24";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time(utc_time, utc_time_offset_str)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -347,7 +365,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+9001";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time(utc_time, utc_time_offset_str)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -357,7 +375,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-4242";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time(utc_time, utc_time_offset_str)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -367,7 +385,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "completely wrong config";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time(utc_time, utc_time_offset_str)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -478,7 +496,7 @@ mod tests {
             })
             .collect();
 
-        // We can't test what it actually is...but we can assert that it is something
+        // The time depends on the local timezone, but it is always shown
         assert!(actual.is_some());
         Ok(())
     }
@@ -514,4 +532,126 @@ mod tests {
         assert!(actual.ends_with(&col_suffix));
         Ok(())
     }
+
+    #[test]
+    fn time_24hr_with_offset() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(15, 36, 47))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                utc_time_offset = "-3"
+            })
+            .collect();
+        let expected = Some(format!("at {} ", Color::Yellow.bold().paint("12:36:47")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_12hr_with_offset() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(15, 36, 47))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                use_12hr = true
+                utc_time_offset = "+5"
+            })
+            .collect();
+        let expected = Some(format!("at {} ", Color::Yellow.bold().paint("08:36:47 PM")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_with_half_hour_offset() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(15, 36, 47))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                utc_time_offset = "+5.5"
+            })
+            .collect();
+        let expected = Some(format!("at {} ", Color::Yellow.bold().paint("21:06:47")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_format_overrides_use_12hr() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(15, 36, 47))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                use_12hr = true
+                utc_time_offset = "0"
+                time_format = "%Y-%m-%d %H:%M"
+            })
+            .collect();
+        let expected = Some(format!(
+            "at {} ",
+            Color::Yellow.bold().paint("2014-07-08 15:36")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_inside_time_range() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(10, 0, 1))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                utc_time_offset = "0"
+                time_range = "10:00:00-14:00:00"
+            })
+            .collect();
+        let expected = Some(format!("at {} ", Color::Yellow.bold().paint("10:00:01")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_at_time_range_end_is_hidden() -> io::Result<()> {
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(14, 0, 0))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                utc_time_offset = "0"
+                time_range = "10:00:00-14:00:00"
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn time_range_uses_the_offset_time() -> io::Result<()> {
+        // 08:30 UTC is 14:00 at +5.5, past the end of the range
+        let actual = ModuleRenderer::new("time")
+            .time(Utc.ymd(2014, 7, 8).and_hms(8, 30, 0))
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                utc_time_offset = "+5.5"
+                time_range = "10:00:00-14:00:00"
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
@@ -2,7 +2,6 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::username::UsernameConfig;
 use crate::formatter::StringFormatter;
-use crate::utils;
 
 /// Creates a module with the current user's username
 ///
@@ -10,36 +9,40 @@ use crate::utils;
 ///     - The current user isn't the same as the one that is logged in (`$LOGNAME` != `$USER`)
 ///     - The current user is root (UID = 0)
 ///     - The user is currently connected as an SSH session (`$SSH_CONNECTION`)
+///     - `show_always` is set
+///
+/// When `$USER` is unset, `$LOGNAME` is used as the username.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let user = context.get_env("USER");
     let logname = context.get_env("LOGNAME");
+    let user = context.get_env("USER").or_else(|| logname.clone());
 
     const ROOT_UID: Option<u32> = Some(0);
-    let user_uid = get_uid();
+    let user_uid = context.uid;
 
     let mut module = context.new_module("username");
     let config: UsernameConfig = UsernameConfig::try_load(module.config);
 
-    if user != logname || is_ssh_connection(&context) || user_uid == ROOT_UID || config.show_always {
+    if user != logname || context.is_ssh_session() || user_uid == ROOT_UID || config.show_always {
         let username = user?;
-        let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-            formatter
-                .map_style(|variable| match variable {
-                    "style" => {
-                        let module_style = match user_uid {
-                            Some(0) => config.style_root,
-                            _ => config.style_user,
-                        };
-                        Some(Ok(module_style))
-                    }
-                    _ => None,
-                })
-                .map(|variable| match variable {
-                    "user" => Some(Ok(&username)),
-                    _ => None,
-                })
-                .parse(None)
-        });
+        let parsed =
+            StringFormatter::new_with_context(config.format, context).and_then(|formatter| {
+                formatter
+                    .map_style(|variable| match variable {
+                        "style" => {
+                            let module_style = match user_uid {
+                                Some(0) => config.style_root,
+                                _ => config.style_user,
+                            };
+                            Some(Ok(module_style))
+                        }
+                        _ => None,
+                    })
+                    .map(|variable| match variable {
+                        "user" => Some(Ok(&username)),
+                        _ => None,
+                    })
+                    .parse(None)
+            });
         module.set_segments(match parsed {
             Ok(segments) => segments,
             Err(error) => {
@@ -54,29 +57,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn is_ssh_connection(context: &Context) -> bool {
-    let ssh_env: Vec<&str> = vec!["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY", "SSH_AUTH_SOCK"];
-    return ssh_env.into_iter()
-                  .any(|env| context.get_env(env).is_some())
-}
-
-fn get_uid() -> Option<u32> {
-    utils::exec_cmd("id", &["-u"])?
-        .stdout
-        .trim()
-        .parse::<u32>()
-        .ok()
-}
-
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::io;
 
-    // TODO: Add tests for if root user (UID == 0)
-    // Requires mocking
-
     #[test]
     fn no_env_variables() -> io::Result<()> {
         let actual = ModuleRenderer::new("username").collect();
@@ -146,6 +132,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ssh_agent_is_not_an_ssh_session() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .env("USER", "astronaut")
+            .env("SSH_AUTH_SOCK", "/tmp/ssh-agent.sock")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn show_always() -> io::Result<()> {
         let actual = ModuleRenderer::new("username")
@@ -160,4 +159,73 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn user_unset_falls_back_to_logname() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Yellow.bold().paint("astronaut")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn user_unset_is_not_a_different_user() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn root_user() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "root")
+            .env("USER", "root")
+            .uid(0)
+            .collect();
+        let expected = Some(format!("{} in ", Color::Red.bold().paint("root")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn root_user_with_custom_style() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .env("USER", "root")
+            .uid(0)
+            .config(toml::toml! {
+                [username]
+                style_root = "purple"
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Purple.paint("root")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn non_root_user_is_hidden_locally() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .env("USER", "astronaut")
+            .uid(1000)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
@@ -1,9 +1,12 @@
 extern crate winapi;
 
+use std::fs;
 use std::iter;
 use std::mem;
 use std::os::windows::ffi::OsStrExt;
+use std::os::windows::fs::OpenOptionsExt;
 use std::path::Path;
+use std::process;
 use winapi::ctypes::c_void;
 use winapi::shared::minwindef::{BOOL, DWORD};
 use winapi::um::handleapi;
@@ -18,10 +21,16 @@ use winapi::um::winnt::{
 
 /// Checks if the current user has write access right to the `folder_path`
 ///
-/// First, the function extracts DACL from the given directory and then calls `AccessCheck` against
-/// the current process access token and directory's security descriptor.
-/// Does not work for network drives and always returns true
+/// Directories with the readonly attribute are reported as read only. Otherwise, the function
+/// extracts DACL from the given directory and then calls `AccessCheck` against the current
+/// process access token and directory's security descriptor. Network drives don't expose a
+/// usable DACL, so there the function probes by creating a file which is deleted when closed.
 pub fn is_write_allowed(folder_path: &Path) -> std::result::Result<bool, &'static str> {
+    let metadata = fs::metadata(folder_path).map_err(|_| "Unable to read directory metadata")?;
+    if metadata.permissions().readonly() {
+        return Ok(false);
+    }
+
     let folder_name: Vec<u16> = folder_path
         .as_os_str()
         .encode_wide()
@@ -29,11 +38,11 @@ pub fn is_write_allowed(folder_path: &Path) -> std::result::Result<bool, &'stati
         .collect();
 
     if is_network_path(&folder_name) {
-        log::info!(
-            "Directory '{:?}' is a network drive, unable to check write permissions. See #1506 for details",
+        log::debug!(
+            "Directory '{:?}' is a network drive, probing write permissions",
             folder_path
         );
-        return Ok(true);
+        return Ok(probe_write(folder_path));
     }
 
     let mut length: DWORD = 0;
@@ -132,6 +141,19 @@ extern "system" {
     fn PathIsNetworkPathW(pszPath: LPCWSTR) -> BOOLEAN;
 }
 
+/// Tries to create a file in `folder_path`, which Windows deletes as soon as it is closed
+fn probe_write(folder_path: &Path) -> bool {
+    const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x0400_0000;
+
+    let probe_path = folder_path.join(format!(".starship-write-probe-{}", process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .custom_flags(FILE_FLAG_DELETE_ON_CLOSE)
+        .open(probe_path)
+        .is_ok()
+}
+
 fn is_network_path(folder_path: &[u16]) -> bool {
     unsafe { PathIsNetworkPathW(folder_path.as_ptr()) == 1 }
 }
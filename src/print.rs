@@ -4,13 +4,15 @@ use rayon::prelude::*;
 use std::collections::BTreeSet;
 use std::fmt::{self, Debug, Write as FmtWrite};
 use std::io::{self, Write};
+use std::process;
+use std::sync::Mutex;
 use std::time::Duration;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
-use crate::configs::PROMPT_ORDER;
-use crate::context::{Context, Shell};
-use crate::formatter::{StringFormatter, VariableHolder};
+use crate::configs::{StarshipRootConfig, PROMPT_ORDER};
+use crate::context::{Context, Shell, Target};
+use crate::formatter::{StringFormatter, VariableHolder, ENV_VARIABLE_PREFIX};
 use crate::module::Module;
 use crate::module::ALL_MODULES;
 use crate::modules;
@@ -29,51 +31,69 @@ pub fn get_prompt(context: Context) -> String {
 
     // A workaround for a fish bug (see #739,#279). Applying it to all shells
     // breaks things (see #808,#824,#834). Should only be printed in fish.
-    if let Shell::Fish = context.shell {
+    if let (Shell::Fish, Target::Main) = (context.shell, context.target) {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
-        formatter
-    } else {
-        log::error!("Error parsing `format`");
-        buf.push('>');
-        return buf;
+    let (format, format_name) = match context.target {
+        Target::Main => (config.format, "format"),
+        Target::Right => (config.right_format, "right_format"),
     };
-    let modules = formatter.get_variables();
+    let formatter = match StringFormatter::new_with_context(format, &context) {
+        Ok(formatter) => formatter,
+        Err(error) => {
+            log::error!("Error parsing `{}`:\n{}", format_name, error);
+            if context.target == Target::Main {
+                buf.push('>');
+            }
+            return buf;
+        }
+    };
+    let modules = get_prompt_modules(&config);
+    let computed_modules = Mutex::new(Vec::new());
     let formatter = formatter.map_variables_to_segments(|module| {
         // Make $all display all modules
         if module == "all" {
             Some(Ok(PROMPT_ORDER
                 .par_iter()
+                .filter(|module| !modules.contains(**module))
                 .flat_map(|module| {
-                    handle_module(module, &context, &modules)
-                        .into_iter()
-                        .flat_map(|module| module.segments)
-                        .collect::<Vec<Segment>>()
+                    into_segments(handle_module(module, &context, &modules), &computed_modules)
                 })
                 .collect::<Vec<_>>()))
+        } else if module.starts_with(ENV_VARIABLE_PREFIX) {
+            // Environment variables are resolved when the format is parsed
+            None
         } else if context.is_module_disabled_in_config(&module) {
             None
         } else {
             // Get segments from module
-            Some(Ok(handle_module(module, &context, &modules)
-                .into_iter()
-                .flat_map(|module| module.segments)
-                .collect::<Vec<Segment>>()))
+            Some(Ok(into_segments(
+                handle_module(module, &context, &modules),
+                &computed_modules,
+            )))
         }
     });
 
     // Creates a root module and prints it.
     let mut root_module = Module::new("Starship Root", "The root module", None);
-    root_module.set_segments(
+    root_module.set_segments(expand_fills(
         formatter
             .parse(None)
             .expect("Unexpected error returned in root format variables"),
-    );
+        context.width,
+    ));
+
+    if log::log_enabled!(log::Level::Trace) {
+        if let Ok(computed_modules) = computed_modules.into_inner() {
+            log::trace!("{}", format_timings(&computed_modules));
+        }
+    }
 
     let module_strings = root_module.ansi_strings_for_shell(context.shell);
-    if config.add_newline {
+    // The first prompt of a session, flagged by the init script, doesn't get a new line
+    let is_first_prompt = context.get_env("STARSHIP_FIRST_PROMPT").is_some();
+    if config.add_newline && context.target == Target::Main && !is_first_prompt {
         writeln!(buf).unwrap();
     }
     write!(buf, "{}", ANSIStrings(&module_strings)).unwrap();
@@ -81,19 +101,146 @@ pub fn get_prompt(context: Context) -> String {
     buf
 }
 
+/// Repeats the symbol of the fill segments so that every line of the prompt is `width` columns
+/// wide. The space left on a line is split evenly between the fills on it.
+fn expand_fills(mut segments: Vec<Segment>, width: usize) -> Vec<Segment> {
+    let mut line_start = 0;
+    let mut line_width = 0;
+    for index in 0..segments.len() {
+        let value = &segments[index].value;
+        if segments[index].is_fill {
+            continue;
+        }
+
+        match (value.find('\n'), value.rfind('\n')) {
+            (Some(first), Some(last)) => {
+                let before = better_width(&value[..first]);
+                let after = better_width(&value[last + 1..]);
+                fill_line(
+                    &mut segments[line_start..index],
+                    width.saturating_sub(line_width + before),
+                );
+                line_start = index + 1;
+                line_width = after;
+            }
+            _ => line_width += better_width(value),
+        }
+    }
+    fill_line(
+        &mut segments[line_start..],
+        width.saturating_sub(line_width),
+    );
+
+    segments
+}
+
+fn fill_line(segments: &mut [Segment], space: usize) {
+    let fills = segments.iter().filter(|segment| segment.is_fill).count();
+    for (index, segment) in segments
+        .iter_mut()
+        .filter(|segment| segment.is_fill)
+        .enumerate()
+    {
+        // Hand out the remainder one column at a time, starting from the leftmost fill
+        let fill_width = space / fills + if index < space % fills { 1 } else { 0 };
+        segment.value = repeat_to_width(&segment.value, fill_width);
+    }
+}
+
+/// Repeats `symbol` up to `width` columns, padding with spaces when the last repetition of the
+/// symbol doesn't fit
+fn repeat_to_width(symbol: &str, width: usize) -> String {
+    let mut filled = String::new();
+    let mut filled_width = 0;
+    if better_width(symbol) > 0 {
+        for grapheme in symbol.graphemes(true).cycle() {
+            let grapheme_width = grapheme_width(grapheme);
+            if filled_width + grapheme_width > width {
+                break;
+            }
+            filled.push_str(grapheme);
+            filled_width += grapheme_width;
+        }
+    }
+    filled.push_str(&" ".repeat(width - filled_width));
+    filled
+}
+
 pub fn module(module_name: &str, args: ArgMatches) {
     let context = Context::new(args);
+    if !is_known_module(module_name, &context) {
+        eprintln!(
+            "Error: Unknown module {}. Use starship module --list to list out all supported modules.",
+            module_name
+        );
+        process::exit(1);
+    }
     let module = get_module(module_name, context).unwrap_or_default();
     print!("{}", module);
 }
 
 pub fn get_module(module_name: &str, context: Context) -> Option<String> {
-    modules::handle(module_name, &context).map(|m| m.to_string())
+    if let Some(custom_module) = module_name.strip_prefix("custom.") {
+        modules::handle_custom(custom_module, &context)
+    } else if let Some(env_var_module) = module_name.strip_prefix("env_var.") {
+        modules::handle_env_var(env_var_module, &context)
+    } else {
+        modules::handle(module_name, &context)
+    }
+    .map(|m| m.to_string())
+}
+
+/// Whether `module_name` is a built-in module, or a custom (`custom.<name>`) or `env_var`
+/// (`env_var.<name>`) module that has a configuration
+fn is_known_module(module_name: &str, context: &Context) -> bool {
+    if let Some(custom_module) = module_name.strip_prefix("custom.") {
+        context
+            .config
+            .get_custom_module_config(custom_module)
+            .is_some()
+    } else if let Some(env_var_module) = module_name.strip_prefix("env_var.") {
+        context
+            .config
+            .get_env_var_module_config(env_var_module)
+            .is_some()
+    } else {
+        ALL_MODULES.contains(&module_name)
+    }
+}
+
+/// The segments of the computed modules. When tracing, the modules are also kept in `computed`,
+/// so that their timings can be logged once the prompt is rendered.
+fn into_segments<'a>(modules: Vec<Module<'a>>, computed: &Mutex<Vec<Module<'a>>>) -> Vec<Segment> {
+    if !log::log_enabled!(log::Level::Trace) {
+        return modules
+            .into_iter()
+            .flat_map(|module| module.segments)
+            .collect();
+    }
+
+    let segments = modules
+        .iter()
+        .flat_map(|module| module.segments.clone())
+        .collect();
+    if let Ok(mut computed) = computed.lock() {
+        computed.extend(modules);
+    }
+    segments
 }
 
 pub fn timings(args: ArgMatches) {
     let context = Context::new(args);
+    print!("{}", get_timings(&context));
+}
+
+fn get_timings(context: &Context) -> String {
+    format_timings(&compute_modules(context))
+}
 
+/// Lists the modules that have an output or took at least 1ms to compute, slowest first. A module
+/// that was computed but shows nothing, like `git_status` outside of a repository, is listed with
+/// an empty value.
+fn format_timings(modules: &[Module]) -> String {
     struct ModuleTiming {
         name: String,
         name_len: usize,
@@ -102,7 +249,7 @@ pub fn timings(args: ArgMatches) {
         duration_len: usize,
     }
 
-    let mut modules = compute_modules(&context)
+    let mut modules = modules
         .iter()
         .filter(|module| !module.is_empty() || module.duration.as_millis() > 0)
         .map(|module| ModuleTiming {
@@ -121,143 +268,205 @@ pub fn timings(args: ArgMatches) {
     let max_name_width = modules.iter().map(|i| i.name_len).max().unwrap_or(0);
     let max_duration_width = modules.iter().map(|i| i.duration_len).max().unwrap_or(0);
 
-    println!("\n Here are the timings of modules in your prompt (>=1ms or output):");
+    let mut buf =
+        String::from("\n Here are the timings of modules in your prompt (>=1ms or output):\n");
 
     // for now we do not expect a wrap around at the end... famous last words
     // Overall a line looks like this: " {module name}  -  {duration}  -  {module value}".
     for timing in &modules {
-        println!(
+        writeln!(
+            buf,
             " {}{}  -  {}{}  -   {}",
             timing.name,
             " ".repeat(max_name_width - (timing.name_len)),
             " ".repeat(max_duration_width - (timing.duration_len)),
             format_duration(&timing.duration),
             timing.value
-        );
+        )
+        .unwrap();
     }
+
+    buf
 }
 
 pub fn explain(args: ArgMatches) {
+    let show_all = args.is_present("all");
     let context = Context::new(args);
+    print!("{}", get_explanation(&context, show_all));
+}
 
+/// Renders a table of the modules in the prompt: their name, their value without styles, how long
+/// they took, and what they show. With `show_all`, the modules that didn't render are listed too.
+fn get_explanation(context: &Context, show_all: bool) -> String {
     struct ModuleInfo {
+        name: String,
         value: String,
-        value_len: usize,
-        desc: String,
         duration: String,
+        desc: String,
     }
 
-    let dont_print = vec!["line_break"];
+    let dont_print = ["line_break", "fill"];
 
-    let modules = compute_modules(&context)
-        .into_iter()
+    let rendered = compute_modules(context);
+    let mut modules = rendered
+        .iter()
         .filter(|module| !dont_print.contains(&module.get_name().as_str()))
         // this contains empty modules which should not print
         .filter(|module| !module.is_empty())
-        .map(|module| {
-            let value = module.get_segments().join("");
-            ModuleInfo {
-                value: ansi_term::ANSIStrings(&module.ansi_strings()).to_string(),
-                value_len: better_width(value.as_str())
-                    + better_width(format_duration(&module.duration).as_str()),
-                desc: module.get_description().to_owned(),
-                duration: format_duration(&module.duration),
-            }
+        .map(|module| ModuleInfo {
+            name: module.get_name().to_owned(),
+            value: module
+                .get_segments()
+                .join("")
+                .trim_end()
+                .replace('\n', "\\n"),
+            duration: format_duration(&module.duration),
+            desc: module.get_description().to_owned(),
         })
         .collect::<Vec<ModuleInfo>>();
 
-    let max_module_width = modules.iter().map(|i| i.value_len).max().unwrap_or(0);
-
-    // In addition to the module width itself there are also 9 padding characters in each line.
-    // Overall a line looks like this: " {module value} ({xxxms})  -  {description}".
-    const PADDING_WIDTH: usize = 9;
-
-    let desc_width = term_size::dimensions()
-        .map(|(w, _)| w)
-        // Add padding length to module length to avoid text overflow. This line also assures desc_width >= 0.
-        .map(|width| width - std::cmp::min(width, max_module_width + PADDING_WIDTH));
+    if show_all {
+        let shown: BTreeSet<&str> = modules.iter().map(|info| info.name.as_str()).collect();
+        let hidden = get_prompt_module_names(context)
+            .into_iter()
+            .filter(|name| name != "custom" && !dont_print.contains(&name.as_str()))
+            .filter(|name| !shown.contains(name.strip_prefix("custom.").unwrap_or(name)))
+            .map(|name| ModuleInfo {
+                desc: modules::description(&name).to_owned(),
+                name,
+                value: String::from("(not shown)"),
+                duration: String::from("-"),
+            })
+            .collect::<Vec<ModuleInfo>>();
+        modules.extend(hidden);
+    }
 
-    println!("\n Here's a breakdown of your prompt:");
+    let max_name_width = modules
+        .iter()
+        .map(|info| better_width(&info.name))
+        .max()
+        .unwrap_or(0);
+    let max_value_width = modules
+        .iter()
+        .map(|info| better_width(&info.value) + better_width(&info.duration))
+        .max()
+        .unwrap_or(0);
+
+    // In addition to the name and the value there are also 12 padding characters in each line.
+    // Overall a line looks like this: " {name}  {value} ({xxxms})  -  {description}".
+    const PADDING_WIDTH: usize = 12;
+    let desc_start = max_name_width + max_value_width + PADDING_WIDTH;
+    // This also assures desc_width >= 0.
+    let desc_width = context.width - std::cmp::min(context.width, desc_start);
+
+    let mut buf = String::from("\n Here's a breakdown of your prompt:\n");
     for info in modules {
-        if let Some(desc_width) = desc_width {
-            // Custom Textwrapping!
-            let mut current_pos = 0;
-            let mut escaping = false;
-            // Print info
-            print!(
-                " {} ({}){}  -  ",
-                info.value,
-                info.duration,
-                " ".repeat(max_module_width - (info.value_len))
-            );
-            for g in info.desc.graphemes(true) {
-                // Handle ANSI escape sequnces
-                if g == "\x1B" {
-                    escaping = true;
-                }
-                if escaping {
-                    print!("{}", g);
-                    escaping = !(("a"..="z").contains(&g) || ("A"..="Z").contains(&g));
+        write!(
+            buf,
+            " {}{}  {} ({}){}  -  ",
+            info.name,
+            " ".repeat(max_name_width - better_width(&info.name)),
+            info.value,
+            info.duration,
+            " ".repeat(max_value_width - better_width(&info.value) - better_width(&info.duration)),
+        )
+        .unwrap();
+
+        // Custom Textwrapping!
+        let mut current_pos = 0;
+        let mut escaping = false;
+        for g in info.desc.graphemes(true) {
+            // Handle ANSI escape sequnces
+            if g == "\x1B" {
+                escaping = true;
+            }
+            if escaping {
+                buf.push_str(g);
+                escaping = !(("a"..="z").contains(&g) || ("A"..="Z").contains(&g));
+                continue;
+            }
+
+            // Handle normal wrapping
+            current_pos += grapheme_width(g);
+            // Wrap when hitting max width or newline
+            if desc_width > 0 && (g == "\n" || current_pos > desc_width) {
+                // trim spaces on linebreak
+                if g == " " && desc_width > 1 {
                     continue;
                 }
 
-                // Handle normal wrapping
-                current_pos += grapheme_width(g);
-                // Wrap when hitting max width or newline
-                if g == "\n" || current_pos > desc_width {
-                    // trim spaces on linebreak
-                    if g == " " && desc_width > 1 {
-                        continue;
-                    }
-
-                    print!("\n{}", " ".repeat(max_module_width + PADDING_WIDTH));
-                    if g == "\n" {
-                        current_pos = 0;
-                        continue;
-                    }
-
-                    current_pos = 1;
+                write!(buf, "\n{}", " ".repeat(desc_start)).unwrap();
+                if g == "\n" {
+                    current_pos = 0;
+                    continue;
                 }
-                print!("{}", g);
+
+                current_pos = 1;
             }
-            println!();
-        } else {
-            println!(
-                " {}{}  -  {}",
-                info.value,
-                " ".repeat(max_module_width - info.value_len),
-                info.desc,
-            );
-        };
+            buf.push_str(g);
+        }
+        buf.push('\n');
     }
+
+    buf
 }
 
+/// Computes every module of the left and the right prompt, each of them once
 fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
-    let mut prompt_order: Vec<Module<'a>> = Vec::new();
+    let config = context.config.get_root_config();
+    let modules = get_prompt_modules(&config);
+
+    get_prompt_module_names(context)
+        .par_iter()
+        .flat_map(|module| handle_module(module, context, &modules))
+        .collect()
+}
 
+/// Lists the modules of the left and the right prompt, with `$all` expanded
+fn get_prompt_module_names(context: &Context) -> Vec<String> {
     let config = context.config.get_root_config();
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
-        formatter
-    } else {
-        log::error!("Error parsing `format`");
-        return Vec::new();
-    };
-    let modules = formatter.get_variables();
+    let modules = get_prompt_modules(&config);
 
+    let mut names = Vec::new();
     for module in &modules {
+        // Environment variables aren't modules
+        if module.starts_with(ENV_VARIABLE_PREFIX) {
+            continue;
+        }
+
         // Manually add all modules if `$all` is encountered
         if module == "all" {
-            for module in PROMPT_ORDER.iter() {
-                let modules = handle_module(module, &context, &modules);
-                prompt_order.extend(modules.into_iter());
-            }
+            names.extend(
+                PROMPT_ORDER
+                    .iter()
+                    .filter(|module| !modules.contains(**module))
+                    .map(|module| module.to_string()),
+            );
         } else {
-            let modules = handle_module(module, &context, &modules);
-            prompt_order.extend(modules.into_iter());
+            names.push(module.to_owned());
         }
     }
 
-    prompt_order
+    names
+}
+
+/// Returns the variables of both `format` and `right_format`. Modules placed explicitly in either
+/// of them are left out of `$all`, so they aren't shown twice.
+fn get_prompt_modules(config: &StarshipRootConfig) -> BTreeSet<String> {
+    [
+        ("format", config.format),
+        ("right_format", config.right_format),
+    ]
+    .iter()
+    .filter_map(|(name, format)| match StringFormatter::new(format) {
+        Ok(formatter) => Some(formatter.get_variables()),
+        Err(error) => {
+            log::error!("Error parsing `{}`:\n{}", name, error);
+            None
+        }
+    })
+    .flatten()
+    .collect()
 }
 
 fn handle_module<'a>(
@@ -275,7 +484,21 @@ fn handle_module<'a>(
 
     let mut modules: Vec<Option<Module>> = Vec::new();
 
-    if ALL_MODULES.contains(&module) {
+    if module == "env_var" {
+        // Write out the `[env_var]` module and all `[env_var.<name>]` modules, except for
+        // those that are explicitly set
+        if !context.is_module_disabled_in_config(module) {
+            modules.push(modules::handle(module, context));
+            modules.extend(
+                context
+                    .config
+                    .get_env_var_modules()
+                    .into_iter()
+                    .filter(|name| !module_list.contains(&format!("env_var.{}", name)))
+                    .map(|name| modules::handle_env_var(name, context)),
+            );
+        }
+    } else if ALL_MODULES.contains(&module) {
         // Write out a module if it isn't disabled
         if !context.is_module_disabled_in_config(module) {
             modules.push(modules::handle(module, &context));
@@ -287,7 +510,7 @@ fn handle_module<'a>(
                 .iter()
                 .map(|(custom_module, config)| {
                     if should_add_implicit_custom_module(custom_module, config, &module_list) {
-                        modules::custom::module(custom_module, &context)
+                        modules::handle_custom(custom_module, context)
                     } else {
                         None
                     }
@@ -299,7 +522,7 @@ fn handle_module<'a>(
         // Write out a custom module if it isn't disabled (and it exists...)
         match context.is_custom_module_disabled_in_config(&module) {
             Some(true) => (), // Module is disabled, we don't add it to the prompt
-            Some(false) => modules.push(modules::custom::module(&module, &context)),
+            Some(false) => modules.push(modules::handle_custom(module, context)),
             None => match context.config.get_custom_modules() {
                 Some(modules) => log::debug!(
                     "top level format contains custom module \"{}\", but no configuration was provided. Configuration for the following modules were provided: {:?}",
@@ -312,6 +535,16 @@ fn handle_module<'a>(
                     ),
             },
         }
+    } else if let Some(module) = module.strip_prefix("env_var.") {
+        // Write out an `env_var` module if it is configured
+        if context.config.get_env_var_module_config(module).is_some() {
+            modules.push(modules::handle_env_var(module, context));
+        } else {
+            log::debug!(
+                "top level format contains env_var module \"{}\", but no configuration was provided.",
+                module,
+            );
+        }
     } else {
         log::debug!(
             "Expected top level format to contain value from {:?}. Instead received {}",
@@ -370,3 +603,419 @@ fn test_grapheme_aware_better_width() {
     assert_eq!(1, better_width("Ü"));
     assert_eq!(11, better_width("normal text"));
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::test::{fixture_repo, FixtureProvider};
+    use std::path::PathBuf;
+
+    fn fill_values(segments: Vec<Segment>, width: usize) -> Vec<String> {
+        expand_fills(segments, width)
+            .into_iter()
+            .map(|segment| segment.value)
+            .collect()
+    }
+
+    #[test]
+    fn fill_takes_remaining_width() {
+        let segments = vec![
+            Segment::new(None, "left"),
+            Segment::fill(None, "."),
+            Segment::new(None, "right"),
+        ];
+        assert_eq!(fill_values(segments, 15), vec!["left", "......", "right"]);
+    }
+
+    #[test]
+    fn multiple_fills_split_width_evenly() {
+        let segments = vec![
+            Segment::fill(None, "-"),
+            Segment::new(None, "mid"),
+            Segment::fill(None, "="),
+            Segment::new(None, "end"),
+            Segment::fill(None, "*"),
+        ];
+        assert_eq!(
+            fill_values(segments, 14),
+            vec!["---", "mid", "===", "end", "**"]
+        );
+    }
+
+    #[test]
+    fn fill_pads_partial_symbols_and_wide_characters() {
+        let segments = vec![Segment::fill(None, "ab"), Segment::new(None, "🚀")];
+        assert_eq!(fill_values(segments, 7), vec!["ababa", "🚀"]);
+
+        let segments = vec![Segment::fill(None, "🚀"), Segment::new(None, "x")];
+        assert_eq!(fill_values(segments, 6), vec!["🚀🚀 ", "x"]);
+
+        let segments = vec![Segment::fill(None, ""), Segment::new(None, "x")];
+        assert_eq!(fill_values(segments, 4), vec!["   ", "x"]);
+    }
+
+    #[test]
+    fn fill_is_computed_per_line() {
+        let segments = vec![
+            Segment::new(None, "top"),
+            Segment::fill(None, "."),
+            Segment::new(None, "!\n> "),
+            Segment::fill(None, "-"),
+            Segment::new(None, "end"),
+        ];
+        assert_eq!(
+            fill_values(segments, 10),
+            vec!["top", "......", "!\n> ", "-----", "end"]
+        );
+    }
+
+    #[test]
+    fn fill_without_space_left_is_empty() {
+        let segments = vec![Segment::new(None, "too long"), Segment::fill(None, ".")];
+        assert_eq!(fill_values(segments, 4), vec!["too long", ""]);
+    }
+
+    fn right_prompt_context(target: Target) -> Context<'static> {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        context.target = target;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                add_newline = true
+                format = "left ${env:LEFT} $line_break> "
+                right_format = "right ${env:RIGHT} $env_var"
+
+                [env_var]
+                variable = "RIGHT"
+                format = "[$env_value]()"
+            }),
+        };
+        context.env.insert("LEFT", "L".to_owned());
+        context.env.insert("RIGHT", "R".to_owned());
+        context
+    }
+
+    #[test]
+    fn main_prompt_renders_format() {
+        let actual = get_prompt(right_prompt_context(Target::Main));
+        assert_eq!(actual, "\nleft L \n> ");
+    }
+
+    #[test]
+    fn right_prompt_renders_right_format() {
+        // The right prompt is never preceded by a new line
+        let actual = get_prompt(right_prompt_context(Target::Right));
+        assert_eq!(actual, "right R R");
+    }
+
+    fn env_var_context(format: &str) -> Context {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                add_newline = false
+                format = format
+
+                [env_var]
+                variable = "LEGACY"
+                format = "[$env_value]() "
+
+                [env_var.FOO]
+                format = "[foo=$env_value]() "
+
+                [env_var.BAR]
+                format = "[bar=$env_value]() "
+            }),
+        };
+        context.env.insert("LEGACY", "legacy".to_owned());
+        context.env.insert("FOO", "1".to_owned());
+        context.env.insert("BAR", "2".to_owned());
+        context
+    }
+
+    #[test]
+    fn env_var_renders_all_env_var_modules() {
+        let actual = get_prompt(env_var_context("$env_var"));
+        assert_eq!(actual, "legacy foo=1 bar=2 ");
+    }
+
+    #[test]
+    fn env_var_modules_can_be_placed_explicitly() {
+        let actual = get_prompt(env_var_context("${env_var.FOO}> $env_var"));
+        assert_eq!(actual, "foo=1 > legacy bar=2 ");
+    }
+
+    #[test]
+    fn env_var_modules_are_known_modules() {
+        let context = env_var_context("$env_var");
+        assert!(is_known_module("env_var.FOO", &context));
+        assert!(!is_known_module("env_var.MISSING", &context));
+    }
+
+    fn newline_context(add_newline: bool, first_prompt: bool) -> Context<'static> {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                add_newline = add_newline
+                format = "> "
+            }),
+        };
+        if first_prompt {
+            context.env.insert("STARSHIP_FIRST_PROMPT", "1".to_owned());
+        }
+        context
+    }
+
+    #[test]
+    fn add_newline_prints_new_line_before_prompt() {
+        assert_eq!(get_prompt(newline_context(true, false)), "\n> ");
+    }
+
+    #[test]
+    fn add_newline_disabled_prints_no_new_line() {
+        assert_eq!(get_prompt(newline_context(false, false)), "> ");
+    }
+
+    #[test]
+    fn first_prompt_has_no_new_line() {
+        assert_eq!(get_prompt(newline_context(true, true)), "> ");
+    }
+
+    #[test]
+    fn first_prompt_with_add_newline_disabled_has_no_new_line() {
+        assert_eq!(get_prompt(newline_context(false, true)), "> ");
+    }
+
+    #[test]
+    fn modules_of_both_prompts_are_computed_once() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$character$line_break"
+                right_format = "$line_break$character"
+            }),
+        };
+
+        let names: Vec<String> = compute_modules(&context)
+            .iter()
+            .map(|module| module.get_name().to_owned())
+            .collect();
+        assert_eq!(names, vec!["character", "line_break"]);
+    }
+
+    fn plain_modules_context(format: &str) -> Context {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        let mut config = toml::toml! {
+            add_newline = false
+
+            [cmd_duration]
+            min_time = 0
+            format = "took $duration "
+
+            [shlvl]
+            disabled = false
+            threshold = 0
+            format = "lvl $shlvl "
+
+            [character]
+            success_symbol = ">"
+        };
+        config
+            .as_table_mut()
+            .unwrap()
+            .insert("format".to_owned(), toml::Value::from(format));
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        context.env.insert("SHLVL", "2".to_owned());
+        context.properties.insert("cmd_duration", "5000".to_owned());
+        context
+    }
+
+    #[test]
+    fn format_sets_module_order_and_text_between_modules() {
+        let context = plain_modules_context("$character then $shlvl| $cmd_duration");
+        assert_eq!(get_prompt(context), ">  then lvl 2 | took 5s ");
+    }
+
+    #[test]
+    fn module_mentioned_twice_is_rendered_twice() {
+        let context = plain_modules_context("$shlvl- $shlvl");
+        assert_eq!(get_prompt(context), "lvl 2 - lvl 2 ");
+    }
+
+    #[test]
+    fn modules_left_out_of_format_are_not_computed() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let mut context = Context::new_with_dir(ArgMatches::default(), repo_dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$directory$character"
+            }),
+        };
+
+        let names: Vec<String> = compute_modules(&context)
+            .iter()
+            .map(|module| module.get_name().to_owned())
+            .collect();
+        assert_eq!(names, vec!["character", "directory"]);
+        assert!(!get_prompt(context).contains("master"));
+
+        repo_dir.close()
+    }
+
+    #[test]
+    fn explain_lists_git_branch_in_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        let mut context = Context::new_with_dir(ArgMatches::default(), repo_dir.path());
+        context.width = 200;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$directory$git_branch$nodejs"
+            }),
+        };
+
+        let explanation = get_explanation(&context, false);
+        let git_branch = explanation
+            .lines()
+            .find(|line| line.starts_with(" git_branch "))
+            .expect("git_branch should be explained");
+        assert!(git_branch.contains("master"));
+        assert!(git_branch.contains(modules::description("git_branch")));
+        assert!(!explanation.contains("\u{1b}["));
+        assert!(!explanation.contains(" nodejs "));
+
+        let explanation = get_explanation(&context, true);
+        let nodejs = explanation
+            .lines()
+            .find(|line| line.starts_with(" nodejs "))
+            .expect("nodejs should be listed with --all");
+        assert!(nodejs.contains("(not shown)"));
+
+        repo_dir.close()
+    }
+
+    #[test]
+    fn modules_record_their_duration() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::GIT)?;
+        std::fs::File::create(repo_dir.path().join("untracked"))?;
+        let mut context = Context::new_with_dir(ArgMatches::default(), repo_dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$git_branch$git_status"
+            }),
+        };
+
+        let modules = compute_modules(&context);
+        let git_status = modules
+            .iter()
+            .find(|module| module.get_name() == "git_status")
+            .expect("git_status should be computed");
+        assert!(git_status.duration > Duration::from_secs(0));
+
+        let timings = get_timings(&context);
+        assert!(timings.lines().any(|line| line.starts_with(" git_status ")));
+
+        repo_dir.close()
+    }
+
+    #[test]
+    fn module_names_are_checked_before_rendering() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.greeting]
+                command = "echo hi"
+                when = "true"
+            }),
+        };
+
+        assert!(is_known_module("git_branch", &context));
+        assert!(is_known_module("custom.greeting", &context));
+        assert!(!is_known_module("custom.missing", &context));
+        assert!(!is_known_module("not_a_module", &context));
+    }
+
+    #[test]
+    fn custom_module_is_rendered_on_its_own() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.greeting]
+                command = "echo hi"
+                when = "true"
+                format = "[$output]($style)"
+                style = ""
+            }),
+        };
+
+        assert_eq!(
+            get_module("custom.greeting", context),
+            Some("hi".to_owned())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn modules_computed_in_parallel_keep_the_format_order() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                add_newline = false
+                format = "$status$cmd_duration$line_break$shlvl$character"
+
+                [status]
+                disabled = false
+                format = "status $status "
+
+                [cmd_duration]
+                min_time = 0
+                format = "took $duration "
+
+                [shlvl]
+                disabled = false
+                threshold = 0
+                format = "lvl $shlvl "
+
+                [character]
+                error_symbol = ">"
+            }),
+        };
+        context.env.insert("SHLVL", "2".to_owned());
+        context.properties.insert("status_code", "1".to_owned());
+        context.properties.insert("cmd_duration", "5000".to_owned());
+
+        assert_eq!(get_prompt(context), "status 1 took 5s \nlvl 2 > ");
+    }
+
+    #[test]
+    fn context_can_be_shared_between_threads() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Context>();
+    }
+
+    #[test]
+    fn prompt_with_fill_fits_terminal_width() {
+        let mut context = Context::new_with_dir(ArgMatches::default(), PathBuf::new());
+        context.shell = Shell::Unknown;
+        context.width = 20;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                add_newline = false
+                format = "left$fill${env:RIGHT}"
+
+                [fill]
+                style = ""
+                symbol = "."
+            }),
+        };
+        context.env.insert("RIGHT", "right".to_owned());
+
+        assert_eq!(get_prompt(context), "left...........right");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::context::Shell;
+use crate::utils::escape_text_for_shell;
 use ansi_term::{ANSIString, Style};
 use std::fmt;
 
@@ -11,6 +13,9 @@ pub struct Segment {
 
     /// The string value of the current segment.
     pub value: String,
+
+    /// Whether the value should be repeated to fill the rest of the line when the prompt is printed.
+    pub is_fill: bool,
 }
 
 impl Segment {
@@ -22,14 +27,33 @@ impl Segment {
         Self {
             style,
             value: value.into(),
+            is_fill: false,
+        }
+    }
+
+    /// Creates a new segment that fills the rest of the line by repeating `symbol`.
+    pub fn fill<T>(style: Option<Style>, symbol: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            is_fill: true,
+            ..Self::new(style, symbol)
         }
     }
 
     // Returns the ANSIString of the segment value, not including its prefix and suffix
     pub fn ansi_string(&self) -> ANSIString {
+        self.ansi_string_for_shell(Shell::Unknown)
+    }
+
+    /// Returns the ANSIString of the segment value, with the characters that `shell` would
+    /// expand in a prompt escaped
+    pub fn ansi_string_for_shell(&self, shell: Shell) -> ANSIString<'_> {
+        let value = escape_text_for_shell(&self.value, shell);
         match self.style {
-            Some(style) => style.paint(&self.value),
-            None => ANSIString::from(&self.value),
+            Some(style) => style.paint(value),
+            None => ANSIString::from(value),
         }
     }
 }
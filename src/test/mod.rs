@@ -1,7 +1,11 @@
 use crate::config::StarshipConfig;
 use crate::context::{Context, Shell};
-use once_cell::sync::Lazy;
+use crate::utils::{CommandOutput, OsInfo};
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use std::ffi::OsStr;
 use std::io;
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::process::Command;
 use tempfile::TempDir;
@@ -24,15 +28,30 @@ impl<'a> ModuleRenderer<'a> {
         let mut context = Context::new_with_dir(clap::ArgMatches::default(), PathBuf::new());
         context.shell = Shell::Unknown;
         context.config = StarshipConfig { config: None };
+        context.uid = None;
+        context.hostname = None;
+        context.local_ipv4 = OnceCell::from(None);
 
         Self { name, context }
     }
 
+    /// Sets both the physical and the logical directory of the underlying context
     pub fn path<T>(mut self, path: T) -> Self
     where
         T: Into<PathBuf>,
     {
         self.context.current_dir = path.into();
+        self.context.logical_dir = self.context.current_dir.clone();
+        self
+    }
+
+    /// Sets the logical directory of the underlying context, so it should be
+    /// called after `path`
+    pub fn logical_path<T>(mut self, path: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        self.context.logical_dir = path.into();
         self
     }
 
@@ -44,23 +63,80 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    /// Loads the config of the underlying context from a file, like `--config` does
+    pub fn config_file<P: AsRef<OsStr>>(mut self, path: P) -> Self {
+        self.context.config = StarshipConfig::initialize(Some(path.as_ref()));
+        self
+    }
+
     /// Adds the variable to the env_mocks of the underlying context
     pub fn env<V: Into<String>>(mut self, key: &'a str, val: V) -> Self {
         self.context.env.insert(key, val.into());
         self
     }
 
+    /// Mocks the output of `cmd`, which is the command and its arguments joined by spaces.
+    /// `None` makes the command fail.
+    pub fn cmd(mut self, cmd: &'a str, output: Option<CommandOutput>) -> Self {
+        self.context.cmd.insert(cmd, output);
+        self
+    }
+
     pub fn shell(mut self, shell: Shell) -> Self {
         self.context.shell = shell;
         self
     }
 
+    pub fn uid(mut self, uid: u32) -> Self {
+        self.context.uid = Some(uid);
+        self
+    }
+
+    pub fn hostname<T: Into<String>>(mut self, hostname: T) -> Self {
+        self.context.hostname = Some(hostname.into());
+        self
+    }
+
+    pub fn local_ipv4(mut self, ip: Ipv4Addr) -> Self {
+        self.context.local_ipv4 = OnceCell::from(Some(ip));
+        self
+    }
+
+    pub fn os_info(mut self, os_info: OsInfo) -> Self {
+        self.context.os_info = OnceCell::from(os_info);
+        self
+    }
+
+    pub fn time(mut self, time: DateTime<Utc>) -> Self {
+        self.context.now = time;
+        self
+    }
+
+    #[cfg(feature = "battery")]
+    pub fn battery_info_provider(
+        mut self,
+        battery_info_provider: &'a (dyn crate::modules::BatteryInfoProvider + Send + Sync),
+    ) -> Self {
+        self.context.battery_info_provider = battery_info_provider;
+        self
+    }
+
+    pub fn pipestatus(mut self, pipestatus: &[i32]) -> Self {
+        let pipestatus = pipestatus
+            .iter()
+            .map(i32::to_string)
+            .collect::<Vec<String>>()
+            .join(" ");
+        self.context.properties.insert("pipestatus", pipestatus);
+        self
+    }
+
     pub fn jobs(mut self, jobs: u64) -> Self {
         self.context.properties.insert("jobs", jobs.to_string());
         self
     }
 
-    pub fn cmd_duration(mut self, duration: u64) -> Self {
+    pub fn cmd_duration(mut self, duration: u128) -> Self {
         self.context
             .properties
             .insert("cmd_duration", duration.to_string());
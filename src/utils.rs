@@ -1,9 +1,16 @@
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::{Read, Result};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::context::Shell;
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 /// Return the string contents of a file
 pub fn read_file<P: AsRef<Path>>(file_name: P) -> Result<String> {
@@ -14,31 +21,44 @@ pub fn read_file<P: AsRef<Path>>(file_name: P) -> Result<String> {
     Ok(data)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
+    /// The exit code, `None` if the command was terminated by a signal
+    pub status: Option<i32>,
+}
+
+impl CommandOutput {
+    /// Whether the command exited with code 0
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
 }
 
 impl PartialEq for CommandOutput {
     fn eq(&self, other: &Self) -> bool {
-        self.stdout == other.stdout && self.stderr == other.stderr
+        self.stdout == other.stdout && self.stderr == other.stderr && self.status == other.status
     }
 }
 
-/// Execute a command and return the output on stdout and stderr if successful
+/// Execute a command and return its output on stdout and stderr along with its exit status.
+/// A single trailing newline is removed from both outputs.
+/// Returns `None` if the command couldn't be started or didn't finish within `time_limit`, in
+/// which case it is killed.
 #[cfg(not(test))]
-pub fn exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
-    internal_exec_cmd(&cmd, &args)
+pub fn exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
+    internal_exec_cmd(cmd, args, time_limit)
 }
 
 #[cfg(test)]
-pub fn exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+pub fn exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
     let command = match args.len() {
         0 => String::from(cmd),
         _ => format!("{} {}", cmd, args.join(" ")),
     };
-    match command.as_str() {
+    // Mocked outputs are what the commands print, they are trimmed like real outputs
+    let output = match command.as_str() {
         "crystal --version" => Some(CommandOutput {
             stdout: String::from(
                 "\
@@ -48,16 +68,19 @@ LLVM: 10.0.0
 Default target: x86_64-apple-macosx\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dart --version" => Some(CommandOutput {
             stdout: String::default(),
             stderr: String::from(
                 "Dart VM version: 2.8.4 (stable) (Wed Jun 3 12:26:04 2020 +0200) on \"macos_x64\"",
             ),
+            status: Some(0),
         }),
         "dummy_command" => Some(CommandOutput {
             stdout: String::from("stdout ok!\n"),
             stderr: String::from("stderr ok!\n"),
+            status: Some(0),
         }),
         "elixir --version" => Some(CommandOutput {
             stdout: String::from(
@@ -67,34 +90,42 @@ Erlang/OTP 22 [erts-10.6.4] [source] [64-bit] [smp:8:8] [ds:8:8:10] [async-threa
 Elixir 1.10 (compiled with Erlang/OTP 22)\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "elm --version" => Some(CommandOutput {
             stdout: String::from("0.19.1\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "go version" => Some(CommandOutput {
             stdout: String::from("go version go1.12.1 linux/amd64\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "helm version --short --client" => Some(CommandOutput {
             stdout: String::from("v3.1.1+gafe7058\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         s if s.ends_with("java -Xinternalversion") => Some(CommandOutput {
             stdout: String::from("OpenJDK 64-Bit Server VM (13.0.2+8) for bsd-amd64 JRE (13.0.2+8), built on Feb  6 2020 02:07:52 by \"brew\" with clang 4.2.1 Compatible Apple LLVM 11.0.0 (clang-1100.0.33.17)"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "julia --version" => Some(CommandOutput {
             stdout: String::from("julia version 1.4.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "lua -v" => Some(CommandOutput{
             stdout: String::from("Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "luajit -v" => Some(CommandOutput{
             stdout: String::from("LuaJIT 2.0.5 -- Copyright (C) 2005-2017 Mike Pall. http://luajit.org/\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "nim --version" => Some(CommandOutput {
             stdout: String::from(
@@ -106,49 +137,60 @@ git hash: 7e83adff84be5d0c401a213eccb61e321a3fb1ff
 active boot switches: -d:release\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "node --version" => Some(CommandOutput {
             stdout: String::from("v12.0.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "ocaml -vnum" => Some(CommandOutput {
             stdout: String::from("4.10.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "esy ocaml -vnum" => Some(CommandOutput {
             stdout: String::from("4.08.1\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "perl -e printf q#%vd#,$^V;" => Some(CommandOutput {
             stdout: String::from("5.26.1"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "php -nr echo PHP_MAJOR_VERSION.\".\".PHP_MINOR_VERSION.\".\".PHP_RELEASE_VERSION;" => {
             Some(CommandOutput {
                 stdout: String::from("7.3.8"),
                 stderr: String::default(),
+                status: Some(0),
             })
         }
         "purs --version" => Some(CommandOutput {
             stdout: String::from("0.13.5\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "pyenv version-name" => Some(CommandOutput {
             stdout: String::from("system\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "python --version" => None,
         "python2 --version" => Some(CommandOutput {
             stdout: String::default(),
             stderr: String::from("Python 2.7.17\n"),
+            status: Some(0),
         }),
         "python3 --version" => Some(CommandOutput {
             stdout: String::from("Python 3.8.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "ruby -v" => Some(CommandOutput {
             stdout: String::from("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "swift --version" => Some(CommandOutput {
             stdout: String::from(
@@ -157,10 +199,12 @@ Apple Swift version 5.2.2 (swiftlang-1103.0.32.6 clang-1103.0.32.51)
 Target: x86_64-apple-darwin19.4.0\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "zig version" => Some(CommandOutput {
             stdout: String::from("0.6.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "cmake --version" => Some(CommandOutput {
             stdout: String::from(
@@ -170,26 +214,32 @@ cmake version 3.17.3
 CMake suite maintained and supported by Kitware (kitware.com/cmake).\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dotnet --version" => Some(CommandOutput {
             stdout: String::from("3.1.103"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dotnet --list-sdks" => Some(CommandOutput {
             stdout: String::from("3.1.103 [/usr/share/dotnet/sdk]"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "terraform version" => Some(CommandOutput {
             stdout: String::from("Terraform v0.12.14\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         s if s.starts_with("erl -noshell -eval") => Some(CommandOutput {
             stdout: String::from("22.1.3\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         // If we don't have a mocked command fall back to executing the command
-        _ => internal_exec_cmd(&cmd, &args),
-    }
+        _ => return internal_exec_cmd(cmd, args, time_limit),
+    };
+    output.map(trim_trailing_newline)
 }
 
 /// Wraps ANSI color escape sequences in the shell-appropriate wrappers.
@@ -199,6 +249,193 @@ pub fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
     wrap_seq_for_shell(ansi, shell, ESCAPE_BEGIN, ESCAPE_END)
 }
 
+/// Escapes the characters of literal prompt text that the shell would otherwise expand:
+/// `%` for zsh and tcsh, and `!` (the history number) for tcsh.
+pub fn escape_text_for_shell(text: &str, shell: Shell) -> Cow<'_, str> {
+    match shell {
+        Shell::Zsh => Cow::Owned(text.replace('%', "%%")),
+        Shell::Tcsh => Cow::Owned(text.replace('%', "%%").replace('!', "\\!")),
+        _ => Cow::Borrowed(text),
+    }
+}
+
+/// The effective user id of the current process, or `None` on platforms without one.
+#[cfg(not(windows))]
+pub fn get_effective_uid() -> Option<u32> {
+    Some(nix::unistd::geteuid().as_raw())
+}
+
+/// The effective user id of the current process, or `None` on platforms without one.
+#[cfg(windows)]
+pub fn get_effective_uid() -> Option<u32> {
+    None
+}
+
+/// The system hostname, or `None` if it isn't valid UTF-8.
+pub fn get_hostname() -> Option<String> {
+    match gethostname::gethostname().into_string() {
+        Ok(hostname) => Some(hostname),
+        Err(bad) => {
+            log::warn!("hostname is not valid UTF!\n{:?}", bad);
+            None
+        }
+    }
+}
+
+/// The IPv4 address of the interface that traffic to `probe` would leave through. Connecting a
+/// UDP socket only picks the route, nothing is sent. `None` when there is no route.
+pub fn get_local_ipv4(probe: SocketAddr) -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    let time_limit = Some(Duration::from_millis(100));
+    socket.set_read_timeout(time_limit).ok()?;
+    socket.set_write_timeout(time_limit).ok()?;
+    socket.connect(probe).ok()?;
+
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+        _ => None,
+    }
+}
+
+/// The operating system, or Linux distribution, starship runs on
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsInfo {
+    /// A lowercase identifier, like `macos`, `windows` or the `ID` of a Linux distribution
+    pub os_type: String,
+    /// A human readable name, like `macOS` or `Arch Linux`
+    pub name: String,
+    /// The version of the operating system, if it is known
+    pub version: Option<String>,
+    /// Whether this is Linux running in the Windows Subsystem for Linux
+    pub wsl: bool,
+}
+
+/// Detects the operating system. On Linux the distribution is read from `/etc/os-release`.
+pub fn get_os_info() -> OsInfo {
+    if cfg!(target_os = "linux") {
+        let os_release = read_file("/etc/os-release")
+            .or_else(|_| read_file("/usr/lib/os-release"))
+            .unwrap_or_default();
+        let wsl = read_file("/proc/sys/kernel/osrelease")
+            .map(|release| release.to_lowercase().contains("microsoft"))
+            .unwrap_or(false);
+        parse_os_release(&os_release, wsl)
+    } else {
+        let os_type = std::env::consts::OS;
+        let name = match os_type {
+            "android" => "Android",
+            "dragonfly" => "DragonFly BSD",
+            "freebsd" => "FreeBSD",
+            "ios" => "iOS",
+            "macos" => "macOS",
+            "netbsd" => "NetBSD",
+            "openbsd" => "OpenBSD",
+            "windows" => "Windows",
+            os_type => os_type,
+        };
+        OsInfo {
+            os_type: os_type.to_owned(),
+            name: name.to_owned(),
+            version: None,
+            wsl: false,
+        }
+    }
+}
+
+/// Reads the Linux distribution from the content of an os-release file. The distribution is
+/// plain `linux` if the file doesn't name one.
+pub fn parse_os_release(os_release: &str, wsl: bool) -> OsInfo {
+    let mut info = OsInfo {
+        os_type: String::from("linux"),
+        name: String::from("Linux"),
+        version: None,
+        wsl,
+    };
+
+    for line in os_release.lines() {
+        let mut parts = line.splitn(2, '=');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => continue,
+        };
+        let value = value.trim_matches(|c| c == '"' || c == '\'').to_owned();
+        if value.is_empty() {
+            continue;
+        }
+
+        match key {
+            "ID" => info.os_type = value,
+            "NAME" => info.name = value,
+            "VERSION_ID" => info.version = Some(value),
+            _ => (),
+        }
+    }
+
+    info
+}
+
+/// Extracts the first version number, like `1.2.3`, `1.2` or `1.52.0-nightly`, from the output of
+/// a command. A build suffix such as `+gafdfb8e` or `_292` is not part of the version.
+pub fn parse_version(output: &str) -> Option<&str> {
+    static VERSION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\d+\.\d+(?:\.\d+)?(?:-[0-9A-Za-z]+(?:\.[0-9A-Za-z]+)*)?").unwrap()
+    });
+
+    VERSION_PATTERN.find(output).map(|version| version.as_str())
+}
+
+/// Renders how long ago `timestamp` was, relative to `now`, in the largest whole
+/// unit: "now", "5m", "3h", "2d", "4mo" or "1y". Both are seconds since the epoch.
+pub fn humanize_age(timestamp: i64, now: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let age = now.saturating_sub(timestamp);
+    let (count, unit) = match age {
+        age if age < MINUTE => return String::from("now"),
+        age if age < HOUR => (age / MINUTE, "m"),
+        age if age < DAY => (age / HOUR, "h"),
+        age if age < MONTH => (age / DAY, "d"),
+        age if age < YEAR => (age / MONTH, "mo"),
+        age => (age / YEAR, "y"),
+    };
+    format!("{}{}", count, unit)
+}
+
+/// Renders a duration in milliseconds as a human-readable string, like "2m31s" or "1d2h".
+/// Milliseconds are only shown for durations under a second, unless `show_millis` is set.
+pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
+    // Calculate a simple breakdown into days/hours/minutes/seconds/milliseconds
+    let (millis, raw_seconds) = (raw_millis % 1000, raw_millis / 1000);
+    let (seconds, raw_minutes) = (raw_seconds % 60, raw_seconds / 60);
+    let (minutes, raw_hours) = (raw_minutes % 60, raw_minutes / 60);
+    let (hours, days) = (raw_hours % 24, raw_hours / 24);
+
+    let components = [days, hours, minutes, seconds];
+    let suffixes = ["d", "h", "m", "s"];
+
+    let mut rendered_components: Vec<String> = components
+        .iter()
+        .zip(&suffixes)
+        .map(render_time_component)
+        .collect();
+    if show_millis || raw_millis < 1000 {
+        rendered_components.push(render_time_component((&millis, &"ms")));
+    }
+    rendered_components.join("")
+}
+
+/// Render a single component of the time string, giving an empty string if component is zero
+fn render_time_component((component, suffix): (&u128, &&str)) -> String {
+    match component {
+        0 => String::new(),
+        n => format!("{}{}", n, suffix),
+    }
+}
+
 /// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
 /// leading to strange visual bugs like duplicated/missing chars. This function wraps a specified
 /// sequence in shell-specific escapes to avoid these problems.
@@ -223,14 +460,14 @@ pub fn wrap_seq_for_shell(
                 escaped = true;
                 match shell {
                     Shell::Bash => format!("{}{}", BASH_BEG, escape_begin),
-                    Shell::Zsh => format!("{}{}", ZSH_BEG, escape_begin),
+                    Shell::Zsh | Shell::Tcsh => format!("{}{}", ZSH_BEG, escape_begin),
                     _ => x.to_string(),
                 }
             } else if x == escape_end && escaped {
                 escaped = false;
                 match shell {
                     Shell::Bash => format!("{}{}", escape_end, BASH_END),
-                    Shell::Zsh => format!("{}{}", escape_end, ZSH_END),
+                    Shell::Zsh | Shell::Tcsh => format!("{}{}", escape_end, ZSH_END),
                     _ => x.to_string(),
                 }
             } else {
@@ -241,31 +478,126 @@ pub fn wrap_seq_for_shell(
     final_string
 }
 
-fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+fn internal_exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
     log::trace!("Executing command {:?} with args {:?}", cmd, args);
-    match Command::new(cmd).args(args).output() {
-        Ok(output) => {
-            let stdout_string = String::from_utf8(output.stdout).unwrap();
-            let stderr_string = String::from_utf8(output.stderr).unwrap();
+    let start = Instant::now();
+
+    let child = match Command::new(cmd)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(error) => {
+            log::info!("Executing command {:?} failed by: {:?}", cmd, error);
+            return None;
+        }
+    };
 
-            log::trace!("stdout: {:?}", stdout_string);
-            log::trace!("stderr: {:?}", stderr_string);
-            log::trace!("exit code: \"{:?}\"", output.status.code());
+    let output = wait_with_time_limit(child, cmd, time_limit)?;
 
-            if !output.status.success() {
-                return None;
+    log::trace!("stdout: {:?}", output.stdout);
+    log::trace!("stderr: {:?}", output.stderr);
+    log::trace!("exit code: \"{:?}\"", output.status);
+    log::trace!("elapsed: {:?}", start.elapsed());
+
+    Some(trim_trailing_newline(output))
+}
+
+fn trim_trailing_newline(output: CommandOutput) -> CommandOutput {
+    fn trim(mut text: String) -> String {
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
             }
+        }
+        text
+    }
 
-            Some(CommandOutput {
-                stdout: stdout_string,
-                stderr: stderr_string,
-            })
+    CommandOutput {
+        stdout: trim(output.stdout),
+        stderr: trim(output.stderr),
+        status: output.status,
+    }
+}
+
+/// The longest `wait_with_time_limit` sleeps between checks on the child
+const MAX_WAIT_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Whether a command timing out was already warned about. Later timeouts are only logged at
+/// debug level, so a slow setup doesn't flood every prompt with warnings.
+static TIMEOUT_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Waits for `child` to exit and collects its output. The child is killed if it is still running
+/// after `time_limit`, and `None` is returned. `cmd` is only used for logging.
+pub fn wait_with_time_limit(
+    mut child: Child,
+    cmd: &str,
+    time_limit: Duration,
+) -> Option<CommandOutput> {
+    let start = Instant::now();
+
+    // Close stdin so commands reading from it see its end
+    drop(child.stdin.take());
+
+    // Drain both pipes in the background so a chatty command can't block on a full pipe
+    let stdout_reader = spawn_reader(child.stdout.take());
+    let stderr_reader = spawn_reader(child.stderr.take());
+
+    // Poll with a growing interval, so quick commands are noticed right away while slow ones
+    // don't keep a core busy
+    let mut interval = Duration::from_millis(1);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() < time_limit => {
+                thread::sleep(interval.min(time_limit.saturating_sub(start.elapsed())));
+                interval = (interval * 2).min(MAX_WAIT_INTERVAL);
+            }
+            Ok(None) => {
+                if TIMEOUT_WARNED.swap(true, Ordering::Relaxed) {
+                    log::debug!(
+                        "Executing command {:?} timed out after {:?}.",
+                        cmd,
+                        time_limit
+                    );
+                } else {
+                    log::warn!(
+                        "Executing command {:?} timed out after {:?}.",
+                        cmd,
+                        time_limit
+                    );
+                    log::warn!("You can set command_timeout in your config to a higher value to allow longer-running commands to keep executing.");
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(error) => {
+                log::info!("Executing command {:?} failed by: {:?}", cmd, error);
+                return None;
+            }
         }
-        Err(error) => {
-            log::info!("Executing command {:?} failed by: {:?}", cmd, error);
-            None
+    };
+
+    Some(CommandOutput {
+        stdout: stdout_reader.join().ok()?,
+        stderr: stderr_reader.join().ok()?,
+        status: status.code(),
+    })
+}
+
+fn spawn_reader<R: Read + Send + 'static>(reader: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut buffer);
         }
-    }
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
 }
 
 #[cfg(test)]
@@ -273,12 +605,86 @@ fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_version_of_real_world_outputs() {
+        let cases = [
+            ("v18.17.0", Some("18.17.0")),
+            ("go version go1.21.3 linux/amd64", Some("1.21.3")),
+            ("go version go1.12 darwin/amd64", Some("1.12")),
+            (
+                "openjdk version \"17.0.2\" 2022-01-18\nOpenJDK Runtime Environment",
+                Some("17.0.2"),
+            ),
+            ("java version \"1.8.0_301\"", Some("1.8.0")),
+            (
+                "OpenJDK 64-Bit Server VM (13.0.2+8) for bsd-amd64 JRE (13.0.2+8)",
+                Some("13.0.2"),
+            ),
+            ("Python 3.11.4", Some("3.11.4")),
+            ("Python 2.7.17", Some("2.7.17")),
+            ("Python 3.6.10 :: Anaconda, Inc.", Some("3.6.10")),
+            (
+                "ruby 3.2.2 (2023-03-30 revision e51014f9c0) [x86_64-linux]",
+                Some("3.2.2"),
+            ),
+            (
+                "ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]",
+                Some("2.5.1"),
+            ),
+            ("rustc 1.34.0 (91856ed52 2019-04-10)", Some("1.34.0")),
+            (
+                "rustc 1.52.0-nightly (152f66092 2021-02-17)",
+                Some("1.52.0-nightly"),
+            ),
+            (
+                "rustc 1.34.0-beta.1 (2bc1d406d 2019-04-10)",
+                Some("1.34.0-beta.1"),
+            ),
+            ("v3.1.1+gafdfb8e", Some("3.1.1")),
+            ("Terraform v0.12.14", Some("0.12.14")),
+            (
+                "Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio",
+                Some("5.4.0"),
+            ),
+            (
+                "LuaJIT 2.0.5 -- Copyright (C) 2005-2017 Mike Pall.",
+                Some("2.0.5"),
+            ),
+            (
+                "Apple Swift version 5.2.2 (swiftlang-1103.0.32.6 clang-1103.0.32.51)",
+                Some("5.2.2"),
+            ),
+            (
+                "Dart VM version: 2.8.4 (stable) (Wed Jun 3 12:26:04 2020 +0200)",
+                Some("2.8.4"),
+            ),
+            (
+                "Crystal 0.35.1 (2020-06-19)\n\nLLVM: 10.0.0",
+                Some("0.35.1"),
+            ),
+            (
+                "cmake version 3.17.3\n\nCMake suite maintained",
+                Some("3.17.3"),
+            ),
+            ("Nim Compiler Version 1.2.0 [Linux: amd64]", Some("1.2.0")),
+            ("julia version 1.4.0", Some("1.4.0")),
+            ("3.1.103", Some("3.1.103")),
+            ("nightly", None),
+            ("", None),
+        ];
+
+        for (output, expected) in cases.iter() {
+            assert_eq!(parse_version(output), *expected, "{:?}", output);
+        }
+    }
+
     #[test]
     fn exec_mocked_command() {
-        let result = exec_cmd("dummy_command", &[]);
+        let result = exec_cmd("dummy_command", &[], Duration::from_millis(500));
         let expected = Some(CommandOutput {
-            stdout: String::from("stdout ok!\n"),
-            stderr: String::from("stderr ok!\n"),
+            stdout: String::from("stdout ok!"),
+            stderr: String::from("stderr ok!"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -286,10 +692,11 @@ mod tests {
 
     #[test]
     fn exec_no_output() {
-        let result = internal_exec_cmd("true", &[]);
+        let result = internal_exec_cmd("true", &[], Duration::from_millis(500));
         let expected = Some(CommandOutput {
             stdout: String::from(""),
             stderr: String::from(""),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -297,10 +704,12 @@ mod tests {
 
     #[test]
     fn exec_with_output_stdout() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello"]);
+        let result =
+            internal_exec_cmd("/bin/sh", &["-c", "echo hello"], Duration::from_millis(500));
         let expected = Some(CommandOutput {
-            stdout: String::from("hello\n"),
+            stdout: String::from("hello"),
             stderr: String::from(""),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -308,10 +717,15 @@ mod tests {
 
     #[test]
     fn exec_with_output_stderr() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello >&2"]);
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo hello >&2"],
+            Duration::from_millis(500),
+        );
         let expected = Some(CommandOutput {
             stdout: String::from(""),
-            stderr: String::from("hello\n"),
+            stderr: String::from("hello"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -319,10 +733,15 @@ mod tests {
 
     #[test]
     fn exec_with_output_both() {
-        let result = internal_exec_cmd("/bin/sh", &["-c", "echo hello; echo world >&2"]);
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo hello; echo world >&2"],
+            Duration::from_millis(500),
+        );
         let expected = Some(CommandOutput {
-            stdout: String::from("hello\n"),
-            stderr: String::from("world\n"),
+            stdout: String::from("hello"),
+            stderr: String::from("world"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -330,12 +749,99 @@ mod tests {
 
     #[test]
     fn exec_with_non_zero_exit_code() {
-        let result = internal_exec_cmd("false", &[]);
+        let result = internal_exec_cmd("false", &[], Duration::from_millis(500));
+        let expected = Some(CommandOutput {
+            stdout: String::from(""),
+            stderr: String::from(""),
+            status: Some(1),
+        });
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn exec_with_non_zero_exit_code_and_output() {
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo error >&2; exit 3"],
+            Duration::from_millis(500),
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::from(""),
+            stderr: String::from("error"),
+            status: Some(3),
+        });
+
+        assert_eq!(result, expected);
+        assert!(!result.unwrap().success());
+    }
+
+    #[test]
+    fn exec_trims_single_trailing_newline() {
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "printf 'hello\\n\\n'; printf 'world\\r\\n' >&2"],
+            Duration::from_millis(500),
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::from("hello\n"),
+            stderr: String::from("world"),
+            status: Some(0),
+        });
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn exec_with_slow_command() {
+        let result = internal_exec_cmd("sleep", &["2"], Duration::from_millis(500));
         let expected = None;
 
         assert_eq!(result, expected)
     }
 
+    #[test]
+    fn test_render_time_500ms() {
+        assert_eq!(render_time(500, false), "500ms")
+    }
+    #[test]
+    fn test_render_time_10s() {
+        assert_eq!(render_time(10_000, true), "10s")
+    }
+    #[test]
+    fn test_render_time_90s() {
+        assert_eq!(render_time(90_000, true), "1m30s")
+    }
+    #[test]
+    fn test_render_time_151s_with_millis() {
+        assert_eq!(render_time(151_250, false), "2m31s");
+        assert_eq!(render_time(151_250, true), "2m31s250ms");
+    }
+    #[test]
+    fn test_render_time_10110s() {
+        assert_eq!(render_time(10_110_000, true), "2h48m30s")
+    }
+    #[test]
+    fn test_render_time_1d() {
+        assert_eq!(render_time(86_400_000, true), "1d")
+    }
+    #[test]
+    fn test_render_time_3d2h() {
+        assert_eq!(render_time(266_400_000, false), "3d2h")
+    }
+
+    #[test]
+    fn test_escape_text_for_shell() {
+        let text = "50% done! $HOME";
+        assert_eq!(escape_text_for_shell(text, Shell::Bash), text);
+        assert_eq!(escape_text_for_shell(text, Shell::Fish), text);
+        assert_eq!(escape_text_for_shell(text, Shell::Zsh), "50%% done! $HOME");
+        assert_eq!(
+            escape_text_for_shell(text, Shell::Tcsh),
+            "50%% done\\! $HOME"
+        );
+    }
+
     #[test]
     fn test_color_sequence_wrappers() {
         let test0 = "\x1b2mhellomynamekeyes\x1b2m"; // BEGIN: \x1b     END: m
@@ -373,4 +879,74 @@ mod tests {
         assert_eq!(&bresult4, "herpaderp");
         assert_eq!(&bresult5, "");
     }
+
+    #[test]
+    fn test_humanize_age() {
+        let now = 1_600_000_000;
+
+        assert_eq!(humanize_age(now, now), "now");
+        assert_eq!(humanize_age(now - 59, now), "now");
+        assert_eq!(humanize_age(now + 120, now), "now");
+        assert_eq!(humanize_age(now - 60, now), "1m");
+        assert_eq!(humanize_age(now - 5 * 3600 - 1, now), "5h");
+        assert_eq!(humanize_age(now - 3 * 86400, now), "3d");
+        assert_eq!(humanize_age(now - 29 * 86400, now), "29d");
+        assert_eq!(humanize_age(now - 65 * 86400, now), "2mo");
+        assert_eq!(humanize_age(now - 400 * 86400, now), "1y");
+        assert_eq!(humanize_age(0, now), "50y");
+    }
+
+    #[test]
+    fn local_ipv4_of_loopback_route() {
+        let probe = "127.0.0.1:9".parse().unwrap();
+        assert_eq!(get_local_ipv4(probe), Some(Ipv4Addr::LOCALHOST));
+    }
+
+    #[test]
+    fn parse_os_release_with_quotes() {
+        let os_release = "\
+NAME=\"Ubuntu\"
+VERSION=\"20.04.1 LTS (Focal Fossa)\"
+ID=ubuntu
+ID_LIKE=debian
+PRETTY_NAME=\"Ubuntu 20.04.1 LTS\"
+VERSION_ID=\"20.04\"
+";
+        let expected = OsInfo {
+            os_type: String::from("ubuntu"),
+            name: String::from("Ubuntu"),
+            version: Some(String::from("20.04")),
+            wsl: true,
+        };
+        assert_eq!(parse_os_release(os_release, true), expected);
+    }
+
+    #[test]
+    fn parse_os_release_without_version() {
+        let os_release = "\
+# rolling release
+NAME='Arch Linux'
+ID=arch
+BUILD_ID=rolling
+VERSION_ID=
+";
+        let expected = OsInfo {
+            os_type: String::from("arch"),
+            name: String::from("Arch Linux"),
+            version: None,
+            wsl: false,
+        };
+        assert_eq!(parse_os_release(os_release, false), expected);
+    }
+
+    #[test]
+    fn parse_empty_os_release() {
+        let expected = OsInfo {
+            os_type: String::from("linux"),
+            name: String::from("Linux"),
+            version: None,
+            wsl: false,
+        };
+        assert_eq!(parse_os_release("", false), expected);
+    }
 }